@@ -0,0 +1,157 @@
+use super::Severity;
+use crate::filter::{Version, VersionConstraint};
+
+/// Range of affected versions as defined by OSV events
+#[derive(Debug, Clone, Default)]
+pub struct VersionRange {
+    pub introduced: Option<Version>,
+    /// First version that is no longer affected
+    pub fixed: Option<Version>,
+    /// Last version that is still affected
+    pub last_affected: Option<Version>,
+}
+
+impl VersionRange {
+    fn contains(&self, version: &Version) -> bool {
+        self.introduced.as_ref().is_none_or(|v| version >= v)
+            && self.fixed.as_ref().is_none_or(|v| version < v)
+            && self.last_affected.as_ref().is_none_or(|v| version <= v)
+    }
+}
+
+/// Versions affected by an advisory
+#[derive(Debug, Clone)]
+pub enum Affected {
+    /// Versions matching the constraint (e.g. `>=10.0,<10.4.20`)
+    Constraint(VersionConstraint),
+    /// Versions in one of the ranges or listed explicitly
+    Ranges {
+        ranges: Vec<VersionRange>,
+        versions: Vec<String>,
+    },
+}
+
+/// Security advisory for a package
+#[derive(Debug, Clone)]
+pub struct Advisory {
+    pub id: String,
+    /// Key of the affected package
+    pub package: String,
+    /// OSV ecosystem of the package (e.g. `Packagist` or `Debian:12`), any
+    /// ecosystem if `None`
+    pub ecosystem: Option<String>,
+    pub summary: String,
+    pub severity: Severity,
+    pub affected: Affected,
+}
+
+impl Advisory {
+    /// Check if the advisory belongs to one of the ecosystems
+    ///
+    /// The release suffix of the advisory's ecosystem (e.g. `:12` in
+    /// `Debian:12`) is ignored
+    pub fn matches_ecosystem(&self, ecosystems: &[&str]) -> bool {
+        match &self.ecosystem {
+            Some(ecosystem) => {
+                let name = ecosystem.split(':').next().unwrap_or_default();
+                ecosystems.iter().any(|e| e.eq_ignore_ascii_case(name))
+            }
+            None => true,
+        }
+    }
+
+    /// Check if the advisory applies to the version of the package
+    pub fn affects(&self, version: &str) -> bool {
+        match &self.affected {
+            Affected::Constraint(constraint) => constraint.matches(version),
+            Affected::Ranges { ranges, versions } => {
+                if versions.iter().any(|v| v == version) {
+                    return true;
+                }
+
+                match Version::parse(version) {
+                    Some(version) => {
+                        ranges.iter().any(|range| range.contains(&version))
+                    }
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(source: &str) -> Option<Version> {
+        Some(Version::parse(source).unwrap())
+    }
+
+    fn build_advisory(affected: Affected) -> Advisory {
+        Advisory {
+            id: "TYPO3-CORE-SA-2021-013".to_owned(),
+            package: "typo3/cms-core".to_owned(),
+            ecosystem: Some("Packagist".to_owned()),
+            summary: "Cross-Site Scripting".to_owned(),
+            severity: Severity::Medium,
+            affected,
+        }
+    }
+
+    #[test]
+    fn affects_constraint_test() {
+        let advisory = build_advisory(Affected::Constraint(
+            VersionConstraint::parse(">=10.0,<10.4.20").unwrap(),
+        ));
+
+        assert!(advisory.affects("10.4.19"));
+        assert!(!advisory.affects("10.4.20"));
+        assert!(!advisory.affects("9.5.30"));
+    }
+
+    #[test]
+    fn affects_ranges_test() {
+        let advisory = build_advisory(Affected::Ranges {
+            ranges: vec![
+                VersionRange {
+                    introduced: version("9.0.0"),
+                    fixed: version("9.5.30"),
+                    last_affected: None,
+                },
+                VersionRange {
+                    introduced: version("10.0.0"),
+                    fixed: None,
+                    last_affected: version("10.4.19"),
+                },
+            ],
+            versions: vec!["dev-main".to_owned()],
+        });
+
+        assert!(advisory.affects("9.5.29"));
+        assert!(!advisory.affects("9.5.30"));
+        assert!(advisory.affects("10.4.19"));
+        assert!(!advisory.affects("10.4.20"));
+        assert!(!advisory.affects("8.7.40"));
+        assert!(advisory.affects("dev-main"));
+        assert!(!advisory.affects("dev-feature"));
+    }
+
+    #[test]
+    fn matches_ecosystem_test() {
+        let mut advisory = build_advisory(Affected::Ranges {
+            ranges: vec![],
+            versions: vec![],
+        });
+        assert!(advisory.matches_ecosystem(&["Packagist"]));
+        assert!(!advisory.matches_ecosystem(&["Debian"]));
+        assert!(!advisory.matches_ecosystem(&[]));
+
+        advisory.ecosystem = Some("Debian:12".to_owned());
+        assert!(advisory.matches_ecosystem(&["Debian"]));
+        assert!(!advisory.matches_ecosystem(&["Packagist"]));
+
+        advisory.ecosystem = None;
+        assert!(advisory.matches_ecosystem(&[]));
+    }
+}
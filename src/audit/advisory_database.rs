@@ -0,0 +1,602 @@
+use super::{Advisory, Affected, Severity, VersionRange};
+use crate::constants;
+use crate::error::Error;
+use crate::filter::{Version, VersionConstraint};
+use crate::information::{Information, InformationCollection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Advisory that applies to a package installed on a host
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditFinding {
+    pub package: String,
+    pub version: String,
+    pub advisory: String,
+    pub severity: Severity,
+    pub summary: String,
+}
+
+/// Findings of each host (hosts without findings have an empty list)
+pub type AuditReport = BTreeMap<String, Vec<AuditFinding>>;
+
+/// Collection of advisories loaded from local files
+///
+/// Two formats are supported:
+///
+/// - OSV JSON files (a single advisory or a list of advisories per file)
+/// - simple YAML or JSON files with a list of advisories, each with an `id`, a
+///   `package` key, the `affected` version constraint and optionally the
+///   `ecosystem`, the `severity` and a `summary`
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    advisories: Vec<Advisory>,
+    /// Problems with advisories that were loaded nonetheless
+    warnings: Vec<String>,
+}
+
+impl AdvisoryDatabase {
+    /// Load the advisories from the file or all files in the directory
+    /// (including subdirectories)
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut files = vec![];
+        if path.is_dir() {
+            collect_files(path, &mut files)?;
+        } else {
+            files.push(path.to_path_buf());
+        }
+
+        let mut advisories = vec![];
+        let mut warnings = vec![];
+        for file in files {
+            let (file_advisories, file_warnings) =
+                load_file(&file).map_err(|e| {
+                    Error::new(format!(
+                        "Could not load the advisory file '{}': {}",
+                        file.to_string_lossy(),
+                        e
+                    ))
+                })?;
+            advisories.extend(file_advisories);
+            warnings.extend(file_warnings.into_iter().map(|warning| {
+                format!("{}: {}", file.to_string_lossy(), warning)
+            }));
+        }
+
+        Ok(AdvisoryDatabase {
+            advisories,
+            warnings,
+        })
+    }
+
+    #[cfg(all(test, feature = "yaml"))]
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Check the packages of every host against the advisories
+    ///
+    /// Only advisories of the host's package ecosystems are applied (see
+    /// [`host_ecosystems`])
+    pub fn audit(
+        &self,
+        information_collection: &InformationCollection,
+    ) -> AuditReport {
+        let mut advisories_by_package: BTreeMap<&str, Vec<&Advisory>> =
+            BTreeMap::new();
+        for advisory in &self.advisories {
+            advisories_by_package
+                .entry(advisory.package.as_str())
+                .or_default()
+                .push(advisory);
+        }
+
+        let mut report = AuditReport::new();
+        for (host, information) in information_collection {
+            let ecosystems = host_ecosystems(information);
+            let mut findings = vec![];
+            for package in information.packages.all.values() {
+                let advisories =
+                    match advisories_by_package.get(package.key.as_str()) {
+                        Some(advisories) => advisories,
+                        None => continue,
+                    };
+                for advisory in advisories {
+                    if advisory.matches_ecosystem(&ecosystems)
+                        && advisory.affects(&package.version)
+                    {
+                        findings.push(AuditFinding {
+                            package: package.key.clone(),
+                            version: package.version.clone(),
+                            advisory: advisory.id.clone(),
+                            severity: advisory.severity,
+                            summary: advisory.summary.clone(),
+                        });
+                    }
+                }
+            }
+
+            // Most severe first
+            findings.sort_by(|a, b| {
+                b.severity
+                    .cmp(&a.severity)
+                    .then_with(|| a.package.cmp(&b.package))
+                    .then_with(|| a.advisory.cmp(&b.advisory))
+            });
+            report.insert(host.clone(), findings);
+        }
+
+        report
+    }
+}
+
+/// Return the OSV ecosystems of the host's packages
+///
+/// Hosts with the fleet provider report their system packages, whose
+/// ecosystem is derived from the distribution. Other providers report the
+/// Composer packages of the application (`Packagist`)
+fn host_ecosystems(information: &Information) -> Vec<&'static str> {
+    if information.fleet.provider_name != constants::PROVIDER_NAME {
+        return vec!["Packagist"];
+    }
+
+    let distribution = information.facts.get_text("distribution.id");
+    match distribution.as_deref() {
+        Some("debian") => vec!["Debian"],
+        Some("ubuntu") => vec!["Ubuntu"],
+        Some("rhel" | "centos") => vec!["Red Hat"],
+        Some("rocky") => vec!["Rocky Linux"],
+        Some("almalinux") => vec!["AlmaLinux"],
+        _ => vec![],
+    }
+}
+
+fn collect_files(
+    directory: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(directory)? {
+        entries.push(entry?.path());
+    }
+    entries.sort();
+
+    for path in entries {
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if is_hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("json" | "yaml" | "yml")
+        ) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Advisories and warnings of a file
+type Advisories = (Vec<Advisory>, Vec<String>);
+
+fn load_file(path: &Path) -> Result<Advisories, Error> {
+    let content = fs::read_to_string(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => parse_json(&content),
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => match serde_yaml::from_str(&content) {
+            Ok(file) => parse_simple(file).map(|a| (a, vec![])),
+            Err(e) => Err(Error::new(e.to_string())),
+        },
+        #[cfg(not(feature = "yaml"))]
+        Some("yaml" | "yml") => Err(Error::new(
+            "YAML support is not enabled (build with the \"yaml\" feature)",
+        )),
+        _ => Err(Error::new(
+            "Unsupported file format (expected JSON or YAML)",
+        )),
+    }
+}
+
+fn parse_json(content: &str) -> Result<Advisories, Error> {
+    let value: serde_json::Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(e) => return Err(Error::new(e.to_string())),
+    };
+
+    // OSV advisories contain a list of `affected` packages, simple advisories
+    // a version constraint
+    let is_osv = |value: &serde_json::Value| {
+        value.get("affected").is_some_and(|a| a.is_array())
+    };
+    let osv_advisories = match &value {
+        serde_json::Value::Array(values) if values.iter().all(is_osv) => {
+            serde_json::from_value::<Vec<OsvAdvisory>>(value)
+        }
+        v if is_osv(v) => {
+            serde_json::from_value::<OsvAdvisory>(value).map(|a| vec![a])
+        }
+        _ => {
+            return match serde_json::from_value(value) {
+                Ok(file) => parse_simple(file).map(|a| (a, vec![])),
+                Err(e) => Err(Error::new(e.to_string())),
+            }
+        }
+    };
+
+    let mut advisories = vec![];
+    let mut warnings = vec![];
+    for osv_advisory in osv_advisories.map_err(|e| Error::new(e.to_string()))? {
+        let (osv_advisories, osv_warnings) = osv_advisory.into_advisories();
+        advisories.extend(osv_advisories);
+        warnings.extend(osv_warnings);
+    }
+
+    Ok((advisories, warnings))
+}
+
+fn parse_simple(file: SimpleFile) -> Result<Vec<Advisory>, Error> {
+    let advisories = match file {
+        SimpleFile::List(advisories) => advisories,
+        SimpleFile::Wrapped { advisories } => advisories,
+    };
+
+    advisories
+        .into_iter()
+        .map(|advisory| {
+            let constraint = VersionConstraint::parse(&advisory.affected)
+                .map_err(|e| {
+                    Error::new(format!("Advisory {}: {}", advisory.id, e))
+                })?;
+
+            Ok(Advisory {
+                id: advisory.id,
+                package: advisory.package,
+                ecosystem: advisory.ecosystem,
+                summary: advisory.summary,
+                severity: advisory
+                    .severity
+                    .as_deref()
+                    .map_or(Severity::Unknown, Severity::parse),
+                affected: Affected::Constraint(constraint),
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SimpleFile {
+    List(Vec<SimpleAdvisory>),
+    Wrapped { advisories: Vec<SimpleAdvisory> },
+}
+
+#[derive(Deserialize)]
+struct SimpleAdvisory {
+    id: String,
+    package: String,
+    affected: String,
+    ecosystem: Option<String>,
+    severity: Option<String>,
+    #[serde(default)]
+    summary: String,
+}
+
+#[derive(Deserialize)]
+struct OsvAdvisory {
+    id: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    severity: Vec<OsvSeverity>,
+    database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct OsvAffected {
+    package: OsvPackage,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+    ecosystem_specific: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    range_type: String,
+    #[serde(default)]
+    events: Vec<OsvEvent>,
+}
+
+#[derive(Deserialize)]
+struct OsvEvent {
+    introduced: Option<String>,
+    fixed: Option<String>,
+    last_affected: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OsvSeverity {
+    #[serde(rename = "type")]
+    severity_type: String,
+    score: String,
+}
+
+impl OsvAdvisory {
+    /// Convert the OSV advisory into one advisory per affected package
+    ///
+    /// Ranges with versions that can not be parsed are skipped with a warning
+    fn into_advisories(self) -> Advisories {
+        let severity_of = |value: &Option<serde_json::Value>| {
+            value
+                .as_ref()
+                .and_then(|v| v.get("severity"))
+                .and_then(|s| s.as_str())
+                .map(Severity::parse)
+        };
+        let cvss_severity = self
+            .severity
+            .iter()
+            .filter(|s| s.severity_type == "CVSS_V3")
+            .find_map(|s| Severity::from_cvss_v3(&s.score));
+        let advisory_severity =
+            severity_of(&self.database_specific).or(cvss_severity);
+
+        let mut advisories = vec![];
+        let mut warnings = vec![];
+        for affected in self.affected {
+            let mut ranges = vec![];
+            for range in
+                affected.ranges.iter().filter(|r| r.range_type != "GIT")
+            {
+                let (valid_ranges, invalid_versions) =
+                    build_ranges(&range.events);
+                ranges.extend(valid_ranges);
+                warnings.extend(invalid_versions.into_iter().map(|version| {
+                    format!(
+                        "Advisory {}: Skipped a range of {} with the \
+                         unsupported version '{}'",
+                        self.id, affected.package.name, version
+                    )
+                }));
+            }
+
+            advisories.push(Advisory {
+                id: self.id.clone(),
+                package: affected.package.name,
+                ecosystem: Some(affected.package.ecosystem),
+                summary: self.summary.clone(),
+                severity: severity_of(&affected.ecosystem_specific)
+                    .or(advisory_severity)
+                    .unwrap_or(Severity::Unknown),
+                affected: Affected::Ranges {
+                    ranges,
+                    versions: affected.versions,
+                },
+            });
+        }
+
+        (advisories, warnings)
+    }
+}
+
+/// Build the version ranges from the OSV events
+///
+/// Each `introduced` event starts a range, which is closed by the following
+/// `fixed` or `last_affected` event. Ranges with a version that can not be
+/// parsed are skipped, because an open bound would match too many versions.
+/// The invalid versions are returned as the second element
+fn build_ranges(events: &[OsvEvent]) -> (Vec<VersionRange>, Vec<String>) {
+    let mut invalid_versions = vec![];
+    let mut parse = |version: &str| {
+        let parsed = Version::parse(version);
+        if parsed.is_none() {
+            invalid_versions.push(version.to_owned());
+        }
+        parsed.ok_or(())
+    };
+
+    let mut ranges = vec![];
+    let mut current: Option<Result<VersionRange, ()>> = None;
+    for event in events {
+        if let Some(introduced) = &event.introduced {
+            ranges.extend(current.take().and_then(Result::ok));
+            current = Some(parse(introduced).map(|version| VersionRange {
+                introduced: Some(version),
+                ..Default::default()
+            }));
+        } else if let Some(range) = current.take() {
+            let range = range.and_then(|mut range| {
+                if let Some(fixed) = &event.fixed {
+                    range.fixed = Some(parse(fixed)?);
+                } else if let Some(last_affected) = &event.last_affected {
+                    range.last_affected = Some(parse(last_affected)?);
+                }
+                Ok(range)
+            });
+            ranges.extend(range.ok());
+        }
+    }
+    ranges.extend(current.and_then(Result::ok));
+
+    (ranges, invalid_versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "yaml")]
+    fn build_collection() -> InformationCollection {
+        use crate::information::{Package, Packages};
+        use std::collections::HashMap;
+
+        let hosts: &[(&str, &[(&str, &str)])] = &[
+            ("old", &[("typo3/cms-core", "10.4.3"), ("news", "8.5.0")]),
+            ("new", &[("typo3/cms-core", "10.4.20"), ("news", "9.0.0")]),
+        ];
+
+        let mut collection = InformationCollection::new();
+        for (host, packages) in hosts {
+            let mut information = Information::new_for_current_env();
+            information.fleet.provider_name = "fleet-typo3".to_owned();
+            information.packages = Packages::new_with_packages(
+                packages
+                    .iter()
+                    .map(|(key, version)| {
+                        (
+                            key.to_string(),
+                            Package {
+                                key: key.to_string(),
+                                version: version.to_string(),
+                                description: "".to_owned(),
+                                state: "active".to_owned(),
+                            },
+                        )
+                    })
+                    .collect::<HashMap<_, _>>(),
+            );
+            collection.insert(host.to_string(), information);
+        }
+
+        collection
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn load_directory_test() {
+        let database = AdvisoryDatabase::load(
+            &crate::test_helpers::get_test_resource_path("advisories"),
+        )
+        .unwrap();
+        assert_eq!(3, database.len());
+        assert!(database.warnings().is_empty());
+
+        let report = database.audit(&build_collection());
+        assert!(report["new"].is_empty());
+
+        let findings = &report["old"];
+        assert_eq!(2, findings.len());
+        assert_eq!("GHSA-xxxx-typo3-core", findings[0].advisory);
+        assert_eq!(Severity::High, findings[0].severity);
+        assert_eq!("10.4.3", findings[0].version);
+        assert_eq!("NEWS-2023-001", findings[1].advisory);
+        assert_eq!(Severity::Medium, findings[1].severity);
+    }
+
+    #[test]
+    fn parse_json_simple_test() {
+        let (advisories, _) = parse_json(
+            r#"[{"id": "A-1", "package": "news", "affected": "<9"}]"#,
+        )
+        .unwrap();
+        assert_eq!(1, advisories.len());
+        assert_eq!(Severity::Unknown, advisories[0].severity);
+        assert!(advisories[0].affects("8.5.0"));
+
+        assert!(parse_json(
+            r#"[{"id": "A-1", "package": "news", "affected": "latest"}]"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_osv_cvss_test() {
+        let (advisories, warnings) = parse_json(
+            r#"{
+                "id": "OSV-1",
+                "affected": [{
+                    "package": {"ecosystem": "Packagist", "name": "news"},
+                    "ranges": [{"type": "ECOSYSTEM", "events": [
+                        {"introduced": "0"}, {"fixed": "9.0.0"}
+                    ]}]
+                }],
+                "severity": [{
+                    "type": "CVSS_V3",
+                    "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(1, advisories.len());
+        assert!(warnings.is_empty());
+        assert_eq!(Some("Packagist"), advisories[0].ecosystem.as_deref());
+        assert_eq!(Severity::Critical, advisories[0].severity);
+        assert!(advisories[0].affects("8.5.0"));
+        assert!(!advisories[0].affects("9.0.0"));
+    }
+
+    #[test]
+    fn build_ranges_test() {
+        let event = |introduced: Option<&str>, fixed: Option<&str>| OsvEvent {
+            introduced: introduced.map(str::to_owned),
+            fixed: fixed.map(str::to_owned),
+            last_affected: None,
+        };
+        let (ranges, invalid_versions) = build_ranges(&[
+            event(Some("0"), None),
+            event(None, Some("9.5.30")),
+            event(Some("10.0.0"), None),
+        ]);
+
+        assert_eq!(2, ranges.len());
+        assert!(invalid_versions.is_empty());
+        assert_eq!(Version::parse("9.5.30"), ranges[0].fixed);
+        assert_eq!(Version::parse("10.0.0"), ranges[1].introduced);
+        assert_eq!(None, ranges[1].fixed);
+
+        let (ranges, invalid_versions) = build_ranges(&[
+            event(Some("unknown"), None),
+            event(None, Some("2.3")),
+            event(Some("3.0"), None),
+            event(None, Some("3.0.x")),
+            event(Some("4.0"), None),
+            event(None, Some("4.2")),
+        ]);
+
+        assert_eq!(1, ranges.len());
+        assert_eq!(vec!["unknown", "3.0.x"], invalid_versions);
+        assert_eq!(Version::parse("4.0"), ranges[0].introduced);
+        assert_eq!(Version::parse("4.2"), ranges[0].fixed);
+    }
+
+    #[test]
+    fn host_ecosystems_test() {
+        let mut information = Information::new_for_current_env();
+        assert!(host_ecosystems(&information).is_empty());
+
+        information.facts.insert(
+            "distribution",
+            serde_json::json!({"id": "debian", "version": "12"}),
+        );
+        assert_eq!(vec!["Debian"], host_ecosystems(&information));
+
+        information.fleet.provider_name = "fleet-typo3".to_owned();
+        assert_eq!(vec!["Packagist"], host_ecosystems(&information));
+    }
+}
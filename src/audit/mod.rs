@@ -0,0 +1,7 @@
+mod advisory;
+mod advisory_database;
+mod severity;
+
+pub use self::advisory::{Advisory, Affected, VersionRange};
+pub use self::advisory_database::{AdvisoryDatabase, AuditReport};
+pub use self::severity::Severity;
@@ -0,0 +1,193 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Severity of an advisory
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Parse the severity name (case-insensitive, `moderate` is `medium`)
+    ///
+    /// Unknown names result in [`Severity::Unknown`]
+    pub fn parse(source: &str) -> Self {
+        match source.trim().to_lowercase().as_str() {
+            "low" => Severity::Low,
+            "medium" | "moderate" => Severity::Medium,
+            "high" => Severity::High,
+            "critical" => Severity::Critical,
+            _ => Severity::Unknown,
+        }
+    }
+
+    /// Return the severity rating of a CVSS base score
+    pub fn from_score(score: f64) -> Self {
+        if score >= 9.0 {
+            Severity::Critical
+        } else if score >= 7.0 {
+            Severity::High
+        } else if score >= 4.0 {
+            Severity::Medium
+        } else if score > 0.0 {
+            Severity::Low
+        } else {
+            Severity::Unknown
+        }
+    }
+
+    /// Return the severity of the CVSS v3 vector
+    /// (e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`)
+    pub fn from_cvss_v3(vector: &str) -> Option<Self> {
+        cvss_v3_base_score(vector).map(Severity::from_score)
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::Unknown => "unknown",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Calculate the base score of the CVSS v3 vector
+fn cvss_v3_base_score(vector: &str) -> Option<f64> {
+    if !vector.starts_with("CVSS:3") {
+        return None;
+    }
+
+    let metric = |name: &str| -> Option<&str> {
+        vector
+            .split('/')
+            .find_map(|m| m.strip_prefix(name)?.strip_prefix(':'))
+    };
+    let scope_changed = match metric("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let attack_vector = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges_required = match (metric("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_metric = |name: &str| -> Option<f64> {
+        match metric(name)? {
+            "H" => Some(0.56),
+            "L" => Some(0.22),
+            "N" => Some(0.0),
+            _ => None,
+        }
+    };
+    let confidentiality = impact_metric("C")?;
+    let integrity = impact_metric("I")?;
+    let availability = impact_metric("A")?;
+
+    let impact_sub_score = 1.0
+        - (1.0 - confidentiality) * (1.0 - integrity) * (1.0 - availability);
+    let impact = if scope_changed {
+        7.52 * (impact_sub_score - 0.029)
+            - 3.25 * (impact_sub_score - 0.02).powi(15)
+    } else {
+        6.42 * impact_sub_score
+    };
+    let exploitability = 8.22
+        * attack_vector
+        * attack_complexity
+        * privileges_required
+        * user_interaction;
+
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+
+    Some(if scope_changed {
+        round_up((1.08 * (impact + exploitability)).min(10.0))
+    } else {
+        round_up((impact + exploitability).min(10.0))
+    })
+}
+
+/// Round up to one decimal as defined in CVSS v3.1
+fn round_up(value: f64) -> f64 {
+    let integer = (value * 100_000.0).round() as u64;
+    if integer.is_multiple_of(10_000) {
+        integer as f64 / 100_000.0
+    } else {
+        (integer / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(Severity::High, Severity::parse("HIGH"));
+        assert_eq!(Severity::Medium, Severity::parse("moderate"));
+        assert_eq!(Severity::Critical, Severity::parse(" Critical "));
+        assert_eq!(Severity::Unknown, Severity::parse("severe"));
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::Low > Severity::Unknown);
+    }
+
+    #[test]
+    fn cvss_v3_base_score_test() {
+        let score = |vector| cvss_v3_base_score(vector).unwrap();
+        assert_eq!(9.8, score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"));
+        assert_eq!(6.1, score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"));
+        assert_eq!(5.4, score("CVSS:3.0/AV:N/AC:L/PR:L/UI:R/S:C/C:L/I:L/A:N"));
+        assert_eq!(0.0, score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"));
+        assert_eq!(None, cvss_v3_base_score("AV:N/AC:L/Au:N/C:P/I:P/A:P"));
+        assert_eq!(None, cvss_v3_base_score("CVSS:3.1/AV:N"));
+    }
+
+    #[test]
+    fn from_cvss_v3_test() {
+        assert_eq!(
+            Some(Severity::Critical),
+            Severity::from_cvss_v3(
+                "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+            )
+        );
+        assert_eq!(
+            Some(Severity::Medium),
+            Severity::from_cvss_v3(
+                "CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"
+            )
+        );
+    }
+}
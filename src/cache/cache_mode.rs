@@ -0,0 +1,85 @@
+use super::CacheEntry;
+use std::time::Duration;
+
+/// Defines if cached information may be used instead of querying the hosts
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum CacheMode {
+    /// Always query the hosts (and update the cache)
+    #[default]
+    Live,
+    /// Use cached information that is not older than the given age and query
+    /// the other hosts
+    MaxAge(Duration),
+    /// Only use cached information (optionally limited to the given age)
+    Offline(Option<Duration>),
+}
+
+impl CacheMode {
+    /// Build the mode from the `--offline` and `--max-age` arguments
+    pub fn from_arguments(offline: bool, max_age: Option<u64>) -> Self {
+        let max_age = max_age.map(Duration::from_secs);
+        match (offline, max_age) {
+            (true, max_age) => CacheMode::Offline(max_age),
+            (false, Some(max_age)) => CacheMode::MaxAge(max_age),
+            (false, None) => CacheMode::Live,
+        }
+    }
+
+    /// Return if the hosts may be queried
+    pub fn is_offline(&self) -> bool {
+        matches!(self, CacheMode::Offline(_))
+    }
+
+    /// Return if the cached entry may be used
+    pub fn accepts(&self, entry: &CacheEntry) -> bool {
+        match self {
+            CacheMode::Live => false,
+            CacheMode::MaxAge(max_age) => entry.age() <= *max_age,
+            CacheMode::Offline(Some(max_age)) => entry.age() <= *max_age,
+            CacheMode::Offline(None) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::information::Information;
+    use std::time::SystemTime;
+
+    fn build_entry(age: u64) -> CacheEntry {
+        CacheEntry::new(
+            SystemTime::now() - Duration::from_secs(age),
+            Information::new_for_current_env(),
+        )
+    }
+
+    #[test]
+    fn from_arguments_test() {
+        assert_eq!(CacheMode::Live, CacheMode::from_arguments(false, None));
+        assert_eq!(
+            CacheMode::MaxAge(Duration::from_secs(60)),
+            CacheMode::from_arguments(false, Some(60))
+        );
+        assert_eq!(
+            CacheMode::Offline(None),
+            CacheMode::from_arguments(true, None)
+        );
+        assert_eq!(
+            CacheMode::Offline(Some(Duration::from_secs(60))),
+            CacheMode::from_arguments(true, Some(60))
+        );
+    }
+
+    #[test]
+    fn accepts_test() {
+        let fresh = build_entry(10);
+        let stale = build_entry(3600);
+
+        assert!(!CacheMode::Live.accepts(&fresh));
+        assert!(CacheMode::from_arguments(false, Some(60)).accepts(&fresh));
+        assert!(!CacheMode::from_arguments(false, Some(60)).accepts(&stale));
+        assert!(CacheMode::from_arguments(true, None).accepts(&stale));
+        assert!(!CacheMode::from_arguments(true, Some(60)).accepts(&stale));
+    }
+}
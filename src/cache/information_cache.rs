@@ -0,0 +1,188 @@
+use crate::configuration::configuration_namespace;
+use crate::error::Error;
+use crate::information::Information;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Cached information of a host
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CacheEntry {
+    /// Unix timestamp of the moment the information was fetched
+    timestamp: u64,
+    information: Information,
+}
+
+impl CacheEntry {
+    pub fn new(time: SystemTime, information: Information) -> Self {
+        CacheEntry {
+            timestamp: time
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            information,
+        }
+    }
+
+    /// Return the time the information was fetched
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.timestamp)
+    }
+
+    /// Return the time that passed since the information was fetched
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.time())
+            .unwrap_or_default()
+    }
+
+    pub fn into_information(self) -> Information {
+        self.information
+    }
+}
+
+/// Persistent cache of the last information fetched from each host
+///
+/// Each host is stored as a JSON file named after the host's configuration key
+/// in a directory of the configuration file
+pub struct InformationCache {
+    directory: PathBuf,
+}
+
+impl InformationCache {
+    pub fn new(directory: PathBuf) -> Self {
+        InformationCache { directory }
+    }
+
+    /// Open the cache of the configuration file in the user's cache directory
+    /// (e.g. `$XDG_CACHE_HOME/fleet/information/fleet-6c5fe1d7a0b2c413`)
+    pub fn open_default(configuration_file: &Path) -> Result<Self, Error> {
+        match dirs::cache_dir() {
+            Some(mut directory) => {
+                directory.push("fleet");
+                directory.push("information");
+                directory.push(configuration_namespace(configuration_file));
+
+                Ok(InformationCache::new(directory))
+            }
+            None => Err(Error::new("Could not detect the cache directory")),
+        }
+    }
+
+    /// Return the cached entry for the host, if one exists
+    pub fn get(&self, host: &str) -> Result<Option<CacheEntry>, Error> {
+        let path = self.path_for_host(host);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        match serde_json::from_str(&content) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => Err(Error::new(format!(
+                "Could not read the cache file '{}': {}",
+                path.to_string_lossy(),
+                e
+            ))),
+        }
+    }
+
+    /// Store the information for the host with the current time
+    pub fn store(
+        &self,
+        host: &str,
+        information: &Information,
+    ) -> Result<(), Error> {
+        let entry = CacheEntry::new(SystemTime::now(), information.clone());
+        let content = match serde_json::to_string(&entry) {
+            Ok(content) => content,
+            Err(e) => return Err(Error::from_error(&e)),
+        };
+
+        fs::create_dir_all(&self.directory)?;
+
+        // Write to a temporary file first, so readers never see partial data
+        let path = self.path_for_host(host);
+        let temporary_path = path.with_extension("json.tmp");
+        fs::write(&temporary_path, content)?;
+        fs::rename(&temporary_path, &path)?;
+
+        Ok(())
+    }
+
+    fn path_for_host(&self, host: &str) -> PathBuf {
+        self.directory
+            .join(format!("{}.json", encode_file_name(host)))
+    }
+}
+
+/// Encode the host so it can be used as file name
+///
+/// Characters other than ASCII letters, digits, `-`, `_` and `.` are
+/// percent-encoded
+fn encode_file_name(host: &str) -> String {
+    let mut encoded = String::with_capacity(host.len());
+    for byte in host.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => {
+                encoded.push(byte as char)
+            }
+            b'.' if !encoded.is_empty() => encoded.push('.'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_cache(name: &str) -> InformationCache {
+        let directory = std::env::temp_dir().join(format!(
+            "fleet-cache-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+
+        InformationCache::new(directory)
+    }
+
+    #[test]
+    fn store_and_get_test() {
+        let cache = build_cache("store");
+        assert_eq!(None, cache.get("my.host").unwrap());
+
+        let information = Information::new_for_current_env();
+        cache.store("my.host", &information).unwrap();
+
+        let entry = cache.get("my.host").unwrap().unwrap();
+        assert!(entry.age() < Duration::from_secs(60));
+        assert_eq!(information, entry.into_information());
+
+        let _ = fs::remove_dir_all(&cache.directory);
+    }
+
+    #[test]
+    fn get_invalid_entry_test() {
+        let cache = build_cache("invalid");
+        fs::create_dir_all(&cache.directory).unwrap();
+        fs::write(cache.path_for_host("broken"), "not json").unwrap();
+
+        assert!(cache.get("broken").is_err());
+
+        let _ = fs::remove_dir_all(&cache.directory);
+    }
+
+    #[test]
+    fn encode_file_name_test() {
+        assert_eq!("my.host-1_a", encode_file_name("my.host-1_a"));
+        assert_eq!("%2E.", encode_file_name(".."));
+        assert_eq!("a%2Fb%20c", encode_file_name("a/b c"));
+    }
+}
@@ -0,0 +1,5 @@
+mod cache_mode;
+mod information_cache;
+
+pub use self::cache_mode::CacheMode;
+pub use self::information_cache::{CacheEntry, InformationCache};
@@ -0,0 +1,85 @@
+use super::{
+    ssh_fetch::fetch_information_collection, CacheArgs, CommandTrait,
+    DefaultArgs,
+};
+use crate::{
+    audit::AdvisoryDatabase, error::Error, filter::HostPattern,
+    outcome::Outcome, FormatterTrait, Printer,
+};
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    /// Hosts to audit (globs or `/regex/`, all hosts if empty)
+    #[arg(num_args(0..), value_parser = HostPattern::parse)]
+    pub hosts: Vec<HostPattern>,
+
+    /// Advisory database: an OSV JSON file, a simple YAML file or a directory
+    /// of them
+    #[arg(
+        short,
+        long,
+        value_name = "path",
+        value_parser = clap::value_parser!(PathBuf)
+    )]
+    pub database: PathBuf,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    #[command(flatten)]
+    pub common: DefaultArgs,
+}
+
+#[derive(Default)]
+pub struct AuditCommand {}
+
+impl CommandTrait for AuditCommand {
+    type Args = AuditArgs;
+
+    fn exec<F: FormatterTrait>(
+        &self,
+        formatter: &F,
+        configuration_file: PathBuf,
+        arguments: Self::Args,
+    ) -> Result<Outcome, Error> {
+        // Load the database first, so that errors are reported before the
+        // hosts are contacted
+        let database = AdvisoryDatabase::load(&arguments.database)?;
+        if database.is_empty() {
+            Printer::print_warning(format!(
+                "The advisory database '{}' does not contain any advisories",
+                arguments.database.to_string_lossy()
+            ));
+        }
+        for warning in database.warnings() {
+            Printer::print_warning(warning);
+        }
+
+        let (information_collection, error_collection) =
+            fetch_information_collection(
+                configuration_file,
+                &arguments.common.selection(arguments.hosts.clone()),
+                false,
+                &arguments.common.settings(),
+                arguments.cache.cache_mode(),
+                arguments.common.verbosity > 0,
+            )?;
+
+        // Hosts with findings count as failed
+        let report = database.audit(&information_collection);
+        let clean = report.values().filter(|f| f.is_empty()).count();
+        let outcome = Outcome::from_results(
+            clean,
+            report.len() - clean + error_collection.len(),
+        );
+        Printer::print_result(formatter.format_audit_report(&report));
+
+        if arguments.common.verbosity > 0 {
+            Printer::print_error_collection(error_collection);
+        }
+
+        Ok(outcome)
+    }
+}
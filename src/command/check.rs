@@ -1,8 +1,11 @@
 use super::{
-    ssh_fetch::fetch_information_collection, CommandTrait, DefaultArgs,
+    ssh_fetch::fetch_information_collection_with_warnings, CommandTrait,
+    DefaultArgs,
+};
+use crate::{
+    cache::CacheMode, error::Error, outcome::Outcome, policy::Policy,
+    FormatterTrait, Printer,
 };
-use crate::{error::Error, prepare_message, FormatterTrait};
-use ansi_term::Colour;
 use clap::Args;
 use std::path::PathBuf;
 
@@ -14,6 +17,18 @@ pub struct CheckArgs {
     /// Check disabled configuration entries
     #[arg(short, long)]
     pub include_disabled: bool,
+
+    /// Exit with a non-zero code if any warning is reported
+    #[arg(long)]
+    pub fail_on_any: bool,
+
+    /// Check the hosts against the rules of the policy file (YAML or JSON)
+    #[arg(
+        long,
+        value_name = "path",
+        value_parser = clap::value_parser!(PathBuf)
+    )]
+    pub policy: Option<PathBuf>,
 }
 
 #[derive(Default)]
@@ -24,33 +39,105 @@ impl CommandTrait for CheckCommand {
 
     fn exec<F: FormatterTrait>(
         &self,
-        _formatter: &F,
+        formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
-        let (information_collection, error_collection) =
-            fetch_information_collection(
+    ) -> Result<Outcome, Error> {
+        // Load the policy first, so that errors are reported before the hosts
+        // are contacted
+        let policy = match &arguments.policy {
+            Some(path) => {
+                let policy = Policy::load(path)?;
+                if policy.is_empty() {
+                    Printer::print_warning(format!(
+                        "The policy '{}' does not contain any rules",
+                        path.to_string_lossy()
+                    ));
+                }
+                Some(policy)
+            }
+            None => None,
+        };
+
+        // The provider warnings are shown if they fail the run
+        let (information_collection, error_collection, warnings) =
+            fetch_information_collection_with_warnings(
                 configuration_file,
+                &arguments.common.selection(vec![]),
                 arguments.include_disabled,
+                &arguments.common.settings(),
+                CacheMode::Live,
+                arguments.common.verbosity > 0 || arguments.fail_on_any,
             )?;
 
-        for (host, _) in information_collection {
-            println!(
-                "{}",
-                Colour::Green.paint(format!("[OK] Host \"{}\"", host))
-            );
-        }
-        for (host, error) in error_collection {
-            eprintln!(
-                "{}",
-                Colour::Red.paint(format!(
-                    "[ERROR] Host \"{}\": {}",
-                    host,
-                    prepare_message(error.message())
-                ))
-            );
-        }
-
-        Ok(())
+        let policy = match policy {
+            Some(policy) => policy,
+            None => {
+                Printer::print_result(formatter.format_check_result(
+                    &information_collection,
+                    &error_collection,
+                ));
+
+                return Ok(Outcome::from_results(
+                    information_collection.len(),
+                    error_collection.len(),
+                )
+                .fail_on_any(arguments.fail_on_any, warnings));
+            }
+        };
+
+        // Hosts violating any rule count as failed
+        let report = policy.evaluate(&information_collection);
+        let compliant = report
+            .values()
+            .filter(|results| results.iter().all(|r| r.passed()))
+            .count();
+        Printer::print_result(
+            formatter.format_policy_report(&report, &error_collection),
+        );
+
+        Ok(Outcome::from_results(
+            compliant,
+            report.len() - compliant + error_collection.len(),
+        )
+        .fail_on_any(arguments.fail_on_any, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_arguments;
+    use super::*;
+    use crate::command::ssh_fetch::fetch_information_collection_with_warnings;
+    use crate::filter::HostSelection;
+    use crate::formatter::JsonFormatter;
+    use crate::test_helpers;
+
+    #[test]
+    fn fail_on_any_test() {
+        let configuration_file = test_helpers::get_test_resource_path(
+            "configuration-test-outcome.json",
+        );
+        let arguments: CheckArgs =
+            parse_arguments(&["--no-prompt", "--fail-on-any"]);
+
+        let (information, errors, warnings) =
+            fetch_information_collection_with_warnings(
+                configuration_file.clone(),
+                &HostSelection::default(),
+                false,
+                &arguments.common.settings(),
+                CacheMode::Live,
+                false,
+            )
+            .unwrap();
+        assert_eq!(0, information.len());
+        assert_eq!(2, errors.len());
+        assert_eq!(0, warnings);
+
+        let outcome = CheckCommand::default()
+            .exec(&JsonFormatter, configuration_file, arguments)
+            .unwrap();
+        assert_eq!(Outcome::TotalFailure, outcome);
     }
 }
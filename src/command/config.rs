@@ -0,0 +1,81 @@
+use super::CommandTrait;
+use super::DefaultArgs;
+use crate::{
+    configuration::{
+        describe_configuration, get_configuration_for_host,
+        ConfigurationProvider,
+    },
+    error::Error,
+    outcome::Outcome,
+    FormatterTrait, Printer,
+};
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigSubcommand {
+    /// Show the configuration of the host and where each property is defined
+    Show(ConfigShowArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigShowArgs {
+    /// Key of the host's configuration
+    pub host: String,
+
+    #[command(flatten)]
+    pub common: DefaultArgs,
+}
+
+impl ConfigArgs {
+    pub fn common(&self) -> &DefaultArgs {
+        match self.command {
+            ConfigSubcommand::Show(ref arguments) => &arguments.common,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ConfigCommand {}
+
+impl CommandTrait for ConfigCommand {
+    type Args = ConfigArgs;
+
+    fn exec<F: FormatterTrait>(
+        &self,
+        formatter: &F,
+        configuration_file: PathBuf,
+        arguments: Self::Args,
+    ) -> Result<Outcome, Error> {
+        match arguments.command {
+            ConfigSubcommand::Show(arguments) => {
+                let mut fleet_configuration = ConfigurationProvider::load_file(
+                    &configuration_file,
+                    true,
+                )?;
+                let sources = fleet_configuration
+                    .field_sources
+                    .remove(&arguments.host)
+                    .unwrap_or_default();
+                let configuration = get_configuration_for_host(
+                    fleet_configuration.hosts,
+                    &arguments.host,
+                    &configuration_file,
+                )?;
+
+                Printer::print_result(formatter.format_configuration(
+                    &arguments.host,
+                    &describe_configuration(&configuration, &sources),
+                ));
+
+                Ok(Outcome::Success)
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use super::{
+    load_selected_configuration, ssh_fetch::fetch_information_collection,
+    CacheArgs, CommandTrait, DefaultArgs,
+};
+use crate::{
+    error::Error,
+    filter::HostPattern,
+    information::{diff_collections, InformationCollection},
+    outcome::Outcome,
+    snapshot::SnapshotStore,
+    FormatterTrait, Printer,
+};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+/// Reference to the information fetched from the hosts right now
+const NOW: &str = "now";
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Older state: `latest`, `latest~N`, an age like `7d`, a snapshot
+    /// identifier or the path to a snapshot file
+    pub from: String,
+
+    /// Newer state: like `from` or `now` to fetch the current information
+    #[arg(default_value = NOW)]
+    pub to: String,
+
+    /// Hosts to compare (globs or `/regex/`, all hosts if empty)
+    #[arg(long = "host", value_parser = HostPattern::parse)]
+    pub hosts: Vec<HostPattern>,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    #[command(flatten)]
+    pub common: DefaultArgs,
+}
+
+#[derive(Default)]
+pub struct DiffCommand {}
+
+impl CommandTrait for DiffCommand {
+    type Args = DiffArgs;
+
+    fn exec<F: FormatterTrait>(
+        &self,
+        formatter: &F,
+        configuration_file: PathBuf,
+        arguments: Self::Args,
+    ) -> Result<Outcome, Error> {
+        let store = SnapshotStore::open_default(&configuration_file)?;
+        let (from_label, from) = self.resolve(
+            &store,
+            &arguments.from,
+            &configuration_file,
+            &arguments,
+        )?;
+        let (to_label, to) = self.resolve(
+            &store,
+            &arguments.to,
+            &configuration_file,
+            &arguments,
+        )?;
+
+        let selection = arguments.common.selection(arguments.hosts.clone());
+        let (from, to) = if selection.is_empty() {
+            (from, to)
+        } else {
+            let selected_hosts = load_selected_configuration(
+                &configuration_file,
+                &selection,
+                true,
+                &arguments.common.settings(),
+            )?
+            .0
+            .hosts;
+            let keep = |collection: InformationCollection| {
+                collection
+                    .into_iter()
+                    .filter(|(host, _)| selected_hosts.contains_key(host))
+                    .collect::<InformationCollection>()
+            };
+
+            (keep(from), keep(to))
+        };
+
+        Printer::print_result(formatter.format_diff(
+            &from_label,
+            &to_label,
+            &diff_collections(&from, &to),
+        ));
+
+        Ok(Outcome::Success)
+    }
+}
+
+impl DiffCommand {
+    /// Load the information for the reference and return it with a label
+    fn resolve(
+        &self,
+        store: &SnapshotStore,
+        reference: &str,
+        configuration_file: &Path,
+        arguments: &DiffArgs,
+    ) -> Result<(String, InformationCollection), Error> {
+        if reference != NOW {
+            let snapshot = store.resolve(reference)?;
+
+            return Ok((snapshot.id().to_owned(), snapshot.into_hosts()));
+        }
+
+        let (information_collection, error_collection) =
+            fetch_information_collection(
+                configuration_file.to_path_buf(),
+                &arguments.common.selection(arguments.hosts.clone()),
+                false,
+                &arguments.common.settings(),
+                arguments.cache.cache_mode(),
+                arguments.common.verbosity > 0,
+            )?;
+        let mut failed_hosts: Vec<&String> = error_collection.keys().collect();
+        failed_hosts.sort();
+        for host in failed_hosts {
+            Printer::print_warning(format!(
+                "Could not fetch the information for host \"{}\", it is \
+                 missing from the comparison",
+                host
+            ));
+        }
+        if arguments.common.verbosity > 0 {
+            Printer::print_error_collection(error_collection);
+        }
+
+        Ok((NOW.to_owned(), information_collection))
+    }
+}
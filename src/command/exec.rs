@@ -1,10 +1,8 @@
-use super::{
-    ssh_execute_shell::{
-        execute_shell_for_collection, execute_shell_for_hosts,
-    },
-    CommandTrait, DefaultArgs,
+use super::{ssh_execute_shell::execute_shell, CommandTrait, DefaultArgs};
+use crate::{
+    error::Error, filter::HostPattern, outcome::Outcome, FormatterTrait,
+    Printer,
 };
-use crate::{error::Error, FormatterTrait, Printer};
 use clap::Args;
 use std::path::PathBuf;
 
@@ -14,9 +12,17 @@ pub struct ExecArgs {
     /// Shell command to execute
     pub command: Vec<String>,
 
-    /// Hosts to fetch the packages
-    #[arg(long = "host", value_name = "host")]
-    pub hosts: Option<Vec<String>>,
+    /// Hosts to execute the command on (globs or `/regex/`)
+    #[arg(
+        long = "host",
+        value_name = "host",
+        value_parser = HostPattern::parse
+    )]
+    pub hosts: Option<Vec<HostPattern>>,
+
+    /// Exit with a non-zero code if any warning is reported
+    #[arg(long)]
+    pub fail_on_any: bool,
 
     #[command(flatten)]
     pub common: DefaultArgs,
@@ -33,24 +39,68 @@ impl CommandTrait for ExecCommand {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
+    ) -> Result<Outcome, Error> {
         let command_string = arguments.command.join(" ");
-        let (collection, errors) = match arguments.hosts {
-            Some(hosts) => execute_shell_for_hosts(
-                configuration_file,
-                &hosts,
-                command_string,
-            )?,
-            None => execute_shell_for_collection(
-                configuration_file,
-                command_string,
-            )?,
-        };
+        let settings = arguments.common.settings();
+        let selection = arguments
+            .common
+            .selection(arguments.hosts.unwrap_or_default());
+        let (collection, errors, warnings) = execute_shell(
+            configuration_file,
+            &selection,
+            command_string,
+            &settings,
+        )?;
 
+        let outcome = Outcome::from_results(collection.len(), errors.len())
+            .fail_on_any(arguments.fail_on_any, warnings);
         Printer::print_result(
             formatter.format_shell_output_collection(collection, errors),
         );
 
-        Ok(())
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_arguments;
+    use super::*;
+    use crate::formatter::JsonFormatter;
+    use crate::test_helpers;
+
+    #[test]
+    fn fail_on_any_test() {
+        let configuration_file = test_helpers::get_test_resource_path(
+            "configuration-test-outcome.json",
+        );
+        let arguments: ExecArgs = parse_arguments(&[
+            "--no-prompt",
+            "--host",
+            "down",
+            "--host",
+            "missing-*",
+            "true",
+        ]);
+        let selection = arguments
+            .common
+            .selection(arguments.hosts.clone().unwrap_or_default());
+
+        // The unmatched pattern is a warning, the unreachable host a failure
+        let (collection, errors, warnings) = execute_shell(
+            configuration_file.clone(),
+            &selection,
+            "true".to_owned(),
+            &arguments.common.settings(),
+        )
+        .unwrap();
+        assert_eq!(0, collection.len());
+        assert_eq!(1, errors.len());
+        assert_eq!(1, warnings);
+
+        let outcome = ExecCommand::default()
+            .exec(&JsonFormatter, configuration_file, arguments)
+            .unwrap();
+        assert_eq!(Outcome::TotalFailure, outcome);
     }
 }
@@ -1,10 +1,11 @@
 use clap::Args;
 
-use crate::{error::Error, FormatterTrait, Printer};
+use crate::{error::Error, outcome::Outcome, FormatterTrait, Printer};
 use std::path::PathBuf;
 
 use super::{
-    ssh_fetch::fetch_information_collection, CommandTrait, DefaultArgs,
+    ssh_fetch::fetch_information_collection, CacheArgs, CommandTrait,
+    DefaultArgs,
 };
 
 #[derive(Args, Debug)]
@@ -13,6 +14,13 @@ pub struct ListArgs {
     #[arg(short, long)]
     pub packages: bool,
 
+    /// Add a column with the value of the fact (e.g. `php.version`)
+    #[arg(long = "fact", value_name = "path")]
+    pub facts: Vec<String>,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
     #[command(flatten)]
     pub common: DefaultArgs,
 }
@@ -28,19 +36,31 @@ impl CommandTrait for ListCommand {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
+    ) -> Result<Outcome, Error> {
         let (information_collection, error_collection) =
-            fetch_information_collection(configuration_file, false)?;
+            fetch_information_collection(
+                configuration_file,
+                &arguments.common.selection(vec![]),
+                false,
+                &arguments.common.settings(),
+                arguments.cache.cache_mode(),
+                arguments.common.verbosity > 0,
+            )?;
 
+        let outcome = Outcome::from_results(
+            information_collection.len(),
+            error_collection.len(),
+        );
         Printer::print_result(formatter.format_information_collection(
             information_collection,
             arguments.packages,
+            &arguments.facts,
         ));
 
         if arguments.common.verbosity > 0 {
             Printer::print_error_collection(error_collection);
         }
 
-        Ok(())
+        Ok(outcome)
     }
 }
@@ -1,26 +1,43 @@
+pub mod audit;
 pub mod check;
+pub mod config;
+pub mod diff;
 pub mod exec;
 pub mod list;
 pub mod packages;
 pub mod provide;
+pub mod refresh;
 pub mod search;
 pub mod show;
+pub mod snapshot;
 mod ssh_execute_shell;
 mod ssh_fetch;
 pub mod update;
 
-use crate::{error::Error, FormatterTrait};
+use crate::{
+    cache::CacheMode,
+    configuration::{ConfigurationProvider, FleetConfiguration, Settings},
+    error::Error,
+    filter::{HostPattern, HostSelection, HostSelector},
+    outcome::Outcome,
+    FormatterTrait, Printer,
+};
+pub use audit::{AuditArgs, AuditCommand};
 pub use check::{CheckArgs, CheckCommand};
+pub use config::{ConfigArgs, ConfigCommand};
+pub use diff::{DiffArgs, DiffCommand};
 pub use exec::{ExecArgs, ExecCommand};
 pub use list::{ListArgs, ListCommand};
 pub use packages::{PackagesArgs, PackagesCommand};
 pub use provide::{ProvideArgs, ProvideCommand};
+pub use refresh::{RefreshArgs, RefreshCommand};
 pub use search::{SearchArgs, SearchCommand};
 pub use show::{ShowArgs, ShowCommand};
+pub use snapshot::{SnapshotArgs, SnapshotCommand};
 pub use update::{UpdateArgs, UpdateCommand};
 
 use clap::Args;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub trait CommandTrait {
     type Args;
@@ -30,7 +47,7 @@ pub trait CommandTrait {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error>;
+    ) -> Result<Outcome, Error>;
 }
 
 #[derive(Args, Debug)]
@@ -46,4 +63,122 @@ pub struct DefaultArgs {
     /// Level of verbosity
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbosity: u8,
+
+    /// Number of hosts to process in parallel
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+
+    /// Seconds to wait for the connection to a host (0 to disable)
+    #[arg(long, value_name = "seconds")]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds to wait for the command on a host (0 to disable, updates have
+    /// no timeout unless it is set)
+    #[arg(long, value_name = "seconds")]
+    pub command_timeout: Option<u64>,
+
+    /// Select hosts by tag, name, group or host address
+    ///
+    /// Example: `tag:prod and not (tag:legacy or name:web-*)`
+    #[arg(
+        short,
+        long,
+        value_name = "expression",
+        value_parser = HostSelector::parse
+    )]
+    pub select: Option<HostSelector>,
+
+    /// Never ask for passphrases (fail instead, e.g. for batch use)
+    #[arg(long)]
+    pub no_prompt: bool,
+}
+
+impl DefaultArgs {
+    /// Return the settings overridden through command line arguments
+    pub fn settings(&self) -> Settings {
+        let settings = Settings::new(
+            self.jobs,
+            self.connect_timeout,
+            self.command_timeout,
+        )
+        .with_verbose(self.verbosity > 0);
+        if self.no_prompt {
+            settings.with_prompt(false)
+        } else {
+            settings
+        }
+    }
+
+    /// Return the selection of the given hosts and the `--select` expression
+    pub fn selection(&self, hosts: Vec<HostPattern>) -> HostSelection {
+        HostSelection::new(hosts, self.select.clone())
+    }
+}
+
+/// Arguments to answer queries from the information cache
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    /// Only use cached information instead of connecting to the hosts
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Use cached information that is at most this old
+    #[arg(long, value_name = "seconds")]
+    pub max_age: Option<u64>,
+}
+
+impl CacheArgs {
+    pub fn cache_mode(&self) -> CacheMode {
+        CacheMode::from_arguments(self.offline, self.max_age)
+    }
+}
+
+/// Load the configuration file and keep only the selected hosts
+///
+/// Returns the configuration and the number of host patterns that do not match
+/// any host (a warning is printed for each)
+fn load_selected_configuration(
+    configuration_file: &Path,
+    selection: &HostSelection,
+    include_disabled: bool,
+    settings: &Settings,
+) -> Result<(FleetConfiguration, usize), Error> {
+    let fleet_configuration = match ConfigurationProvider::load_resolved(
+        configuration_file,
+        include_disabled,
+        settings,
+    ) {
+        Ok(c) => c,
+        Err(e) => {
+            return Err(Error::config(format!(
+                "Error when loading configuration file '{}': {}",
+                configuration_file.to_string_lossy(),
+                e
+            )))
+        }
+    };
+
+    let unmatched = selection.unmatched_patterns(&fleet_configuration.hosts);
+    for pattern in &unmatched {
+        Printer::print_warning(format!(
+            "The pattern \"{}\" does not match any host",
+            pattern
+        ));
+    }
+    let warnings = unmatched.len();
+
+    Ok((fleet_configuration.select(selection)?, warnings))
+}
+
+/// Parse the command line arguments of a command in tests
+#[cfg(test)]
+fn parse_arguments<A: Args + clap::FromArgMatches>(arguments: &[&str]) -> A {
+    let command = A::augment_args(clap::Command::new("fleet"));
+    let matches = command
+        .try_get_matches_from(
+            std::iter::once("fleet").chain(arguments.iter().copied()),
+        )
+        .unwrap();
+
+    A::from_arg_matches(&matches).unwrap()
 }
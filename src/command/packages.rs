@@ -1,16 +1,38 @@
 use super::{
-    ssh_fetch::{fetch_information_collection, fetch_information_for_hosts},
-    CommandTrait, DefaultArgs,
+    ssh_fetch::fetch_information_collection, CacheArgs, CommandTrait,
+    DefaultArgs,
+};
+use crate::{
+    error::Error,
+    filter::{
+        HostPattern, InformationCollectionFilter, PackageQuery, PackageState,
+    },
+    outcome::Outcome,
+    FormatterTrait, Printer,
 };
-use crate::{error::Error, FormatterTrait, Printer};
 use clap::Args;
 use std::path::PathBuf;
 
 #[derive(Args, Debug)]
 pub struct PackagesArgs {
-    /// Hosts to fetch the packages
-    #[arg(num_args(0..))]
-    pub hosts: Vec<String>,
+    /// Hosts to fetch the packages (globs or `/regex/`)
+    #[arg(num_args(0..), value_parser = HostPattern::parse)]
+    pub hosts: Vec<HostPattern>,
+
+    /// Show the package versions of all hosts side by side
+    #[arg(long)]
+    pub matrix: bool,
+
+    /// Only show packages whose versions differ between the hosts
+    #[arg(long, requires = "matrix")]
+    pub only_drift: bool,
+
+    /// Only show packages in the given state
+    #[arg(long, value_enum, default_value_t)]
+    pub state: PackageState,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
 
     #[command(flatten)]
     pub common: DefaultArgs,
@@ -27,25 +49,43 @@ impl CommandTrait for PackagesCommand {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
-        let hosts = &arguments.hosts;
+    ) -> Result<Outcome, Error> {
+        let (information_collection, error_collection) =
+            fetch_information_collection(
+                configuration_file,
+                &arguments.common.selection(arguments.hosts.clone()),
+                false,
+                &arguments.common.settings(),
+                arguments.cache.cache_mode(),
+                arguments.common.verbosity > 0,
+            )?;
 
-        let (information_collection, error_collection) = if hosts.is_empty() {
-            fetch_information_collection(configuration_file, false)?
-        } else {
-            fetch_information_for_hosts(configuration_file, hosts)?
-        };
-
-        Printer::print_result(
-            formatter.format_packages_from_information_collection(
-                information_collection,
-            ),
+        let outcome = Outcome::from_results(
+            information_collection.len(),
+            error_collection.len(),
         );
+        let information_collection =
+            InformationCollectionFilter::filter_packages(
+                information_collection,
+                &PackageQuery::all().with_state(arguments.state),
+            );
+        if arguments.matrix {
+            Printer::print_result(formatter.format_package_matrix(
+                &information_collection,
+                arguments.only_drift,
+            ));
+        } else {
+            Printer::print_result(
+                formatter.format_packages_from_information_collection(
+                    information_collection,
+                ),
+            );
+        }
 
         if arguments.common.verbosity > 0 {
             Printer::print_error_collection(error_collection);
         }
 
-        Ok(())
+        Ok(outcome)
     }
 }
@@ -1,6 +1,7 @@
 use crate::{
     error::Error,
     information::Platform,
+    outcome::Outcome,
     provider::{LocalProvider, Provider},
     FormatterTrait, Printer,
 };
@@ -20,7 +21,7 @@ impl ProvideCommand {
     pub fn provide<F: FormatterTrait>(
         &self,
         formatter: &F,
-    ) -> Result<(), Error> {
+    ) -> Result<Outcome, Error> {
         let platform = Platform::new_for_current_env();
         let result = LocalProvider::new().get_information();
 
@@ -31,6 +32,6 @@ impl ProvideCommand {
             Err(e) => Err(e),
         });
 
-        Ok(())
+        Ok(Outcome::Success)
     }
 }
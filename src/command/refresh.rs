@@ -0,0 +1,62 @@
+use super::{
+    ssh_fetch::fetch_information_collection_with_warnings, CommandTrait,
+    DefaultArgs,
+};
+use crate::{
+    cache::CacheMode, error::Error, filter::HostPattern, outcome::Outcome,
+    FormatterTrait, Printer,
+};
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct RefreshArgs {
+    /// Hosts to refresh (globs or `/regex/`, all hosts if empty)
+    #[arg(num_args(0..), value_parser = HostPattern::parse)]
+    pub hosts: Vec<HostPattern>,
+
+    /// Exit with a non-zero code if any warning is reported
+    #[arg(long)]
+    pub fail_on_any: bool,
+
+    #[command(flatten)]
+    pub common: DefaultArgs,
+}
+
+#[derive(Default)]
+pub struct RefreshCommand {}
+
+impl CommandTrait for RefreshCommand {
+    type Args = RefreshArgs;
+
+    fn exec<F: FormatterTrait>(
+        &self,
+        formatter: &F,
+        configuration_file: PathBuf,
+        arguments: Self::Args,
+    ) -> Result<Outcome, Error> {
+        // Fetching live information stores it in the cache
+        let (information_collection, error_collection, warnings) =
+            fetch_information_collection_with_warnings(
+                configuration_file,
+                &arguments.common.selection(arguments.hosts.clone()),
+                false,
+                &arguments.common.settings(),
+                CacheMode::Live,
+                arguments.common.verbosity > 0 || arguments.fail_on_any,
+            )?;
+
+        Printer::print_result(
+            formatter.format_check_result(
+                &information_collection,
+                &error_collection,
+            ),
+        );
+
+        Ok(Outcome::from_results(
+            information_collection.len(),
+            error_collection.len(),
+        )
+        .fail_on_any(arguments.fail_on_any, warnings))
+    }
+}
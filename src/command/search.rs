@@ -1,9 +1,14 @@
 use super::{
-    ssh_fetch::fetch_information_collection, CommandTrait, DefaultArgs,
+    ssh_fetch::fetch_information_collection, CacheArgs, CommandTrait,
+    DefaultArgs,
 };
 use crate::{
     error::Error,
-    filter::{InformationCollectionFilter, PackageFilter},
+    filter::{
+        FactCondition, InformationCollectionFilter, PackageFilter,
+        PackageQuery, PackageState, VersionConstraint,
+    },
+    outcome::Outcome,
     FormatterTrait, Printer,
 };
 use clap::Args;
@@ -12,12 +17,42 @@ use std::path::PathBuf;
 #[derive(Args, Debug)]
 pub struct SearchArgs {
     /// Package name to search for
-    pub package: String,
+    #[arg(required_unless_present = "facts")]
+    pub package: Option<String>,
+
+    /// Only show packages with a matching version (e.g. `'<10.4.20'`)
+    #[arg(value_parser = VersionConstraint::parse)]
+    pub constraint: Option<VersionConstraint>,
+
+    /// Only show packages with a matching version (e.g. `'>=8.7,<9'`)
+    #[arg(
+        long,
+        value_name = "constraint",
+        value_parser = VersionConstraint::parse,
+        conflicts_with = "constraint",
+        requires = "package"
+    )]
+    pub version: Option<VersionConstraint>,
+
+    /// Only show hosts with a matching fact (e.g. `php.version=8.1.*`)
+    #[arg(
+        long = "fact",
+        value_name = "path=value",
+        value_parser = FactCondition::parse
+    )]
+    pub facts: Vec<FactCondition>,
+
+    /// Only show packages in the given state
+    #[arg(long, value_enum, default_value_t)]
+    pub state: PackageState,
 
     /// Only show exact matches
     #[arg(short, long)]
     pub exact: bool,
 
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
     #[command(flatten)]
     pub common: DefaultArgs,
 }
@@ -33,37 +68,69 @@ impl CommandTrait for SearchCommand {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
-        let package = &arguments.package;
-        let exact = arguments.exact;
-
-        if package.trim().is_empty() {
-            return Err(Error::new("Please specify the 'package' argument"));
-        }
+    ) -> Result<Outcome, Error> {
+        let query = match &arguments.package {
+            Some(package) if package.trim().is_empty() => {
+                return Err(Error::new("Please specify the 'package' argument"))
+            }
+            Some(package) => Some(
+                PackageQuery::new(package, arguments.exact)
+                    .with_version(arguments.version.or(arguments.constraint))
+                    .with_state(arguments.state),
+            ),
+            None => None,
+        };
 
         let (information_collection, error_collection) =
-            fetch_information_collection(configuration_file, false)?;
-        let filtered_collection =
-            InformationCollectionFilter::filter_by_package(
-                information_collection,
-                package,
-                exact,
-            );
-        for (host, information) in filtered_collection {
-            Printer::print_result(formatter.format_information(
-                &host,
-                &information,
+            fetch_information_collection(
+                configuration_file,
+                &arguments.common.selection(vec![]),
                 false,
-            ));
-            Printer::print_result(formatter.format_packages(
-                &PackageFilter::filter(information.packages, package, exact),
-            ));
+                &arguments.common.settings(),
+                arguments.cache.cache_mode(),
+                arguments.common.verbosity > 0,
+            )?;
+        let outcome = Outcome::from_results(
+            information_collection.len(),
+            error_collection.len(),
+        );
+        let filtered_collection = InformationCollectionFilter::filter_by_facts(
+            information_collection,
+            &arguments.facts,
+        );
+        match query {
+            Some(query) => {
+                let filtered_collection =
+                    InformationCollectionFilter::filter_by_package(
+                        filtered_collection,
+                        &query,
+                    );
+                for (host, information) in filtered_collection {
+                    Printer::print_result(formatter.format_information(
+                        &host,
+                        &information,
+                        false,
+                    ));
+                    Printer::print_result(formatter.format_packages(
+                        &PackageFilter::filter(information.packages, &query),
+                    ));
+                }
+            }
+            None => {
+                for (host, information) in filtered_collection {
+                    Printer::print_result(formatter.format_information(
+                        &host,
+                        &information,
+                        false,
+                    ));
+                }
+            }
         }
 
         if arguments.common.verbosity > 0 {
             Printer::print_error_collection(error_collection);
         }
 
-        Ok(())
+        Ok(outcome)
     }
 }
@@ -1,5 +1,7 @@
-use super::{ssh_fetch::fetch_information_for_host, CommandTrait, DefaultArgs};
-use crate::{error::Error, FormatterTrait, Printer};
+use super::{
+    ssh_fetch::fetch_information_for_host, CacheArgs, CommandTrait, DefaultArgs,
+};
+use crate::{error::Error, outcome::Outcome, FormatterTrait, Printer};
 use clap::Args;
 use std::path::PathBuf;
 
@@ -12,6 +14,9 @@ pub struct ShowArgs {
     #[arg(short, long)]
     pub packages: bool,
 
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
     #[command(flatten)]
     pub common: DefaultArgs,
 }
@@ -27,9 +32,16 @@ impl CommandTrait for ShowCommand {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
+    ) -> Result<Outcome, Error> {
         let host = &arguments.host;
-        let information = fetch_information_for_host(configuration_file, host)?;
+        let information = fetch_information_for_host(
+            configuration_file,
+            host,
+            &arguments.common.selection(vec![]),
+            &arguments.common.settings(),
+            arguments.cache.cache_mode(),
+            arguments.common.verbosity > 0,
+        )?;
 
         Printer::print_result(formatter.format_information(
             host,
@@ -42,6 +54,6 @@ impl CommandTrait for ShowCommand {
             );
         }
 
-        Ok(())
+        Ok(Outcome::Success)
     }
 }
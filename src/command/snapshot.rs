@@ -0,0 +1,86 @@
+use super::{
+    ssh_fetch::fetch_information_collection, CacheArgs, CommandTrait,
+    DefaultArgs,
+};
+use crate::{
+    error::Error, filter::HostPattern, outcome::Outcome,
+    snapshot::SnapshotStore, FormatterTrait, Printer,
+};
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct SnapshotArgs {
+    /// Hosts to include in the snapshot (globs or `/regex/`, all hosts if
+    /// empty)
+    #[arg(num_args(0..), value_parser = HostPattern::parse)]
+    pub hosts: Vec<HostPattern>,
+
+    /// List the stored snapshots instead of taking a new one
+    #[arg(long)]
+    pub list: bool,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    #[command(flatten)]
+    pub common: DefaultArgs,
+}
+
+#[derive(Default)]
+pub struct SnapshotCommand {}
+
+impl CommandTrait for SnapshotCommand {
+    type Args = SnapshotArgs;
+
+    fn exec<F: FormatterTrait>(
+        &self,
+        _formatter: &F,
+        configuration_file: PathBuf,
+        arguments: Self::Args,
+    ) -> Result<Outcome, Error> {
+        let store = SnapshotStore::open_default(&configuration_file)?;
+        if arguments.list {
+            Printer::print_result(Ok(store.list()?.join("\n")));
+
+            return Ok(Outcome::Success);
+        }
+
+        let (information_collection, error_collection) =
+            fetch_information_collection(
+                configuration_file,
+                &arguments.common.selection(arguments.hosts.clone()),
+                false,
+                &arguments.common.settings(),
+                arguments.cache.cache_mode(),
+                arguments.common.verbosity > 0,
+            )?;
+
+        let outcome = Outcome::from_results(
+            information_collection.len(),
+            error_collection.len(),
+        );
+        if information_collection.is_empty() && !error_collection.is_empty() {
+            Printer::print_error_collection(error_collection);
+            Printer::print_warning(
+                "No information could be fetched, no snapshot was saved",
+            );
+
+            return Ok(outcome);
+        }
+
+        let host_count = information_collection.len();
+        let snapshot = store.save(information_collection)?;
+        Printer::print_result(Ok(format!(
+            "Saved snapshot {} with {} host(s)",
+            snapshot.id(),
+            host_count
+        )));
+
+        if arguments.common.verbosity > 0 {
+            Printer::print_error_collection(error_collection);
+        }
+
+        Ok(outcome)
+    }
+}
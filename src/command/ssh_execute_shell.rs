@@ -1,85 +1,75 @@
+use super::load_selected_configuration;
 use crate::{
-    configuration::{ConfigurationCollection, ConfigurationProvider},
+    configuration::{ConfigurationCollection, Settings},
     error::{Error, ErrorCollection},
-    provider::{Provider, SshProvider},
+    filter::HostSelection,
+    provider::SshProvider,
     shell::ShellOutputCollection,
 };
 use std::path::PathBuf;
 
-/// Execute a shell command for all hosts through SSH
-pub fn execute_shell_for_collection(
-    configuration_file: PathBuf,
-    command: String,
-) -> Result<(ShellOutputCollection, ErrorCollection), Error> {
-    let configuration_collection =
-        ConfigurationProvider::load(configuration_file.as_path(), false)?;
+/// Outputs and errors of the hosts and the number of warnings
+pub type ShellResult =
+    Result<(ShellOutputCollection, ErrorCollection, usize), Error>;
 
-    Ok(SshProvider::new()
-        .execute_shell_for_collection(command, configuration_collection))
-}
-
-/// Execute a shell command for the given hosts through SSH
-pub fn execute_shell_for_hosts(
+/// Execute a shell command for the selected hosts through SSH
+///
+/// The warnings are the host patterns that do not match any host
+pub fn execute_shell(
     configuration_file: PathBuf,
-    hosts: &[String],
+    selection: &HostSelection,
     command: String,
-) -> Result<(ShellOutputCollection, ErrorCollection), Error> {
-    let configuration_collection =
-        ConfigurationProvider::load(configuration_file.as_path(), false)?;
-    let filtered: ConfigurationCollection = configuration_collection
-        .into_iter()
-        .filter(|(host, _)| !host.is_empty() && hosts.contains(host))
-        .collect();
-
-    if filtered.is_empty() {
-        Err(Error::new(format!(
-            "{}: {}",
-            if hosts.len() > 1 {
-                "No configurations found for hosts"
-            } else {
-                "No configuration found for host"
-            },
-            hosts.join(", ")
-        )))
-    } else {
-        Ok(SshProvider::new().execute_shell_for_collection(command, filtered))
-    }
-}
+    settings: &Settings,
+) -> ShellResult {
+    let (fleet_configuration, warnings) = load_selected_configuration(
+        configuration_file.as_path(),
+        selection,
+        false,
+        settings,
+    )?;
 
-/// Execute the update command for all hosts through SSH
-pub fn execute_update_for_collection(
-    configuration_file: PathBuf,
-) -> Result<(ShellOutputCollection, ErrorCollection), Error> {
-    let configuration_collection =
-        ConfigurationProvider::load(configuration_file.as_path(), false)?;
+    let (collection, errors) =
+        SshProvider::with_settings(fleet_configuration.settings)
+            .execute_shell_for_collection(command, fleet_configuration.hosts);
 
-    Ok(SshProvider::new()
-        .execute_update_for_collection(configuration_collection))
+    Ok((collection, errors, warnings))
 }
 
-/// Execute the update command for the given hosts through SSH
-pub fn execute_update_for_hosts(
+/// Execute the update command for the selected hosts through SSH
+///
+/// Hosts without an `update_command` are skipped. If `fail_skipped` is set,
+/// they are reported as failed instead. The warnings are the host patterns
+/// that do not match any host
+pub fn execute_update(
     configuration_file: PathBuf,
-    hosts: &[String],
-) -> Result<(ShellOutputCollection, ErrorCollection), Error> {
-    let configuration_collection =
-        ConfigurationProvider::load(configuration_file.as_path(), false)?;
-    let filtered: ConfigurationCollection = configuration_collection
-        .into_iter()
-        .filter(|(host, _)| !host.is_empty() && hosts.contains(host))
-        .collect();
+    selection: &HostSelection,
+    settings: &Settings,
+    fail_skipped: bool,
+) -> ShellResult {
+    let (fleet_configuration, warnings) = load_selected_configuration(
+        configuration_file.as_path(),
+        selection,
+        false,
+        settings,
+    )?;
 
-    if filtered.is_empty() {
-        Err(Error::new(format!(
-            "{}: {}",
-            if hosts.len() > 1 {
-                "No configurations found for hosts"
-            } else {
-                "No configuration found for host"
-            },
-            hosts.join(", ")
-        )))
-    } else {
-        Ok(SshProvider::new().execute_update_for_collection(filtered))
+    let (hosts, skipped): (ConfigurationCollection, ConfigurationCollection) =
+        fleet_configuration
+            .hosts
+            .into_iter()
+            .partition(|(_, c)| c.update_command().is_some());
+
+    let (collection, mut errors) =
+        SshProvider::with_settings(fleet_configuration.settings)
+            .execute_update_for_collection(hosts);
+    if fail_skipped {
+        for host in skipped.into_keys() {
+            errors.insert(
+                host,
+                Error::new("No update command is configured for the host"),
+            );
+        }
     }
+
+    Ok((collection, errors, warnings))
 }
@@ -1,97 +1,209 @@
+use super::load_selected_configuration;
 use crate::{
+    cache::{CacheMode, InformationCache},
     configuration::{
-        Configuration, ConfigurationCollection, ConfigurationProvider,
+        get_configuration_for_host, ConfigurationCollection, Settings,
     },
     error::{Error, ErrorCollection},
+    filter::HostSelection,
     information::{CollectionResult, Information, InformationCollection},
-    provider::{Provider, SshProvider},
+    provider::SshProvider,
+    Printer,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Fetch information for the given host
 ///
 /// This will perform the following steps:
-///     1. Look for the configuration file
-///     2. Fetch the configuration for the host
-///     3. Fetch the information using the configuration
+///     1. Load the configuration file
+///     2. Fetch the configuration for the host (if it is selected)
+///     3. Fetch the information using the configuration (or from the cache)
+///
+/// Warnings about the provider's output are printed if `verbose` is set
 pub fn fetch_information_for_host(
     configuration_file: PathBuf,
     host: &str,
+    selection: &HostSelection,
+    settings: &Settings,
+    cache_mode: CacheMode,
+    verbose: bool,
 ) -> Result<Information, Error> {
-    let configuration = ConfigurationProvider::get_configuration_for_host(
+    let (fleet_configuration, _) = load_selected_configuration(
         configuration_file.as_path(),
+        selection,
+        false,
+        settings,
+    )?;
+    let configuration = get_configuration_for_host(
+        fleet_configuration.hosts,
         host,
+        configuration_file.as_path(),
     )?;
 
-    fetch_information(&configuration)
+    let cache = open_cache(&configuration_file, cache_mode)?;
+    if let Some(information) = get_cached(&cache, host, cache_mode) {
+        return Ok(information);
+    }
+    if cache_mode.is_offline() {
+        return Err(build_not_cached_error(host));
+    }
+
+    let (information, warnings) =
+        SshProvider::with_settings(fleet_configuration.settings)
+            .get_information(&configuration)?;
+    store(&cache, host, &information);
+    if verbose {
+        print_warnings(host, &warnings);
+    }
+
+    Ok(information)
 }
 
-/// Fetch information for the given hosts
+/// Fetch the information for the selected hosts
 ///
 /// This will perform the following steps:
-///     1. Look for the configuration file
+///     1. Load the configuration file
 ///     2. Filter the list of configurations
-///     3. Fetch the information using the configurations
-pub fn fetch_information_for_hosts(
+///     3. Fetch the information using the configurations (or from the cache)
+///
+/// Warnings about the providers' output are printed if `verbose` is set
+pub fn fetch_information_collection(
     configuration_file: PathBuf,
-    hosts: &[String],
+    selection: &HostSelection,
+    include_disabled: bool,
+    settings: &Settings,
+    cache_mode: CacheMode,
+    verbose: bool,
 ) -> CollectionResult {
-    let configuration_collection =
-        ConfigurationProvider::load(configuration_file.as_path(), false)?;
-    let filtered: ConfigurationCollection = configuration_collection
-        .into_iter()
-        .filter(|(host, _)| !host.is_empty() && hosts.contains(host))
-        .collect();
-
-    if filtered.is_empty() {
-        Err(Error::new(format!(
-            "{}: {}",
-            if hosts.len() > 1 {
-                "No configurations found for hosts"
-            } else {
-                "No configuration found for host"
-            },
-            hosts.join(", ")
-        )))
-    } else {
-        Ok(fetch_information_for_configuration_collection(filtered))
-    }
+    fetch_information_collection_with_warnings(
+        configuration_file,
+        selection,
+        include_disabled,
+        settings,
+        cache_mode,
+        verbose,
+    )
+    .map(|(information, errors, _)| (information, errors))
 }
 
-/// Fetch the information for all hosts in the configuration collection
-pub fn fetch_information_collection(
+/// Fetch the information for the selected hosts and count the warnings
+///
+/// The warnings are the host patterns that do not match any host and the
+/// hosts whose provider output had to be repaired
+pub fn fetch_information_collection_with_warnings(
     configuration_file: PathBuf,
+    selection: &HostSelection,
     include_disabled: bool,
-) -> CollectionResult {
-    let configuration_collection = match ConfigurationProvider::load(
+    settings: &Settings,
+    cache_mode: CacheMode,
+    verbose: bool,
+) -> Result<(InformationCollection, ErrorCollection, usize), Error> {
+    let (fleet_configuration, mut warning_count) = load_selected_configuration(
         configuration_file.as_path(),
+        selection,
         include_disabled,
-    ) {
-        Ok(c) => c,
+        settings,
+    )?;
+    let cache = open_cache(&configuration_file, cache_mode)?;
+
+    let mut information_collection = InformationCollection::new();
+    let mut error_collection = ErrorCollection::new();
+    let mut uncached = ConfigurationCollection::new();
+    for (host, configuration) in fleet_configuration.hosts {
+        match get_cached(&cache, &host, cache_mode) {
+            Some(information) => {
+                information_collection.insert(host, information);
+            }
+            None if cache_mode.is_offline() => {
+                error_collection
+                    .insert(host.clone(), build_not_cached_error(&host));
+            }
+            None => {
+                uncached.insert(host, configuration);
+            }
+        }
+    }
+
+    if !uncached.is_empty() {
+        let (fetched, errors, warnings) =
+            SshProvider::with_settings(fleet_configuration.settings)
+                .get_information_for_collection(uncached);
+        for (host, information) in &fetched {
+            store(&cache, host, information);
+        }
+        if verbose {
+            for (host, warnings) in &warnings {
+                print_warnings(host, warnings);
+            }
+        }
+        warning_count += warnings.len();
+        information_collection.extend(fetched);
+        error_collection.extend(errors);
+    }
+
+    Ok((information_collection, error_collection, warning_count))
+}
+
+/// Open the information cache
+///
+/// If the cache is not available, only offline queries fail
+fn open_cache(
+    configuration_file: &Path,
+    cache_mode: CacheMode,
+) -> Result<Option<InformationCache>, Error> {
+    match InformationCache::open_default(configuration_file) {
+        Ok(cache) => Ok(Some(cache)),
+        Err(e) if cache_mode.is_offline() => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Return the cached information for the host, if the cache mode accepts it
+fn get_cached(
+    cache: &Option<InformationCache>,
+    host: &str,
+    cache_mode: CacheMode,
+) -> Option<Information> {
+    if cache_mode == CacheMode::Live {
+        return None;
+    }
+
+    match cache.as_ref()?.get(host) {
+        Ok(Some(entry)) if cache_mode.accepts(&entry) => {
+            Some(entry.into_information())
+        }
+        Ok(_) => None,
         Err(e) => {
-            return Err(Error::new(format!(
-                "Error when loading configuration file '{}': {}",
-                configuration_file.to_string_lossy(),
-                e
-            )))
+            Printer::print_warning(e.message());
+            None
         }
-    };
+    }
+}
 
-    Ok(fetch_information_for_configuration_collection(
-        configuration_collection,
-    ))
+fn store(
+    cache: &Option<InformationCache>,
+    host: &str,
+    information: &Information,
+) {
+    if let Some(cache) = cache {
+        if let Err(e) = cache.store(host, information) {
+            Printer::print_warning(format!(
+                "Could not update the cache for host \"{}\": {}",
+                host, e
+            ));
+        }
+    }
 }
 
-/// Fetch the information for all hosts in the given configuration collection
-fn fetch_information_for_configuration_collection(
-    configuration_collection: ConfigurationCollection,
-) -> (InformationCollection, ErrorCollection) {
-    SshProvider::new().get_information_for_collection(configuration_collection)
+fn print_warnings(host: &str, warnings: &[String]) {
+    for warning in warnings {
+        Printer::print_warning(format!("Host \"{}\": {}", host, warning));
+    }
 }
 
-/// Fetch information from the host in the given configuration
-fn fetch_information(
-    configuration: &Configuration,
-) -> Result<Information, Error> {
-    SshProvider::new().get_information(configuration)
+fn build_not_cached_error(host: &str) -> Error {
+    Error::new(format!(
+        "No cached information for host \"{}\" that is recent enough",
+        host
+    ))
 }
@@ -1,23 +1,26 @@
-use super::{
-    ssh_execute_shell::{
-        execute_update_for_collection, execute_update_for_hosts,
-    },
-    CommandTrait, DefaultArgs,
+use super::{ssh_execute_shell::execute_update, CommandTrait, DefaultArgs};
+use crate::{
+    error::Error, filter::HostPattern, outcome::Outcome, FormatterTrait,
+    Printer,
 };
-use crate::{error::Error, FormatterTrait, Printer};
 use clap::Args;
 use std::path::PathBuf;
 
 #[derive(Args, Debug)]
 pub struct UpdateArgs {
-    /// Hosts to fetch the packages
-    #[arg()]
-    pub hosts: Vec<String>,
+    /// Hosts to update (globs or `/regex/`)
+    #[arg(value_parser = HostPattern::parse)]
+    pub hosts: Vec<HostPattern>,
 
     /// Update all hosts
     #[arg(short = 'a', long)]
     pub update_all: bool,
 
+    /// Exit with a non-zero code if any warning is reported and fail hosts
+    /// without an update command
+    #[arg(long)]
+    pub fail_on_any: bool,
+
     #[command(flatten)]
     pub common: DefaultArgs,
 }
@@ -33,24 +36,62 @@ impl CommandTrait for UpdateCommand {
         formatter: &F,
         configuration_file: PathBuf,
         arguments: Self::Args,
-    ) -> Result<(), Error> {
-        let hosts = arguments.hosts;
-        if hosts.is_empty() && !arguments.update_all {
+    ) -> Result<Outcome, Error> {
+        let selection = arguments.common.selection(arguments.hosts);
+        if selection.is_empty() && !arguments.update_all {
             return Err(Error::new(
-                "At least one host or `update-all` must be given",
+                "At least one host, `select` or `update-all` must be given",
             ));
         }
 
-        let (collection, errors) = if !hosts.is_empty() {
-            execute_update_for_hosts(configuration_file, &hosts)?
-        } else {
-            execute_update_for_collection(configuration_file)?
-        };
+        let settings = arguments.common.settings();
+        let (collection, errors, warnings) = execute_update(
+            configuration_file,
+            &selection,
+            &settings,
+            arguments.fail_on_any,
+        )?;
 
+        let outcome = Outcome::from_results(collection.len(), errors.len())
+            .fail_on_any(arguments.fail_on_any, warnings);
         Printer::print_result(
             formatter.format_shell_output_collection(collection, errors),
         );
 
-        Ok(())
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_arguments;
+    use super::*;
+    use crate::formatter::JsonFormatter;
+    use crate::test_helpers;
+
+    fn run(arguments: &[&str]) -> Outcome {
+        let arguments: UpdateArgs = parse_arguments(arguments);
+
+        UpdateCommand::default()
+            .exec(
+                &JsonFormatter,
+                test_helpers::get_test_resource_path(
+                    "configuration-test-outcome.json",
+                ),
+                arguments,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn fail_on_any_test() {
+        // Hosts without update command are skipped ...
+        assert_eq!(Outcome::Success, run(&["--no-prompt", "no-update"]));
+        // ... but fail the run with `--fail-on-any`
+        assert_eq!(
+            Outcome::TotalFailure,
+            run(&["--no-prompt", "--fail-on-any", "no-update"])
+        );
+        assert_eq!(Outcome::TotalFailure, run(&["--no-prompt", "down"]));
     }
 }
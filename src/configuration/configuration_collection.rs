@@ -5,16 +5,8 @@ use std::path::Path;
 
 pub type ConfigurationCollection = HashMap<String, Configuration>;
 
-pub fn chunk_configuration_collection(
-    collection: ConfigurationCollection,
-    size: usize,
-) -> Vec<ConfigurationCollection> {
-    let list: Vec<_> = collection.into_iter().collect();
-
-    list.chunks(size)
-        .map(|c| c.iter().cloned().collect())
-        .collect()
-}
+/// Named groups of hosts (lists of configuration keys or patterns)
+pub type Groups = HashMap<String, Vec<String>>;
 
 pub fn get_configuration_for_host(
     mut collection: ConfigurationCollection,
@@ -23,7 +15,7 @@ pub fn get_configuration_for_host(
 ) -> Result<Configuration, Error> {
     match collection.remove(host) {
         Some(configuration) => Ok(configuration),
-        None => Err(Error::new(format!(
+        None => Err(Error::config(format!(
             "Host {} not found in configuration file {}",
             host,
             file.to_str().unwrap_or("(non UTF-8 file path)")
@@ -2,30 +2,115 @@ use crate::error::Error;
 use std::env;
 use std::path::*;
 
+/// Environment variable with the path to the configuration file
+const CONFIG_ENV_VAR: &str = "FLEET_CONFIG";
+
+/// Names of the configuration file in the order they are looked up
+const FILE_NAMES: &[&str] = &[".fleet.json", "fleet.json"];
+
+/// System wide configuration directory
+const SYSTEM_DIRECTORY: &str = "/etc/fleet";
+
+/// Detect the configuration file to use
+///
+/// If `FLEET_CONFIG` is set, its value is used. Otherwise `.fleet.json` and
+/// `fleet.json` are looked up in the current working directory, its parent
+/// directories, `$XDG_CONFIG_HOME/fleet/` (`~/.config/fleet/`) and
+/// `/etc/fleet/`. The first file found is used
 pub fn detect_configuration_file() -> Result<PathBuf, Error> {
+    if let Some(path) = env::var_os(CONFIG_ENV_VAR).filter(|p| !p.is_empty()) {
+        let path = PathBuf::from(path);
+
+        return if path.is_file() {
+            Ok(path)
+        } else {
+            Err(Error::config(format!(
+                "The configuration file {:?} defined in {} does not exist",
+                path, CONFIG_ENV_VAR
+            )))
+        };
+    }
+
     let pwd = match env::current_dir() {
         Ok(pwd) => pwd,
-        Err(e) => return Err(Error::from_error(&e)),
+        Err(e) => return Err(Error::config(e.to_string())),
     };
 
-    let mut dot_file = pwd.clone();
-    dot_file.push(".fleet.json");
+    let directories = get_lookup_directories(&pwd, get_config_home());
+    match find_configuration_file(&directories) {
+        Some(file) => Ok(file),
+        None => Err(Error::config(format!(
+            "Could not detect the configuration file: No configuration file \
+             found in {:?}, its parent directories or {}",
+            pwd,
+            directories
+                .iter()
+                .skip(pwd.ancestors().count())
+                .map(|d| format!("{:?}", d))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
 
-    if dot_file.exists() {
-        return Ok(dot_file);
+/// Return the directories to look for the configuration file in order
+fn get_lookup_directories(
+    pwd: &Path,
+    config_home: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> =
+        pwd.ancestors().map(Path::to_path_buf).collect();
+    if let Some(config_home) = config_home {
+        directories.push(config_home.join("fleet"));
     }
+    directories.push(PathBuf::from(SYSTEM_DIRECTORY));
 
-    let mut file = pwd.clone();
-    file.push("fleet.json");
+    directories
+}
 
-    if file.exists() {
-        return Ok(file);
+/// Return the first configuration file found in the directories
+fn find_configuration_file(directories: &[PathBuf]) -> Option<PathBuf> {
+    directories
+        .iter()
+        .flat_map(|directory| FILE_NAMES.iter().map(|n| directory.join(n)))
+        .find(|file| file.is_file())
+}
+
+/// Return a directory name that is unique for the configuration file
+///
+/// Cached information and snapshots are stored per configuration file, because
+/// different configurations may use the same host keys for other servers. The
+/// name consists of the file stem and a hash of the canonical path (e.g.
+/// `fleet-6c5fe1d7a0b2c413`)
+pub fn configuration_namespace(configuration_file: &Path) -> String {
+    let path = configuration_file
+        .canonicalize()
+        .unwrap_or_else(|_| configuration_file.to_path_buf());
+
+    // FNV-1a, which (unlike `DefaultHasher`) is stable between releases
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in path.as_os_str().as_encoded_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
     }
 
-    Err(Error::new(format!(
-        "Could not detect the configuration file: No configuration file found in {:?}",
-        pwd
-    )))
+    let stem: String = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().trim_start_matches('.').to_owned())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect();
+
+    format!("{}-{:016x}", stem, hash)
+}
+
+/// Return `$XDG_CONFIG_HOME` or its default `~/.config`
+fn get_config_home() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(path) if path.is_absolute() => Some(path),
+        _ => dirs::home_dir().map(|home| home.join(".config")),
+    }
 }
 
 #[cfg(test)]
@@ -106,4 +191,63 @@ mod tests {
             configuration_file_result.unwrap()
         );
     }
+
+    #[test]
+    fn configuration_namespace_test() {
+        let file = test_helpers::get_test_resource_path("configuration_file")
+            .join("fleet.json");
+        let namespace = configuration_namespace(&file);
+        assert!(namespace.starts_with("fleet-"));
+        assert_eq!(namespace, configuration_namespace(&file));
+
+        let dot_file =
+            test_helpers::get_test_resource_path("configuration_dot_file")
+                .join(".fleet.json");
+        let dot_namespace = configuration_namespace(&dot_file);
+        assert!(dot_namespace.starts_with("fleet-"));
+        assert_ne!(namespace, dot_namespace);
+    }
+
+    #[test]
+    fn get_lookup_directories_test() {
+        let directories = get_lookup_directories(
+            Path::new("/srv/shop/deploy"),
+            Some(PathBuf::from("/home/daniel/.config")),
+        );
+
+        assert_eq!(
+            vec![
+                PathBuf::from("/srv/shop/deploy"),
+                PathBuf::from("/srv/shop"),
+                PathBuf::from("/srv"),
+                PathBuf::from("/"),
+                PathBuf::from("/home/daniel/.config/fleet"),
+                PathBuf::from("/etc/fleet"),
+            ],
+            directories
+        );
+    }
+
+    #[test]
+    fn find_configuration_file_test() {
+        let directories = [
+            test_helpers::get_test_resource_path("advisories"),
+            test_helpers::get_test_resource_path("configuration_dot_file"),
+            test_helpers::get_test_resource_path("configuration_file"),
+        ];
+
+        assert_eq!(
+            Some(test_helpers::get_test_resource_path(
+                "configuration_dot_file/.fleet.json"
+            )),
+            find_configuration_file(&directories)
+        );
+        assert_eq!(
+            Some(test_helpers::get_test_resource_path(
+                "configuration_file/fleet.json"
+            )),
+            find_configuration_file(&directories[2..])
+        );
+        assert_eq!(None, find_configuration_file(&directories[..1]));
+    }
 }
@@ -1,73 +1,463 @@
+use super::inheritance::{FieldSources, HostValues, Inheritance};
+use super::jump_hosts::resolve_jump_hosts;
+use super::ssh_config::{expand_include, SshConfigFile};
 use crate::configuration::*;
 use crate::error::Error;
+use crate::filter::HostSelection;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs;
 use std::path::*;
 
-pub struct ConfigurationProvider;
+/// Directory next to the configuration file whose files are included
+const INCLUDE_DIRECTORY: &str = "fleet.d";
 
-impl ConfigurationProvider {
-    pub fn get_configuration_for_host(
-        path: &Path,
-        host: &str,
-    ) -> Result<Configuration, Error> {
-        let collection = Self::load(path, false)?;
+/// Content of a configuration file
+///
+/// The file may either be a map of host configurations or an object with the
+/// keys `hosts`, `settings`, `groups`, `defaults`, `templates` and `include`
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigurationFile {
+    settings: Option<Settings>,
+    #[serde(default)]
+    defaults: HostValues,
+    #[serde(default)]
+    templates: BTreeMap<String, HostValues>,
+    #[serde(default)]
+    hosts: BTreeMap<String, HostValues>,
+    #[serde(default)]
+    groups: Groups,
+    /// Paths of further configuration files (wildcards are supported in the
+    /// file name)
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+impl ConfigurationFile {
+    /// Merge the `defaults` and `templates` into the host configurations
+    fn resolve(self) -> Result<FleetConfiguration, Error> {
+        let inheritance = Inheritance::new(&self.defaults, &self.templates);
+        let mut hosts = ConfigurationCollection::new();
+        let mut field_sources = HashMap::new();
+        for (host, values) in self.hosts {
+            let (merged, sources) = inheritance
+                .resolve(&values)
+                .map_err(|e| build_host_error(&host, e.message()))?;
+            let configuration =
+                serde_json::from_value(serde_json::Value::Object(merged))
+                    .map_err(|e| build_host_error(&host, &e.to_string()))?;
+
+            hosts.insert(host.clone(), configuration);
+            field_sources.insert(host, sources);
+        }
 
-        get_configuration_for_host(collection, host, path)
+        Ok(FleetConfiguration {
+            settings: self.settings.unwrap_or_default(),
+            hosts,
+            groups: self.groups,
+            field_sources,
+        })
     }
+}
 
-    pub fn load(
-        path: &Path,
-        include_disabled: bool,
-    ) -> Result<ConfigurationCollection, Error> {
-        let file = match File::open(path) {
+/// Loads a configuration file together with the files it includes
+///
+/// The files listed in `include` are loaded after the including file, the
+/// files in the `fleet.d` directory next to the main file last. Each host,
+/// template, group and default may only be defined once across all files.
+/// Settings may only be defined in the main file
+#[derive(Default)]
+struct ConfigurationLoader {
+    file: ConfigurationFile,
+    /// File that defines each host, template, group and default
+    origins: HashMap<String, PathBuf>,
+    loaded: HashSet<PathBuf>,
+}
+
+impl ConfigurationLoader {
+    fn load(path: &Path) -> Result<ConfigurationFile, Error> {
+        let mut loader = ConfigurationLoader::default();
+        let mut file = ConfigurationProvider::read_file(path)?;
+        loader.file.settings = file.settings.take();
+        loader.add(path, file)?;
+
+        let directory = base_directory(path).join(INCLUDE_DIRECTORY);
+        let mut paths: Vec<PathBuf> = FileFormat::EXTENSIONS
+            .iter()
+            .flat_map(|e| expand_include(&format!("*.{}", e), &directory))
+            .collect();
+        paths.sort();
+        for path in paths {
+            loader.include(&path)?;
+        }
+
+        Ok(loader.file)
+    }
+
+    fn include(&mut self, path: &Path) -> Result<(), Error> {
+        let file = match ConfigurationProvider::read_file(path) {
             Ok(file) => file,
             Err(e) => {
-                return Err(Error::with_error_and_details(&e, e.to_string()))
+                return Err(Error::config(format!(
+                    "Could not load included file {:?}: {}",
+                    path,
+                    e.message()
+                )))
             }
         };
+        if file.settings.is_some() {
+            return Err(Error::config(format!(
+                "The included file {:?} defines settings, they can only be \
+                 defined in the main configuration file",
+                path
+            )));
+        }
+
+        self.add(path, file)
+    }
+
+    /// Merge the file into the configuration and load its includes
+    ///
+    /// Files that were already loaded are skipped
+    fn add(
+        &mut self,
+        path: &Path,
+        file: ConfigurationFile,
+    ) -> Result<(), Error> {
+        let canonical =
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !self.loaded.insert(canonical) {
+            return Ok(());
+        }
+
+        let origins = &mut self.origins;
+        let target = &mut self.file;
+        merge_entries(origins, "host", path, file.hosts, |k, v| {
+            target.hosts.insert(k, v);
+        })?;
+        merge_entries(origins, "template", path, file.templates, |k, v| {
+            target.templates.insert(k, v);
+        })?;
+        merge_entries(origins, "group", path, file.groups, |k, v| {
+            target.groups.insert(k, v);
+        })?;
+        merge_entries(origins, "default", path, file.defaults, |k, v| {
+            target.defaults.insert(k, v);
+        })?;
 
-        if let Some(extension) = path.extension() {
-            let collection = match extension.to_str() {
-                #[cfg(feature = "yaml")]
-                Some("yaml") => ConfigurationProvider::load_yaml(file),
-                Some("json") => ConfigurationProvider::load_json(file),
-                _ => Err(build_file_format_error(extension)),
-            }?;
-
-            if include_disabled {
-                Ok(collection)
-            } else {
-                Ok(ConfigurationCollection::from_iter(
-                    collection.into_iter().filter(|(_, c)| !c.disabled()),
-                ))
+        for pattern in &file.include {
+            let paths = expand_include(pattern, base_directory(path));
+            if paths.is_empty() && !pattern.contains(['*', '?']) {
+                return Err(Error::config(format!(
+                    "The file {:?} included in {:?} does not exist",
+                    pattern, path
+                )));
             }
+            for included in paths {
+                self.include(&included)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Insert the entries and remember the file defining them
+///
+/// An error is returned if an entry is already defined in another file
+fn merge_entries<V, I, F>(
+    origins: &mut HashMap<String, PathBuf>,
+    kind: &str,
+    path: &Path,
+    entries: I,
+    mut insert: F,
+) -> Result<(), Error>
+where
+    I: IntoIterator<Item = (String, V)>,
+    F: FnMut(String, V),
+{
+    for (name, value) in entries {
+        let key = format!("{} '{}'", kind, name);
+        if let Some(origin) = origins.get(&key) {
+            return Err(Error::config(format!(
+                "The {} is defined in both {:?} and {:?}",
+                key, origin, path
+            )));
+        }
+
+        origins.insert(key, path.to_path_buf());
+        insert(name, value);
+    }
+
+    Ok(())
+}
+
+fn base_directory(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
+/// Host configurations and settings loaded from a configuration file
+#[derive(Debug, Default)]
+pub struct FleetConfiguration {
+    pub settings: Settings,
+    pub hosts: ConfigurationCollection,
+    pub groups: Groups,
+    /// Source of each property of the host configurations
+    pub field_sources: HashMap<String, FieldSources>,
+}
+
+impl FleetConfiguration {
+    /// Apply the settings from the command line
+    ///
+    /// The overrides replace the global settings and the timeouts of each host
+    pub fn with_overrides(self, overrides: &Settings) -> Self {
+        FleetConfiguration {
+            settings: self.settings.merge(overrides),
+            hosts: self
+                .hosts
+                .into_iter()
+                .map(|(host, c)| (host, overrides.override_configuration(c)))
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Complete the host configurations with the SSH client configuration
+    ///
+    /// The `host` of each configuration is looked up as an alias in the file
+    /// defined by the `ssh_config` setting (`~/.ssh/config` by default). A
+    /// relative path is resolved against `base_directory`.
+    ///
+    /// Afterwards the `proxy_jump` chains are resolved
+    pub fn resolve_ssh_config(
+        self,
+        base_directory: &Path,
+    ) -> Result<Self, Error> {
+        let ssh_config = match self.settings.ssh_config() {
+            Some(path) => SshConfigFile::load(&base_directory.join(path))?,
+            None => SshConfigFile::default(),
+        };
+
+        let hosts = self
+            .hosts
+            .into_iter()
+            .map(|(host, c)| {
+                let options = ssh_config.lookup(c.host());
+                (host, c.with_ssh_options(options))
+            })
+            .collect();
+
+        Ok(FleetConfiguration {
+            hosts: resolve_jump_hosts(hosts, &ssh_config)?,
+            ..self
+        })
+    }
+
+    /// Keep only the configurations of the selected hosts
+    pub fn select(self, selection: &HostSelection) -> Result<Self, Error> {
+        Ok(FleetConfiguration {
+            hosts: selection.select(self.hosts, &self.groups)?,
+            ..self
+        })
+    }
+
+    /// Remove the disabled host configurations
+    fn without_disabled(self) -> Self {
+        FleetConfiguration {
+            hosts: ConfigurationCollection::from_iter(
+                self.hosts.into_iter().filter(|(_, c)| !c.disabled()),
+            ),
+            ..self
+        }
+    }
+
+    /// Check if the value uses the structured layout
+    ///
+    /// All keys have to be keys of the structured layout and each section
+    /// must have the expected shape. So a legacy configuration of hosts that
+    /// are called like the keys (e.g. "hosts" or "defaults") is not mistaken
+    /// for the structured layout, because host configurations are objects
+    /// with a `host` address
+    fn is_structured(value: &serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::Object(object) if !object.is_empty() => object
+                .iter()
+                .all(|(key, section)| is_structured_section(key, section)),
+            _ => false,
+        }
+    }
+}
+
+/// Check if the section has the shape of the key in the structured layout
+fn is_structured_section(key: &str, section: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    let values_are = |predicate: fn(&Value) -> bool| match section {
+        Value::Object(object) => object.values().all(predicate),
+        _ => false,
+    };
+    match key {
+        "settings" => {
+            serde_json::from_value::<Settings>(section.clone()).is_ok()
+        }
+        "defaults" => {
+            section.is_object()
+                && !section.get("host").is_some_and(Value::is_string)
+        }
+        "templates" | "hosts" => values_are(Value::is_object),
+        "groups" => values_are(Value::is_array),
+        "include" => section.is_array(),
+        _ => false,
+    }
+}
+
+#[derive(Clone, Copy)]
+enum FileFormat {
+    Json,
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl FileFormat {
+    /// Extensions of the supported file formats
+    #[cfg(feature = "yaml")]
+    const EXTENSIONS: &'static [&'static str] = &["json", "yaml"];
+    #[cfg(not(feature = "yaml"))]
+    const EXTENSIONS: &'static [&'static str] = &["json"];
+
+    fn from_extension(extension: &OsStr) -> Result<Self, Error> {
+        match extension.to_str() {
+            #[cfg(feature = "yaml")]
+            Some("yaml") => Ok(FileFormat::Yaml),
+            Some("json") => Ok(FileFormat::Json),
+            _ => Err(build_file_format_error(extension)),
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T, Error> {
+        match self {
+            FileFormat::Json => match serde_json::from_str(content) {
+                Ok(configuration) => Ok(configuration),
+                Err(e) => Err(Error::config(e.to_string())),
+            },
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => match serde_yaml::from_str(content) {
+                Ok(configuration) => Ok(configuration),
+                Err(e) => Err(Error::config(e.to_string())),
+            },
+        }
+    }
+}
+
+pub struct ConfigurationProvider;
+
+impl ConfigurationProvider {
+    #[cfg(test)]
+    pub fn load(
+        path: &Path,
+        include_disabled: bool,
+    ) -> Result<ConfigurationCollection, Error> {
+        Ok(Self::load_file(path, include_disabled)?.hosts)
+    }
+
+    /// Load the configuration file and prepare it for connecting to the hosts
+    ///
+    /// The command line `overrides` are applied and the host configurations
+    /// are completed with the SSH client configuration. Disabled hosts are
+    /// only removed afterwards, so they can still be used as jump hosts
+    pub fn load_resolved(
+        path: &Path,
+        include_disabled: bool,
+        overrides: &Settings,
+    ) -> Result<FleetConfiguration, Error> {
+        let configuration = Self::load_file(path, true)?
+            .with_overrides(overrides)
+            .resolve_ssh_config(base_directory(path))?;
+
+        if include_disabled {
+            Ok(configuration)
+        } else {
+            Ok(configuration.without_disabled())
+        }
+    }
+
+    /// Load the host configurations and the settings from the given file
+    ///
+    /// The included files and the files in the `fleet.d` directory next to
+    /// the file are merged into the configuration
+    pub fn load_file(
+        path: &Path,
+        include_disabled: bool,
+    ) -> Result<FleetConfiguration, Error> {
+        let configuration = ConfigurationLoader::load(path)?.resolve()?;
+
+        if include_disabled {
+            Ok(configuration)
         } else {
-            Err(Error::new(format!(
+            Ok(configuration.without_disabled())
+        }
+    }
+
+    /// Read a single configuration file without its includes
+    fn read_file(path: &Path) -> Result<ConfigurationFile, Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return Err(Error::config(e.to_string())),
+        };
+
+        match path.extension() {
+            Some(extension) => ConfigurationProvider::read(
+                FileFormat::from_extension(extension)?,
+                &content,
+            ),
+            None => Err(Error::config(format!(
                 "Could not load configuration from '{}'",
                 path.to_string_lossy()
-            )))
+            ))),
         }
     }
 
-    fn load_json(file: File) -> Result<ConfigurationCollection, Error> {
-        match serde_json::from_reader(file) {
-            Ok(configuration) => Ok(configuration),
-            Err(e) => Err(Error::with_error_and_details(&e, e.to_string())),
-        }
+    /// Parse the content and merge the `defaults` and `templates`
+    #[cfg(test)]
+    fn parse(
+        format: FileFormat,
+        content: &str,
+    ) -> Result<FleetConfiguration, Error> {
+        Self::read(format, content)?.resolve()
     }
 
-    #[cfg(feature = "yaml")]
-    fn load_yaml(file: File) -> Result<ConfigurationCollection, Error> {
-        match serde_yaml::from_reader(file) {
-            Ok(configuration) => Ok(configuration),
-            Err(e) => Err(Error::with_error_and_details(&e, e.to_string())),
+    /// Read the content in the detected layout
+    ///
+    /// The content is parsed a second time (instead of converting the
+    /// intermediate value) to keep line and column numbers in error messages
+    fn read(
+        format: FileFormat,
+        content: &str,
+    ) -> Result<ConfigurationFile, Error> {
+        let value: serde_json::Value = format.parse(content)?;
+        if FleetConfiguration::is_structured(&value) {
+            format.parse(content)
+        } else {
+            Ok(ConfigurationFile {
+                hosts: format.parse(content)?,
+                ..Default::default()
+            })
         }
     }
 }
 
+fn build_host_error(host: &str, message: &str) -> Error {
+    Error::config(format!(
+        "Invalid configuration for host '{}': {}",
+        host, message
+    ))
+}
+
 fn build_file_format_error(extension: &OsStr) -> Error {
-    Error::new(format!(
+    Error::config(format!(
         "Could not load configuration from file with extension '{}'",
         extension.to_string_lossy()
     ))
@@ -182,4 +572,220 @@ mod tests {
                 .unwrap();
         assert_eq!(5, configurations.keys().len());
     }
+
+    #[test]
+    fn load_file_with_settings_test() {
+        let json_file_path = test_helpers::get_test_resource_path(
+            "configuration-test-settings.json",
+        );
+        let configuration =
+            ConfigurationProvider::load_file(json_file_path.as_path(), false)
+                .unwrap();
+        assert_eq!(16, configuration.settings.concurrency());
+        assert_eq!(1, configuration.hosts.len());
+        assert!(configuration.hosts.contains_key("my.host.local"));
+
+        let configuration =
+            ConfigurationProvider::load_file(json_file_path.as_path(), true)
+                .unwrap();
+        assert_eq!(2, configuration.hosts.len());
+    }
+
+    #[test]
+    fn load_file_without_settings_test() {
+        let json_file_path = test_helpers::get_test_resource_path(
+            "configuration-test-0.2.0.json",
+        );
+        let configuration =
+            ConfigurationProvider::load_file(json_file_path.as_path(), false)
+                .unwrap();
+        assert_eq!(Settings::default(), configuration.settings);
+        assert_eq!(4, configuration.hosts.len());
+    }
+
+    #[test]
+    fn load_resolved_test() {
+        let json_file_path = test_helpers::get_test_resource_path(
+            "configuration-test-ssh-config.json",
+        );
+        let configuration = ConfigurationProvider::load_resolved(
+            json_file_path.as_path(),
+            false,
+            &Settings::default(),
+        )
+        .unwrap();
+
+        let shop = &configuration.hosts["shop"];
+        assert_eq!("shop.example.com", shop.host());
+        assert_eq!("deploy", shop.username());
+        assert_eq!(2222, shop.port());
+        assert_eq!(Some(PathBuf::from("/keys/shop")), shop.private_key());
+
+        let explicit = &configuration.hosts["explicit"];
+        assert_eq!("included.example.com", explicit.host());
+        assert_eq!("admin", explicit.username());
+        assert_eq!(22, explicit.port());
+
+        // The disabled bastion is removed, but still used as jump host
+        assert!(!configuration.hosts.contains_key("bastion"));
+        let jump_hosts = explicit.jump_hosts();
+        assert_eq!(1, jump_hosts.len());
+        assert_eq!("bastion.example.com", jump_hosts[0].host());
+    }
+
+    #[test]
+    fn parse_secret_references_test() {
+        let configuration = ConfigurationProvider::parse(
+            FileFormat::Json,
+            r#"{
+                "shop": {
+                    "host": "shop.example.com",
+                    "command": "fleet provide",
+                    "password": {"env": "FLEET_TEST_SHOP_PW"},
+                    "passphrase": {"command": "echo s3cr3t"}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let shop = &configuration.hosts["shop"];
+        assert_eq!("s3cr3t", shop.passphrase().unwrap().resolve().unwrap());
+        let error = shop.password().unwrap().resolve().unwrap_err();
+        assert!(error.message().contains("FLEET_TEST_SHOP_PW"));
+
+        let error = ConfigurationProvider::parse(
+            FileFormat::Json,
+            r#"{"shop": {"host": "a", "command": "b", "password": {"vault": 1}}}"#,
+        )
+        .unwrap_err();
+        assert!(error.message().contains("{env: ..}"), "{}", error.message());
+    }
+
+    #[test]
+    fn load_file_with_templates_test() {
+        let json_file_path = test_helpers::get_test_resource_path(
+            "configuration-test-templates.json",
+        );
+        let configuration =
+            ConfigurationProvider::load_file(json_file_path.as_path(), false)
+                .unwrap();
+
+        let shop = &configuration.hosts["shop"];
+        assert_eq!("vendor/bin/fleet provide", shop.command());
+        assert_eq!(
+            Some("apt-get upgrade -y".to_owned()),
+            shop.update_command()
+        );
+        assert_eq!("deploy", shop.username());
+        assert!(shop.private_key().is_some());
+
+        let blog = &configuration.hosts["blog"];
+        assert_eq!("fleet provide", blog.command());
+        assert_eq!("admin", blog.username());
+
+        let sources = &configuration.field_sources["shop"];
+        assert_eq!(
+            FieldSource::Template("typo3".to_owned()),
+            sources["command"]
+        );
+        assert_eq!(
+            FieldSource::Template("web".to_owned()),
+            sources["update_command"]
+        );
+        assert_eq!(FieldSource::Defaults, sources["username"]);
+        assert_eq!(FieldSource::Host, sources["host"]);
+        assert_eq!(
+            FieldSource::Host,
+            configuration.field_sources["blog"]["username"]
+        );
+    }
+
+    #[test]
+    fn parse_with_undefined_template_test() {
+        let error = ConfigurationProvider::parse(
+            FileFormat::Json,
+            r#"{"hosts": {"shop": {"extends": "web", "host": "a"}}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            "Invalid configuration for host 'shop': \
+             The template 'web' is not defined",
+            error.message()
+        );
+    }
+
+    #[test]
+    fn load_file_with_includes_test() {
+        let json_file_path = test_helpers::get_test_resource_path(
+            "configuration_include/fleet.json",
+        );
+        let configuration =
+            ConfigurationProvider::load_file(json_file_path.as_path(), false)
+                .unwrap();
+
+        assert_eq!(8, configuration.settings.concurrency());
+        let mut hosts: Vec<_> = configuration.hosts.keys().collect();
+        hosts.sort();
+        assert_eq!(vec!["blog", "main", "shop"], hosts);
+        let shop = &configuration.hosts["shop"];
+        assert_eq!("fleet provide", shop.command());
+        assert_eq!("deploy", shop.username());
+        assert_eq!(
+            vec!["shop".to_owned(), "blog".to_owned()],
+            configuration.groups["websites"]
+        );
+    }
+
+    #[test]
+    fn load_file_with_duplicate_host_test() {
+        let json_file_path = test_helpers::get_test_resource_path(
+            "configuration_include_duplicate/fleet.json",
+        );
+        let error =
+            ConfigurationProvider::load_file(json_file_path.as_path(), false)
+                .unwrap_err();
+
+        assert!(
+            error
+                .message()
+                .starts_with("The host 'shop' is defined in both"),
+            "{}",
+            error.message()
+        );
+        assert!(error.message().contains("fleet.d/shop.json"));
+    }
+
+    #[test]
+    fn is_structured_test() {
+        let is_structured = |content: &str| {
+            FleetConfiguration::is_structured(
+                &serde_json::from_str(content).unwrap(),
+            )
+        };
+
+        assert!(is_structured(r#"{"hosts": {"a": {}}}"#));
+        assert!(is_structured(r#"{"templates": {}, "include": ["a.json"]}"#));
+        assert!(is_structured(
+            r#"{"settings": {"concurrency": 8}, "defaults": {"username": "u"},
+                "groups": {"web": ["a"]}}"#
+        ));
+        assert!(!is_structured(r#"{"hosts": {"host": "a"}}"#));
+        assert!(!is_structured(r#"{"include": {"host": "a"}}"#));
+        assert!(!is_structured(r#"{"shop": {}, "hosts": {"a": {}}}"#));
+        assert!(!is_structured("{}"));
+
+        // Legacy configurations of hosts called like the keys
+        let host = r#"{"host": "h", "command": "c", "username": "u"}"#;
+        for key in ["settings", "defaults", "templates", "groups"] {
+            assert!(
+                !is_structured(&format!(r#"{{"{}": {}}}"#, key, host)),
+                "{}",
+                key
+            );
+        }
+        assert!(!is_structured(&format!(
+            r#"{{"settings": {}, "defaults": {}}}"#,
+            host, host
+        )));
+    }
 }
@@ -0,0 +1,350 @@
+use super::Configuration;
+use crate::error::Error;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Properties of a host configuration or template as defined in the file
+pub type HostValues = Map<String, Value>;
+
+/// Source of each property of a host configuration
+pub type FieldSources = BTreeMap<String, FieldSource>;
+
+/// Key of the template names a host or template inherits from
+const EXTENDS: &str = "extends";
+
+/// Properties that are masked when showing a configuration
+const SECRET_FIELDS: &[&str] = &["password", "passphrase"];
+
+/// Origin of a property of a host configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldSource {
+    /// The property is not configured and the built-in default is used
+    BuiltIn,
+    /// The `defaults` block
+    Defaults,
+    /// The named template
+    Template(String),
+    /// The host's own configuration
+    Host,
+}
+
+impl fmt::Display for FieldSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldSource::BuiltIn => f.write_str("built-in"),
+            FieldSource::Defaults => f.write_str("defaults"),
+            FieldSource::Template(name) => write!(f, "template:{}", name),
+            FieldSource::Host => f.write_str("host"),
+        }
+    }
+}
+
+impl Serialize for FieldSource {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Property of a host configuration and where it is defined
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ConfigurationField {
+    pub name: String,
+    pub value: Value,
+    pub source: FieldSource,
+}
+
+/// Merges the `defaults` and the `templates` into the host configurations
+///
+/// The properties are applied in the following order, later ones replace
+/// earlier ones:
+///
+///     1. `defaults`
+///     2. Each template in `extends` (after the templates it extends itself)
+///     3. The host's own properties
+pub struct Inheritance<'a> {
+    defaults: &'a HostValues,
+    templates: &'a BTreeMap<String, HostValues>,
+}
+
+impl<'a> Inheritance<'a> {
+    pub fn new(
+        defaults: &'a HostValues,
+        templates: &'a BTreeMap<String, HostValues>,
+    ) -> Self {
+        Inheritance {
+            defaults,
+            templates,
+        }
+    }
+
+    /// Return the merged properties of the host and the source of each
+    pub fn resolve(
+        &self,
+        values: &HostValues,
+    ) -> Result<(HostValues, FieldSources), Error> {
+        if self.defaults.contains_key(EXTENDS) {
+            return Err(Error::config(
+                "The `defaults` can not extend templates",
+            ));
+        }
+
+        let mut merged = HostValues::new();
+        let mut sources = FieldSources::new();
+        merge(
+            &mut merged,
+            &mut sources,
+            self.defaults,
+            FieldSource::Defaults,
+        );
+        for name in extended_templates(values)? {
+            self.apply_template(name, &mut merged, &mut sources, &mut vec![])?;
+        }
+        merge(&mut merged, &mut sources, values, FieldSource::Host);
+
+        Ok((merged, sources))
+    }
+
+    fn apply_template(
+        &self,
+        name: &str,
+        merged: &mut HostValues,
+        sources: &mut FieldSources,
+        chain: &mut Vec<String>,
+    ) -> Result<(), Error> {
+        if chain.iter().any(|n| n == name) {
+            return Err(Error::config(format!(
+                "The templates extend each other in a cycle ({} -> {})",
+                chain.join(" -> "),
+                name
+            )));
+        }
+        let template = match self.templates.get(name) {
+            Some(template) => template,
+            None => {
+                return Err(Error::config(format!(
+                    "The template '{}' is not defined",
+                    name
+                )))
+            }
+        };
+
+        chain.push(name.to_owned());
+        for parent in extended_templates(template)? {
+            self.apply_template(parent, merged, sources, chain)?;
+        }
+        chain.pop();
+
+        merge(
+            merged,
+            sources,
+            template,
+            FieldSource::Template(name.to_owned()),
+        );
+
+        Ok(())
+    }
+}
+
+/// Return the properties of the configuration and where they are defined
+///
+/// Unset properties are omitted. Plain text passwords and passphrases are
+/// masked
+pub fn describe_configuration(
+    configuration: &Configuration,
+    sources: &FieldSources,
+) -> Vec<ConfigurationField> {
+    let values = match serde_json::to_value(configuration) {
+        Ok(Value::Object(values)) => values,
+        _ => return vec![],
+    };
+
+    values
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(_) if SECRET_FIELDS.contains(&name.as_str()) => {
+                    Value::String("********".to_owned())
+                }
+                value => value,
+            };
+            let source =
+                sources.get(&name).cloned().unwrap_or(FieldSource::BuiltIn);
+
+            ConfigurationField {
+                name,
+                value,
+                source,
+            }
+        })
+        .collect()
+}
+
+fn extended_templates(values: &HostValues) -> Result<Vec<&str>, Error> {
+    match values.get(EXTENDS) {
+        None => Ok(vec![]),
+        Some(Value::String(name)) => Ok(vec![name.as_str()]),
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|name| name.as_str().ok_or_else(build_extends_error))
+            .collect(),
+        Some(_) => Err(build_extends_error()),
+    }
+}
+
+fn build_extends_error() -> Error {
+    Error::config(
+        "`extends` must be a template name or a list of template names",
+    )
+}
+
+fn merge(
+    merged: &mut HostValues,
+    sources: &mut FieldSources,
+    values: &HostValues,
+    source: FieldSource,
+) {
+    for (key, value) in values {
+        if key != EXTENDS {
+            merged.insert(key.clone(), value.clone());
+            sources.insert(key.clone(), source.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values(value: Value) -> HostValues {
+        match value {
+            Value::Object(values) => values,
+            _ => unreachable!(),
+        }
+    }
+
+    fn build_templates() -> BTreeMap<String, HostValues> {
+        BTreeMap::from([
+            (
+                "web".to_owned(),
+                values(json!({
+                    "command": "fleet provide",
+                    "update_command": "apt-get upgrade -y"
+                })),
+            ),
+            (
+                "typo3".to_owned(),
+                values(json!({
+                    "extends": "web",
+                    "command": "vendor/bin/fleet"
+                })),
+            ),
+            ("loop-a".to_owned(), values(json!({"extends": "loop-b"}))),
+            ("loop-b".to_owned(), values(json!({"extends": ["loop-a"]}))),
+        ])
+    }
+
+    #[test]
+    fn resolve_test() {
+        let defaults = values(json!({"username": "deploy", "port": 2222}));
+        let templates = build_templates();
+        let inheritance = Inheritance::new(&defaults, &templates);
+
+        let (merged, sources) = inheritance
+            .resolve(&values(json!({
+                "extends": ["typo3"],
+                "host": "shop.example.com",
+                "port": 22
+            })))
+            .unwrap();
+
+        assert_eq!(
+            values(json!({
+                "command": "vendor/bin/fleet",
+                "host": "shop.example.com",
+                "port": 22,
+                "update_command": "apt-get upgrade -y",
+                "username": "deploy"
+            })),
+            merged
+        );
+        assert_eq!(
+            FieldSources::from([
+                (
+                    "command".to_owned(),
+                    FieldSource::Template("typo3".to_owned())
+                ),
+                ("host".to_owned(), FieldSource::Host),
+                ("port".to_owned(), FieldSource::Host),
+                (
+                    "update_command".to_owned(),
+                    FieldSource::Template("web".to_owned())
+                ),
+                ("username".to_owned(), FieldSource::Defaults),
+            ]),
+            sources
+        );
+    }
+
+    #[test]
+    fn resolve_error_test() {
+        let defaults = HostValues::new();
+        let templates = build_templates();
+        let inheritance = Inheritance::new(&defaults, &templates);
+
+        let error = inheritance
+            .resolve(&values(json!({"extends": "missing"})))
+            .unwrap_err();
+        assert_eq!("The template 'missing' is not defined", error.message());
+
+        let error = inheritance
+            .resolve(&values(json!({"extends": "loop-a"})))
+            .unwrap_err();
+        assert_eq!(
+            "The templates extend each other in a cycle \
+             (loop-a -> loop-b -> loop-a)",
+            error.message()
+        );
+
+        assert!(inheritance.resolve(&values(json!({"extends": 1}))).is_err());
+
+        let defaults = values(json!({"extends": "web"}));
+        assert!(Inheritance::new(&defaults, &templates)
+            .resolve(&HostValues::new())
+            .is_err());
+    }
+
+    #[test]
+    fn describe_configuration_test() {
+        let configuration = Configuration::new_with_password(
+            "shop.example.com",
+            22,
+            "fleet provide",
+            "deploy",
+            "s3cr3t",
+        );
+        let sources = FieldSources::from([
+            ("host".to_owned(), FieldSource::Host),
+            ("password".to_owned(), FieldSource::Defaults),
+        ]);
+
+        let fields = describe_configuration(&configuration, &sources);
+        let field = |name: &str| fields.iter().find(|f| f.name == name);
+
+        assert_eq!(FieldSource::Host, field("host").unwrap().source);
+        assert_eq!(json!("********"), field("password").unwrap().value);
+        assert_eq!(FieldSource::Defaults, field("password").unwrap().source);
+        assert_eq!(FieldSource::BuiltIn, field("disabled").unwrap().source);
+        assert!(field("private_key").is_none());
+        assert_eq!(
+            "template:web",
+            FieldSource::Template("web".to_owned()).to_string()
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use super::ssh_config::SshConfigFile;
+use super::{Configuration, ConfigurationCollection};
+use crate::error::Error;
+
+/// Resolve the `proxy_jump` chains of all configurations
+///
+/// Each entry of a chain is either the name of another configured host or a
+/// `[user@]host[:port]` address, which is completed with the SSH client
+/// configuration. The chains of the jump hosts (including a `ProxyJump` of the
+/// SSH client configuration) are resolved as well
+pub fn resolve_jump_hosts(
+    collection: ConfigurationCollection,
+    ssh_config: &SshConfigFile,
+) -> Result<ConfigurationCollection, Error> {
+    let mut resolved = ConfigurationCollection::new();
+    for (name, configuration) in &collection {
+        let jump_hosts = resolve_chain(
+            configuration,
+            &collection,
+            ssh_config,
+            &mut vec![name.clone()],
+        )?;
+        resolved.insert(
+            name.clone(),
+            configuration.clone().with_jump_hosts(jump_hosts),
+        );
+    }
+
+    Ok(resolved)
+}
+
+fn resolve_chain(
+    configuration: &Configuration,
+    collection: &ConfigurationCollection,
+    ssh_config: &SshConfigFile,
+    visited: &mut Vec<String>,
+) -> Result<Vec<Configuration>, Error> {
+    let proxy_jump = match configuration.proxy_jump() {
+        Some(p) if !p.trim().is_empty() && p.trim() != "none" => p,
+        _ => return Ok(vec![]),
+    };
+
+    let mut jump_hosts = vec![];
+    for entry in proxy_jump.split(',').map(str::trim) {
+        if visited.iter().any(|v| v == entry) {
+            return Err(Error::config(format!(
+                "The proxy_jump chain of host \"{}\" contains a loop: {} -> {}",
+                visited[0],
+                visited.join(" -> "),
+                entry
+            )));
+        }
+
+        let jump_configuration = match collection.get(entry) {
+            Some(jump_configuration) => jump_configuration.clone(),
+            None => {
+                let jump_configuration = parse_address(entry)?;
+                let options = ssh_config.lookup(jump_configuration.host());
+                jump_configuration.with_ssh_options(options)
+            }
+        };
+
+        visited.push(entry.to_owned());
+        jump_hosts.extend(resolve_chain(
+            &jump_configuration,
+            collection,
+            ssh_config,
+            visited,
+        )?);
+        visited.pop();
+        jump_hosts.push(jump_configuration.with_jump_hosts(vec![]));
+    }
+
+    Ok(jump_hosts)
+}
+
+/// Parse a jump host address in the form `[user@]host[:port]`
+fn parse_address(address: &str) -> Result<Configuration, Error> {
+    let (username, host_and_port) = match address.rsplit_once('@') {
+        Some((username, rest)) => (Some(username.to_owned()), rest),
+        None => (None, address),
+    };
+
+    let (host, port) = match host_and_port.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => {
+                return Err(Error::config(format!(
+                    "Invalid port in proxy_jump address \"{}\"",
+                    address
+                )))
+            }
+        },
+        None => (host_and_port, None),
+    };
+
+    if host.is_empty() {
+        return Err(Error::config(format!(
+            "Missing host in proxy_jump address \"{}\"",
+            address
+        )));
+    }
+
+    Ok(Configuration::for_jump_host(
+        host.to_owned(),
+        port,
+        username,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn build_configuration(host: &str, proxy_jump: &str) -> Configuration {
+        serde_json::from_value(serde_json::json!({
+            "host": host,
+            "command": "command",
+            "username": "user",
+            "proxy_jump": proxy_jump,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_address_test() {
+        let c = parse_address("admin@bastion.example.com:2222").unwrap();
+        assert_eq!("bastion.example.com", c.host());
+        assert_eq!(2222, c.port());
+        assert_eq!("admin", c.username());
+
+        let c = parse_address("bastion.example.com").unwrap();
+        assert_eq!("bastion.example.com", c.host());
+        assert_eq!(22, c.port());
+
+        assert!(parse_address("bastion:port").is_err());
+        assert!(parse_address("admin@").is_err());
+    }
+
+    #[test]
+    fn resolve_jump_hosts_test() {
+        let mut collection = ConfigurationCollection::new();
+        collection.insert(
+            "web".to_owned(),
+            build_configuration("web.internal", "inner, admin@edge:2222"),
+        );
+        collection.insert(
+            "inner".to_owned(),
+            build_configuration("inner.internal", "outer"),
+        );
+        collection.insert(
+            "outer".to_owned(),
+            build_configuration("outer.example.com", "none"),
+        );
+
+        let resolved =
+            resolve_jump_hosts(collection, &SshConfigFile::default()).unwrap();
+
+        let hosts: Vec<&str> = resolved["web"]
+            .jump_hosts()
+            .iter()
+            .map(|c| c.host().as_str())
+            .collect();
+        assert_eq!(vec!["outer.example.com", "inner.internal", "edge"], hosts);
+        assert_eq!(2222, resolved["web"].jump_hosts()[2].port());
+        assert!(resolved["outer"].jump_hosts().is_empty());
+    }
+
+    #[test]
+    fn resolve_jump_hosts_loop_test() {
+        let mut collection = ConfigurationCollection::new();
+        collection.insert("a".to_owned(), build_configuration("a", "b"));
+        collection.insert("b".to_owned(), build_configuration("b", "a"));
+
+        let error = resolve_jump_hosts(collection, &SshConfigFile::default())
+            .unwrap_err();
+        assert_eq!(&ErrorKind::Config, error.kind());
+        assert!(error.message().contains("loop"));
+    }
+
+    #[test]
+    fn resolve_ssh_config_proxy_jump_test() {
+        let ssh_config = SshConfigFile::parse_str(
+            "Host bastion\n\
+             \x20   HostName bastion.example.com\n\
+             \x20   ProxyJump gateway.example.com:2200\n\
+             Host loop\n\
+             \x20   ProxyJump admin@loop\n",
+        )
+        .unwrap();
+
+        let mut collection = ConfigurationCollection::new();
+        collection
+            .insert("web".to_owned(), build_configuration("web", "bastion"));
+        let resolved = resolve_jump_hosts(collection, &ssh_config).unwrap();
+
+        let jump_hosts = resolved["web"].jump_hosts();
+        assert_eq!(2, jump_hosts.len());
+        assert_eq!("gateway.example.com", jump_hosts[0].host());
+        assert_eq!(2200, jump_hosts[0].port());
+        assert_eq!("bastion.example.com", jump_hosts[1].host());
+
+        let mut collection = ConfigurationCollection::new();
+        collection.insert("web".to_owned(), build_configuration("web", "loop"));
+        let error = resolve_jump_hosts(collection, &ssh_config).unwrap_err();
+        assert_eq!(&ErrorKind::Config, error.kind());
+        assert!(error.message().contains("loop"));
+    }
+}
@@ -3,11 +3,26 @@ mod configuration_file_provider;
 mod configuration_provider;
 #[cfg(test)]
 pub mod helper;
+mod inheritance;
+mod jump_hosts;
+mod secret;
+mod settings;
+mod ssh_config;
 mod ssh_configuration;
 
 pub use self::configuration_collection::*;
-pub use self::configuration_file_provider::detect_configuration_file;
-pub use self::configuration_provider::ConfigurationProvider;
+pub use self::configuration_file_provider::{
+    configuration_namespace, detect_configuration_file,
+};
+pub use self::configuration_provider::{
+    ConfigurationProvider, FleetConfiguration,
+};
+pub use self::inheritance::{
+    describe_configuration, ConfigurationField, FieldSource,
+};
+pub use self::secret::Secret;
+pub use self::settings::Settings;
 use self::ssh_configuration::SshConfiguration;
+pub use self::ssh_configuration::{AuthMethod, HostKeyCheck};
 
 pub type Configuration = SshConfiguration;
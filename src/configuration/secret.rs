@@ -0,0 +1,227 @@
+use super::ssh_configuration::patch_key_path;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Password or passphrase of a host configuration
+///
+/// The secret is either defined as plain text or as a reference to the source
+/// of the secret. References are only resolved when connecting to the host:
+///
+/// - `{"env": "SHOP_PW"}` reads the environment variable
+/// - `{"file": "~/.secrets/shop"}` reads the file (without the trailing line
+///   break)
+/// - `{"command": "pass show shop/ssh"}` runs the command through `sh -c` and
+///   uses the first line of its output
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(
+    untagged,
+    expecting = "a string or one of `{env: ..}`, `{file: ..}` or \
+                 `{command: ..}`"
+)]
+pub enum Secret {
+    Plain(String),
+    Reference(SecretReference),
+}
+
+/// Source to read a secret from
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+pub enum SecretReference {
+    Env(String),
+    File(PathBuf),
+    Command(String),
+}
+
+impl Secret {
+    /// Return the value of the secret
+    ///
+    /// The error messages describe the reference, but never contain the
+    /// (partially) resolved value
+    pub fn resolve(&self) -> Result<String, Error> {
+        match self {
+            Secret::Plain(value) => Ok(value.clone()),
+            Secret::Reference(reference) => reference.resolve(),
+        }
+    }
+}
+
+impl SecretReference {
+    fn resolve(&self) -> Result<String, Error> {
+        match self {
+            SecretReference::Env(name) => match env::var(name) {
+                Ok(value) => Ok(value),
+                Err(_) => Err(Error::config(format!(
+                    "Could not read the secret from the environment variable \
+                     '{}': the variable is not set or not valid unicode",
+                    name
+                ))),
+            },
+            SecretReference::File(path) => {
+                let path = patch_key_path(path).unwrap_or(path.clone());
+                match fs::read_to_string(&path) {
+                    Ok(content) => {
+                        Ok(content.trim_end_matches(['\r', '\n']).to_owned())
+                    }
+                    Err(e) => Err(Error::config(format!(
+                        "Could not read the secret from the file '{}': {}",
+                        path.to_string_lossy(),
+                        e
+                    ))),
+                }
+            }
+            SecretReference::Command(command) => resolve_command(command),
+        }
+    }
+}
+
+fn resolve_command(command: &str) -> Result<String, Error> {
+    let output = match Command::new("sh").arg("-c").arg(command).output() {
+        Ok(output) => output,
+        Err(e) => {
+            return Err(Error::config(format!(
+                "Could not run the secret command '{}': {}",
+                command, e
+            )))
+        }
+    };
+
+    // The standard output is never part of the error, because it may contain
+    // the secret
+    if !output.status.success() {
+        return Err(Error::config(format!(
+            "The secret command '{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    match String::from_utf8(output.stdout) {
+        Ok(stdout) => match stdout.lines().next() {
+            Some(line) if !line.is_empty() => Ok(line.to_owned()),
+            _ => Err(Error::config(format!(
+                "The secret command '{}' did not output a secret",
+                command
+            ))),
+        },
+        Err(_) => Err(Error::config(format!(
+            "The output of the secret command '{}' is not valid unicode",
+            command
+        ))),
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret::Plain(value)
+    }
+}
+
+/// Only references are shown, plain text secrets are masked
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Secret::Plain(_) => f.write_str("Plain(***)"),
+            Secret::Reference(reference) => reference.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<Secret, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn deserialize_test() {
+        assert_eq!(
+            Secret::Plain("s3cr3t".to_owned()),
+            parse(r#""s3cr3t""#).unwrap()
+        );
+        assert_eq!(
+            Secret::Reference(SecretReference::Env("SHOP_PW".to_owned())),
+            parse(r#"{"env": "SHOP_PW"}"#).unwrap()
+        );
+        assert_eq!(
+            Secret::Reference(SecretReference::File(PathBuf::from(
+                "~/.secrets/shop"
+            ))),
+            parse(r#"{"file": "~/.secrets/shop"}"#).unwrap()
+        );
+        assert_eq!(
+            Secret::Reference(SecretReference::Command(
+                "pass show shop/ssh".to_owned()
+            )),
+            parse(r#"{"command": "pass show shop/ssh"}"#).unwrap()
+        );
+
+        let error = parse(r#"{"vault": "shop"}"#).unwrap_err();
+        assert!(error.to_string().contains("`{env: ..}`"), "{}", error);
+        assert!(parse(r#"{"env": "A", "file": "b"}"#).is_err());
+    }
+
+    #[test]
+    fn resolve_plain_test() {
+        assert_eq!(
+            "s3cr3t",
+            Secret::from("s3cr3t".to_owned()).resolve().unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_env_test() {
+        let secret = parse(r#"{"env": "PATH"}"#).unwrap();
+        assert_eq!(env::var("PATH").unwrap(), secret.resolve().unwrap());
+
+        let secret = parse(r#"{"env": "FLEET_TEST_MISSING_SECRET"}"#).unwrap();
+        let error = secret.resolve().unwrap_err();
+        assert!(error.message().contains("FLEET_TEST_MISSING_SECRET"));
+    }
+
+    #[test]
+    fn resolve_file_test() {
+        let path = env::temp_dir()
+            .join(format!("fleet-secret-test-{}", std::process::id()));
+        fs::write(&path, "s3cr3t\n").unwrap();
+        let secret = Secret::Reference(SecretReference::File(path.clone()));
+        assert_eq!("s3cr3t", secret.resolve().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        let error = secret.resolve().unwrap_err();
+        assert!(error.message().contains("fleet-secret-test"));
+    }
+
+    #[test]
+    fn resolve_command_test() {
+        let secret = parse(r#"{"command": "echo s3cr3t; echo rest"}"#).unwrap();
+        assert_eq!("s3cr3t", secret.resolve().unwrap());
+
+        let secret =
+            parse(r#"{"command": "echo $((6*7)); echo denied >&2; exit 3"}"#)
+                .unwrap();
+        let error = secret.resolve().unwrap_err();
+        assert!(error.message().contains("denied"), "{}", error.message());
+        assert!(!error.message().contains("42"), "{}", error.message());
+
+        let secret = parse(r#"{"command": "true"}"#).unwrap();
+        assert!(secret.resolve().is_err());
+    }
+
+    #[test]
+    fn debug_test() {
+        let secret = Secret::from("s3cr3t".to_owned());
+        assert!(!format!("{:?}", secret).contains("s3cr3t"));
+        assert_eq!(
+            r#"Env("SHOP_PW")"#,
+            format!("{:?}", parse(r#"{"env": "SHOP_PW"}"#).unwrap())
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use super::ssh_config::SshConfigFile;
+use super::Configuration;
+use crate::constants;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Global settings that apply to all hosts
+///
+/// The settings can be defined in the `settings` block of the configuration
+/// file and overridden through command line arguments
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Number of hosts to process in parallel
+    concurrency: Option<usize>,
+
+    /// Default number of seconds to wait for the connection and handshake
+    connect_timeout: Option<u64>,
+
+    /// Default number of seconds to wait for a command to finish
+    command_timeout: Option<u64>,
+
+    /// Path to the SSH client configuration (an empty path disables it)
+    ssh_config: Option<PathBuf>,
+
+    /// Ask for missing passphrases on the terminal
+    prompt: Option<bool>,
+
+    /// Report details like failed authentication attempts (command line only)
+    #[serde(skip)]
+    verbose: bool,
+}
+
+impl Settings {
+    pub fn new(
+        concurrency: Option<usize>,
+        connect_timeout: Option<u64>,
+        command_timeout: Option<u64>,
+    ) -> Self {
+        Settings {
+            concurrency,
+            connect_timeout,
+            command_timeout,
+            ssh_config: None,
+            prompt: None,
+            verbose: false,
+        }
+    }
+
+    /// Return a copy of these settings with prompting enabled or disabled
+    pub fn with_prompt(self, prompt: bool) -> Self {
+        Settings {
+            prompt: Some(prompt),
+            ..self
+        }
+    }
+
+    /// Return the number of hosts to process in parallel (at least one)
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+            .unwrap_or(constants::DEFAULT_CONCURRENCY)
+            .max(1)
+    }
+
+    /// Return the timeout for connecting to the host in `configuration`
+    ///
+    /// `None` is returned if the timeout is disabled (set to `0`)
+    pub fn connect_timeout(
+        &self,
+        configuration: &Configuration,
+    ) -> Option<Duration> {
+        as_timeout(
+            configuration
+                .connect_timeout()
+                .or(self.connect_timeout)
+                .unwrap_or(constants::DEFAULT_CONNECT_TIMEOUT),
+        )
+    }
+
+    /// Return the timeout for commands executed on the host in `configuration`
+    ///
+    /// `None` is returned if the timeout is disabled (set to `0`)
+    pub fn command_timeout(
+        &self,
+        configuration: &Configuration,
+    ) -> Option<Duration> {
+        as_timeout(
+            configuration
+                .command_timeout()
+                .or(self.command_timeout)
+                .unwrap_or(constants::DEFAULT_COMMAND_TIMEOUT),
+        )
+    }
+
+    /// Return the timeout for the update command of the host in
+    /// `configuration`
+    ///
+    /// Updates may take long, so unlike other commands they only time out if
+    /// `command_timeout` is set for the host or the run. `None` is returned
+    /// if the timeout is disabled or not set
+    pub fn update_timeout(
+        &self,
+        configuration: &Configuration,
+    ) -> Option<Duration> {
+        as_timeout(
+            configuration
+                .command_timeout()
+                .or(self.command_timeout)
+                .unwrap_or(0),
+        )
+    }
+
+    /// Return the path to the SSH client configuration
+    ///
+    /// Defaults to `~/.ssh/config`. `None` is returned if the SSH client
+    /// configuration is disabled
+    pub fn ssh_config(&self) -> Option<PathBuf> {
+        match self.ssh_config {
+            Some(ref path) if path.as_os_str().is_empty() => None,
+            Some(ref path) => match path.strip_prefix("~") {
+                Ok(relative) => {
+                    dirs::home_dir().map(|home| home.join(relative))
+                }
+                Err(_) => Some(path.clone()),
+            },
+            None => SshConfigFile::default_path(),
+        }
+    }
+
+    /// Return a copy of these settings with verbose reporting enabled or
+    /// disabled
+    pub fn with_verbose(self, verbose: bool) -> Self {
+        Settings { verbose, ..self }
+    }
+
+    /// Return if details like failed authentication attempts are reported
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Return if missing passphrases may be asked for on the terminal
+    pub fn prompt(&self) -> bool {
+        self.prompt.unwrap_or(true)
+    }
+
+    /// Return a copy of these settings where all values defined in
+    /// `overrides` replace the current values
+    pub fn merge(&self, overrides: &Settings) -> Settings {
+        Settings {
+            concurrency: overrides.concurrency.or(self.concurrency),
+            connect_timeout: overrides.connect_timeout.or(self.connect_timeout),
+            command_timeout: overrides.command_timeout.or(self.command_timeout),
+            ssh_config: overrides
+                .ssh_config
+                .clone()
+                .or_else(|| self.ssh_config.clone()),
+            prompt: overrides.prompt.or(self.prompt),
+            verbose: overrides.verbose || self.verbose,
+        }
+    }
+
+    /// Apply the timeouts defined in these settings to the configuration
+    ///
+    /// This is used for command line arguments, which take precedence over
+    /// the values of the individual hosts
+    pub fn override_configuration(
+        &self,
+        configuration: Configuration,
+    ) -> Configuration {
+        let connect_timeout =
+            self.connect_timeout.or(configuration.connect_timeout());
+        let command_timeout =
+            self.command_timeout.or(configuration.command_timeout());
+
+        configuration.with_timeouts(connect_timeout, command_timeout)
+    }
+}
+
+fn as_timeout(seconds: u64) -> Option<Duration> {
+    if seconds == 0 {
+        None
+    } else {
+        Some(Duration::from_secs(seconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrency_test() {
+        assert_eq!(
+            constants::DEFAULT_CONCURRENCY,
+            Settings::default().concurrency()
+        );
+        assert_eq!(16, Settings::new(Some(16), None, None).concurrency());
+        assert_eq!(1, Settings::new(Some(0), None, None).concurrency());
+    }
+
+    #[test]
+    fn merge_test() {
+        let file_settings = Settings::new(Some(8), Some(5), None);
+
+        let merged = file_settings.merge(&Settings::default());
+        assert_eq!(file_settings, merged);
+
+        let merged =
+            file_settings.merge(&Settings::new(Some(32), None, Some(9)));
+        assert_eq!(Settings::new(Some(32), Some(5), Some(9)), merged);
+    }
+
+    #[test]
+    fn prompt_test() {
+        assert!(Settings::default().prompt());
+
+        let file_settings: Settings =
+            serde_json::from_str(r#"{"prompt": false}"#).unwrap();
+        assert!(!file_settings.prompt());
+        assert!(!file_settings.merge(&Settings::default()).prompt());
+        assert!(file_settings
+            .merge(&Settings::default().with_prompt(true))
+            .prompt());
+    }
+
+    #[test]
+    fn timeout_test() {
+        let configuration = Configuration::new_empty();
+        assert_eq!(
+            Some(Duration::from_secs(constants::DEFAULT_CONNECT_TIMEOUT)),
+            Settings::default().connect_timeout(&configuration)
+        );
+        assert_eq!(
+            Some(Duration::from_secs(constants::DEFAULT_COMMAND_TIMEOUT)),
+            Settings::default().command_timeout(&configuration)
+        );
+
+        let settings = Settings::new(None, Some(3), Some(0));
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            settings.connect_timeout(&configuration)
+        );
+        assert_eq!(None, settings.command_timeout(&configuration));
+        assert_eq!(None, Settings::default().update_timeout(&configuration));
+
+        let configuration = configuration.with_timeouts(Some(7), Some(60));
+        assert_eq!(
+            Some(Duration::from_secs(7)),
+            settings.connect_timeout(&configuration)
+        );
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            settings.command_timeout(&configuration)
+        );
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            settings.update_timeout(&configuration)
+        );
+    }
+
+    #[test]
+    fn override_configuration_test() {
+        let configuration =
+            Configuration::new_empty().with_timeouts(Some(7), Some(60));
+        let overrides = Settings::new(None, Some(1), None);
+        let configuration = overrides.override_configuration(configuration);
+
+        assert_eq!(Some(1), configuration.connect_timeout());
+        assert_eq!(Some(60), configuration.command_timeout());
+    }
+
+    #[test]
+    fn ssh_config_test() {
+        assert_eq!(
+            SshConfigFile::default_path(),
+            Settings::default().ssh_config()
+        );
+
+        let settings: Settings =
+            serde_json::from_str(r#"{"ssh_config": "/etc/fleet/ssh_config"}"#)
+                .unwrap();
+        assert_eq!(
+            Some(PathBuf::from("/etc/fleet/ssh_config")),
+            settings.ssh_config()
+        );
+
+        let settings: Settings =
+            serde_json::from_str(r#"{"ssh_config": ""}"#).unwrap();
+        assert_eq!(None, settings.ssh_config());
+    }
+}
@@ -0,0 +1,439 @@
+use crate::error::Error;
+use crate::filter::wildcard_match;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum depth of nested `Include` directives
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Options of an OpenSSH client configuration that apply to a host
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SshHostOptions {
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<PathBuf>,
+    pub proxy_jump: Option<String>,
+}
+
+#[derive(Debug)]
+struct HostBlock {
+    /// Patterns of the `Host` line
+    ///
+    /// `Match` blocks are not supported and never match (`None`)
+    patterns: Option<Vec<String>>,
+    options: Vec<(String, String)>,
+}
+
+impl HostBlock {
+    fn new(patterns: Option<Vec<String>>) -> Self {
+        HostBlock {
+            patterns,
+            options: vec![],
+        }
+    }
+
+    fn matches(&self, alias: &str) -> bool {
+        let patterns = match self.patterns {
+            Some(ref patterns) => patterns,
+            None => return false,
+        };
+
+        let mut matched = false;
+        for pattern in patterns {
+            if let Some(negated) = pattern.strip_prefix('!') {
+                if wildcard_match(negated, alias) {
+                    return false;
+                }
+            } else if wildcard_match(pattern, alias) {
+                matched = true;
+            }
+        }
+
+        matched
+    }
+}
+
+/// Parsed OpenSSH client configuration (e.g. `~/.ssh/config`)
+#[derive(Debug, Default)]
+pub struct SshConfigFile {
+    blocks: Vec<HostBlock>,
+    /// Directory against which relative `Include` paths are resolved
+    base_directory: PathBuf,
+}
+
+impl SshConfigFile {
+    /// Load the configuration file
+    ///
+    /// A missing file results in an empty configuration. Relative `Include`
+    /// paths are resolved against the file's directory (i.e. `~/.ssh` for
+    /// the user's configuration)
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut config = SshConfigFile {
+            blocks: vec![],
+            base_directory: path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        if path.exists() {
+            config.parse_file(path, None, 0)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Return the path of the user's configuration (`~/.ssh/config`)
+    pub fn default_path() -> Option<PathBuf> {
+        get_ssh_dir().map(|mut path| {
+            path.push("config");
+            path
+        })
+    }
+
+    /// Parse the configuration from a string
+    #[cfg(test)]
+    pub fn parse_str(content: &str) -> Result<Self, Error> {
+        let mut config = SshConfigFile::default();
+        config.parse(content, None, 0)?;
+
+        Ok(config)
+    }
+
+    /// Look up the options for the given host alias
+    ///
+    /// Like OpenSSH the first value found for each option is used. `%h` is
+    /// replaced by the alias in `HostName` and by the resolved host name in
+    /// `IdentityFile`
+    pub fn lookup(&self, alias: &str) -> SshHostOptions {
+        let mut options = SshHostOptions::default();
+        let mut identity_file: Option<&str> = None;
+
+        for block in self.blocks.iter().filter(|b| b.matches(alias)) {
+            for (keyword, value) in &block.options {
+                match keyword.as_str() {
+                    "hostname" if options.host_name.is_none() => {
+                        options.host_name = Some(value.replace("%h", alias))
+                    }
+                    "user" if options.user.is_none() => {
+                        options.user = Some(value.clone())
+                    }
+                    "port" if options.port.is_none() => {
+                        options.port = value.parse().ok()
+                    }
+                    "identityfile" if identity_file.is_none() => {
+                        identity_file = Some(value)
+                    }
+                    "proxyjump" if options.proxy_jump.is_none() => {
+                        options.proxy_jump = Some(value.clone())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(identity_file) = identity_file {
+            let host_name = options.host_name.as_deref().unwrap_or(alias);
+            options.identity_file =
+                Some(PathBuf::from(identity_file.replace("%h", host_name)));
+        }
+
+        options
+    }
+
+    fn parse_file(
+        &mut self,
+        path: &Path,
+        patterns: Option<Vec<String>>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => self.parse(&content, patterns, depth),
+            Err(e) => Err(Error::config(format!(
+                "Could not read SSH configuration '{}': {}",
+                path.to_string_lossy(),
+                e
+            ))),
+        }
+    }
+
+    fn parse(
+        &mut self,
+        content: &str,
+        patterns: Option<Vec<String>>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        // Lines before the first `Host` apply to all hosts
+        let mut current =
+            HostBlock::new(patterns.or_else(|| Some(vec!["*".to_owned()])));
+
+        for line in content.lines() {
+            let (keyword, arguments) = match split_line(line) {
+                Some(l) => l,
+                None => continue,
+            };
+
+            match keyword.as_str() {
+                "host" => {
+                    self.blocks.push(current);
+                    current = HostBlock::new(Some(arguments));
+                }
+                "match" => {
+                    self.blocks.push(current);
+                    current = HostBlock::new(None);
+                }
+                "include" => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        return Err(Error::config(
+                            "Too many nested Include directives in the SSH configuration",
+                        ));
+                    }
+
+                    let patterns = current.patterns.clone();
+                    self.blocks.push(current);
+                    for argument in arguments {
+                        let paths =
+                            expand_include(&argument, &self.base_directory);
+                        for path in paths {
+                            self.parse_file(
+                                &path,
+                                patterns.clone(),
+                                depth + 1,
+                            )?;
+                        }
+                    }
+                    current = HostBlock::new(patterns);
+                }
+                _ => {
+                    if let Some(value) = arguments.into_iter().next() {
+                        current.options.push((keyword, value));
+                    }
+                }
+            }
+        }
+        self.blocks.push(current);
+
+        Ok(())
+    }
+}
+
+/// Split a line into the lowercase keyword and its arguments
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (keyword, rest) =
+        match line.find(|c: char| c.is_whitespace() || c == '=') {
+            Some(position) => (&line[..position], &line[position..]),
+            None => (line, ""),
+        };
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+
+    Some((keyword.to_lowercase(), split_arguments(rest)))
+}
+
+/// Split the arguments at whitespace, respecting double quotes
+fn split_arguments(input: &str) -> Vec<String> {
+    let mut arguments = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_argument = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_argument = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    has_argument = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_argument = true;
+            }
+        }
+    }
+    if has_argument {
+        arguments.push(current);
+    }
+
+    arguments
+}
+
+/// Return the files matching the `Include` argument
+///
+/// Relative paths are resolved against `base_directory`. Wildcards are
+/// supported in the file name
+pub(super) fn expand_include(
+    argument: &str,
+    base_directory: &Path,
+) -> Vec<PathBuf> {
+    let path = if let Some(relative) = argument.strip_prefix("~/") {
+        match dirs::home_dir() {
+            Some(home) => home.join(relative),
+            None => return vec![],
+        }
+    } else if Path::new(argument).is_absolute() {
+        PathBuf::from(argument)
+    } else {
+        base_directory.join(argument)
+    };
+
+    let file_name = match path.file_name() {
+        Some(f) => f.to_string_lossy().to_string(),
+        None => return vec![],
+    };
+    if !file_name.contains(['*', '?']) {
+        return if path.is_file() { vec![path] } else { vec![] };
+    }
+
+    let directory = match path.parent() {
+        Some(d) => d,
+        None => return vec![],
+    };
+    let mut paths: Vec<PathBuf> = match fs::read_dir(directory) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file()
+                    && p.file_name().is_some_and(|n| {
+                        wildcard_match(&file_name, &n.to_string_lossy())
+                    })
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    paths.sort();
+
+    paths
+}
+
+fn get_ssh_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|mut path| {
+        path.push(".ssh");
+        path
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers;
+
+    #[test]
+    fn lookup_test() {
+        let config = SshConfigFile::parse_str(
+            r#"
+# Comment
+Host shop shop-staging
+    HostName %h.example.com
+    User deploy
+    Port 2222
+    IdentityFile ~/.ssh/shop_key
+    ProxyJump bastion
+
+Host *.internal !db.internal
+    User=internal
+
+Host *
+    User fallback
+    IdentityFile "~/.ssh/id ed25519"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            SshHostOptions {
+                host_name: Some("shop.example.com".to_owned()),
+                user: Some("deploy".to_owned()),
+                port: Some(2222),
+                identity_file: Some(PathBuf::from("~/.ssh/shop_key")),
+                proxy_jump: Some("bastion".to_owned()),
+            },
+            config.lookup("shop")
+        );
+        assert_eq!(
+            Some("shop-staging.example.com".to_owned()),
+            config.lookup("shop-staging").host_name
+        );
+
+        // `%h` in `IdentityFile` is the resolved host name
+        let expanded = SshConfigFile::parse_str(
+            "Host blog\n    IdentityFile ~/.ssh/%h\n    HostName %h.example.org\n\
+             Host *\n    IdentityFile ~/.ssh/keys/%h\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Some(PathBuf::from("~/.ssh/blog.example.org")),
+            expanded.lookup("blog").identity_file
+        );
+        assert_eq!(
+            Some(PathBuf::from("~/.ssh/keys/other")),
+            expanded.lookup("other").identity_file
+        );
+        assert_eq!(
+            Some("internal".to_owned()),
+            config.lookup("web.internal").user
+        );
+        assert_eq!(
+            Some("fallback".to_owned()),
+            config.lookup("db.internal").user
+        );
+        assert_eq!(
+            SshHostOptions {
+                host_name: None,
+                user: Some("fallback".to_owned()),
+                port: None,
+                identity_file: Some(PathBuf::from("~/.ssh/id ed25519")),
+                proxy_jump: None,
+            },
+            config.lookup("other")
+        );
+    }
+
+    #[test]
+    fn lookup_ignores_match_blocks_test() {
+        let config = SshConfigFile::parse_str(
+            "Match host shop\n    User matched\nHost shop\n    User host",
+        )
+        .unwrap();
+
+        assert_eq!(Some("host".to_owned()), config.lookup("shop").user);
+    }
+
+    #[test]
+    fn load_with_include_test() {
+        let path = test_helpers::get_test_resource_path("ssh_config/config");
+        let config = SshConfigFile::load(&path).unwrap();
+
+        assert_eq!(
+            Some("included.example.com".to_owned()),
+            config.lookup("included").host_name
+        );
+        assert_eq!(
+            Some("included-user".to_owned()),
+            config.lookup("included").user
+        );
+        assert_eq!(
+            Some("shop.example.com".to_owned()),
+            config.lookup("shop").host_name
+        );
+        assert_eq!(Some(2222), config.lookup("shop").port);
+    }
+
+    #[test]
+    fn load_missing_file_test() {
+        let path = test_helpers::get_test_resource_path("ssh_config/missing");
+        let config = SshConfigFile::load(&path).unwrap();
+
+        assert_eq!(SshHostOptions::default(), config.lookup("shop"));
+    }
+}
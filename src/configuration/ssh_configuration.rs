@@ -1,32 +1,120 @@
+use super::secret::Secret;
+use super::ssh_config::SshHostOptions;
 use serde::{Deserialize, Serialize};
 use std::path::*;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct SshConfiguration {
     host: String,
-
-    #[serde(default = "default_port")]
-    port: u16,
+    port: Option<u16>,
     command: String,
     update_command: Option<String>,
+    #[serde(default)]
     username: String,
-    password: Option<String>,
-    passphrase: Option<String>,
+    password: Option<Secret>,
+    passphrase: Option<Secret>,
     private_key: Option<PathBuf>,
     public_key: Option<PathBuf>,
     #[serde(default = "default_disabled")]
     disabled: bool,
+    connect_timeout: Option<u64>,
+    command_timeout: Option<u64>,
+    #[serde(default)]
+    host_key_check: HostKeyCheck,
+    auth_methods: Option<Vec<AuthMethod>>,
+    proxy_jump: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+
+    /// Hosts to tunnel through, resolved from `proxy_jump`
+    #[serde(skip)]
+    jump_hosts: Vec<SshConfiguration>,
+}
+
+/// Mode for verifying the host key against `~/.ssh/known_hosts`
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum HostKeyCheck {
+    /// Reject hosts that are unknown or whose key does not match
+    Strict,
+    /// Add unknown hosts, but reject hosts whose key does not match
+    #[default]
+    AcceptNew,
+    /// Do not verify the host key
+    Off,
+}
+
+/// Method to authenticate with
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthMethod {
+    /// Identities of the running `ssh-agent`
+    Agent,
+    /// The configured `private_key`
+    Publickey,
+    /// The configured (or entered) password
+    Password,
+    /// Answer the server's prompts with the password
+    KeyboardInteractive,
 }
 
-fn default_port() -> u16 {
-    22
+impl AuthMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuthMethod::Agent => "agent",
+            AuthMethod::Publickey => "publickey",
+            AuthMethod::Password => "password",
+            AuthMethod::KeyboardInteractive => "keyboard-interactive",
+        }
+    }
 }
 
+const DEFAULT_PORT: u16 = 22;
+
+/// Authentication methods that are tried if `auth_methods` is not configured
+///
+/// The password methods are only tried if a `password` is configured
+const DEFAULT_AUTH_METHODS: &[AuthMethod] = &[
+    AuthMethod::Publickey,
+    AuthMethod::Agent,
+    AuthMethod::Password,
+    AuthMethod::KeyboardInteractive,
+];
+
 fn default_disabled() -> bool {
     false
 }
 
 impl SshConfiguration {
+    /// Create the configuration for a jump host that is not configured itself
+    pub fn for_jump_host(
+        host: String,
+        port: Option<u16>,
+        username: Option<String>,
+    ) -> Self {
+        SshConfiguration {
+            host,
+            port,
+            command: String::new(),
+            update_command: None,
+            username: username.unwrap_or_default(),
+            password: None,
+            passphrase: None,
+            private_key: None,
+            public_key: None,
+            disabled: false,
+            connect_timeout: None,
+            command_timeout: None,
+            host_key_check: HostKeyCheck::default(),
+            auth_methods: None,
+            proxy_jump: None,
+            tags: vec![],
+            jump_hosts: vec![],
+        }
+    }
+
     #[cfg(test)]
     #[allow(clippy::too_many_arguments)]
     pub fn new<S, P>(
@@ -44,16 +132,23 @@ impl SshConfiguration {
         P: AsRef<Path>,
     {
         SshConfiguration {
-            port,
+            port: Some(port),
             host: host.into(),
             command: command.into(),
             update_command: None,
             username: username.into(),
-            password: password.map(|s| s.into()),
-            passphrase: passphrase.map(|s| s.into()),
+            password: password.map(|s| Secret::from(s.into())),
+            passphrase: passphrase.map(|s| Secret::from(s.into())),
             private_key: as_path_buf_option(private_key),
             public_key: as_path_buf_option(public_key),
             disabled: false,
+            connect_timeout: None,
+            command_timeout: None,
+            host_key_check: HostKeyCheck::default(),
+            auth_methods: None,
+            proxy_jump: None,
+            tags: vec![],
+            jump_hosts: vec![],
         }
     }
 
@@ -69,16 +164,23 @@ impl SshConfiguration {
         S: Into<String>,
     {
         SshConfiguration {
-            port,
+            port: Some(port),
             host: host.into(),
             command: command.into(),
             update_command: None,
             username: username.into(),
-            password: Some(password.into()),
+            password: Some(Secret::from(password.into())),
             passphrase: None,
             private_key: None,
             public_key: None,
             disabled: false,
+            connect_timeout: None,
+            command_timeout: None,
+            host_key_check: HostKeyCheck::default(),
+            auth_methods: None,
+            proxy_jump: None,
+            tags: vec![],
+            jump_hosts: vec![],
         }
     }
 
@@ -96,18 +198,25 @@ impl SshConfiguration {
         S: Into<String>,
         P: AsRef<Path>,
     {
-        let passphrase_string = passphrase.map(|p| p.into());
+        let passphrase_secret = passphrase.map(|p| Secret::from(p.into()));
         SshConfiguration {
-            port,
+            port: Some(port),
             host: host.into(),
             command: command.into(),
             update_command: None,
             username: username.into(),
             password: None,
-            passphrase: passphrase_string,
+            passphrase: passphrase_secret,
             private_key: Some(private_key.as_ref().to_path_buf()),
             public_key: as_path_buf_option(public_key),
             disabled: false,
+            connect_timeout: None,
+            command_timeout: None,
+            host_key_check: HostKeyCheck::default(),
+            auth_methods: None,
+            proxy_jump: None,
+            tags: vec![],
+            jump_hosts: vec![],
         }
     }
 
@@ -115,7 +224,7 @@ impl SshConfiguration {
     pub fn new_empty() -> Self {
         SshConfiguration {
             host: "".to_owned(),
-            port: 0,
+            port: None,
             update_command: None,
             command: "".to_owned(),
             username: "".to_owned(),
@@ -124,6 +233,13 @@ impl SshConfiguration {
             private_key: None,
             public_key: None,
             disabled: false,
+            connect_timeout: None,
+            command_timeout: None,
+            host_key_check: HostKeyCheck::default(),
+            auth_methods: None,
+            proxy_jump: None,
+            tags: vec![],
+            jump_hosts: vec![],
         }
     }
 
@@ -132,7 +248,7 @@ impl SshConfiguration {
     }
 
     pub fn port(&self) -> u16 {
-        self.port
+        self.port.unwrap_or(DEFAULT_PORT)
     }
 
     pub fn command(&self) -> &String {
@@ -147,12 +263,14 @@ impl SshConfiguration {
         &self.username
     }
 
-    pub fn password(&self) -> Option<String> {
-        self.password.clone()
+    /// Password (or the reference to it) to authenticate with
+    pub fn password(&self) -> Option<&Secret> {
+        self.password.as_ref()
     }
 
-    pub fn passphrase(&self) -> Option<String> {
-        self.passphrase.clone()
+    /// Passphrase (or the reference to it) of the private key
+    pub fn passphrase(&self) -> Option<&Secret> {
+        self.passphrase.as_ref()
     }
 
     pub fn private_key(&self) -> Option<PathBuf> {
@@ -172,6 +290,104 @@ impl SshConfiguration {
     pub fn disabled(&self) -> bool {
         self.disabled
     }
+
+    /// Number of seconds to wait for the connection and handshake
+    pub fn connect_timeout(&self) -> Option<u64> {
+        self.connect_timeout
+    }
+
+    /// Number of seconds to wait for a command to finish
+    pub fn command_timeout(&self) -> Option<u64> {
+        self.command_timeout
+    }
+
+    /// Mode for verifying the host key
+    pub fn host_key_check(&self) -> HostKeyCheck {
+        self.host_key_check
+    }
+
+    /// Authentication methods in the order they are tried
+    ///
+    /// Without `auth_methods` the password is only used if it is configured,
+    /// so it is never asked for on the terminal
+    pub fn auth_methods(&self) -> Vec<AuthMethod> {
+        match self.auth_methods {
+            Some(ref methods) => methods.clone(),
+            None => DEFAULT_AUTH_METHODS
+                .iter()
+                .copied()
+                .filter(|method| {
+                    self.password.is_some()
+                        || !matches!(
+                            method,
+                            AuthMethod::Password
+                                | AuthMethod::KeyboardInteractive
+                        )
+                })
+                .collect(),
+        }
+    }
+
+    /// Comma separated chain of hosts to tunnel through
+    pub fn proxy_jump(&self) -> Option<&str> {
+        self.proxy_jump.as_deref()
+    }
+
+    /// Tags to select the host by
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Resolved hosts to tunnel through (in the order of the connections)
+    pub fn jump_hosts(&self) -> &[SshConfiguration] {
+        &self.jump_hosts
+    }
+
+    /// Return a copy of the configuration with the given jump hosts
+    pub fn with_jump_hosts(self, jump_hosts: Vec<SshConfiguration>) -> Self {
+        SshConfiguration { jump_hosts, ..self }
+    }
+
+    /// Return a copy of the configuration with the given timeouts
+    pub fn with_timeouts(
+        self,
+        connect_timeout: Option<u64>,
+        command_timeout: Option<u64>,
+    ) -> Self {
+        SshConfiguration {
+            connect_timeout,
+            command_timeout,
+            ..self
+        }
+    }
+
+    /// Return a copy of the configuration completed with the options from the
+    /// SSH client configuration
+    ///
+    /// Values defined in the fleet configuration take precedence. If no user
+    /// is defined at all, the local user name is used (like `ssh` does)
+    pub fn with_ssh_options(self, options: SshHostOptions) -> Self {
+        let username = if !self.username.is_empty() {
+            self.username
+        } else {
+            options.user.or_else(local_username).unwrap_or_default()
+        };
+
+        SshConfiguration {
+            host: options.host_name.unwrap_or(self.host),
+            port: self.port.or(options.port),
+            username,
+            private_key: self.private_key.or(options.identity_file),
+            proxy_jump: self.proxy_jump.or(options.proxy_jump),
+            ..self
+        }
+    }
+}
+
+fn local_username() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
 }
 
 #[cfg(test)]
@@ -179,7 +395,7 @@ fn as_path_buf_option<P: AsRef<Path>>(input: Option<P>) -> Option<PathBuf> {
     input.map(|p| p.as_ref().to_path_buf())
 }
 
-fn patch_key_path(p: &Path) -> Option<PathBuf> {
+pub(super) fn patch_key_path(p: &Path) -> Option<PathBuf> {
     if p.starts_with("~/") {
         let path_relative: String =
             p.to_string_lossy().chars().skip(2).collect();
@@ -217,7 +433,7 @@ mod tests {
         assert_eq!(
             SshConfiguration {
                 host: "localhost".to_owned(),
-                port: 22,
+                port: Some(22),
                 command: "cmd".to_owned(),
                 update_command: None,
                 username: "daniel".to_owned(),
@@ -225,7 +441,14 @@ mod tests {
                 passphrase: None,
                 private_key: Some(private_key.clone()),
                 public_key: None,
-                disabled: false
+                disabled: false,
+                connect_timeout: None,
+                command_timeout: None,
+                host_key_check: HostKeyCheck::AcceptNew,
+                auth_methods: None,
+                proxy_jump: None,
+                tags: vec![],
+                jump_hosts: vec![],
             },
             SshConfiguration::new_with_public_key(
                 "localhost",
@@ -241,7 +464,7 @@ mod tests {
         assert_eq!(
             SshConfiguration {
                 host: "localhost".to_owned(),
-                port: 22,
+                port: Some(22),
                 command: "cmd".to_owned(),
                 update_command: None,
                 username: "daniel".to_owned(),
@@ -249,7 +472,14 @@ mod tests {
                 passphrase: None,
                 private_key: Some(private_key.clone()),
                 public_key: Some(public_key.clone()),
-                disabled: false
+                disabled: false,
+                connect_timeout: None,
+                command_timeout: None,
+                host_key_check: HostKeyCheck::AcceptNew,
+                auth_methods: None,
+                proxy_jump: None,
+                tags: vec![],
+                jump_hosts: vec![],
             },
             SshConfiguration::new_with_public_key(
                 "localhost",
@@ -265,15 +495,22 @@ mod tests {
         assert_eq!(
             SshConfiguration {
                 host: "localhost".to_owned(),
-                port: 22,
+                port: Some(22),
                 command: "cmd".to_owned(),
                 update_command: None,
                 username: "daniel".to_owned(),
                 password: None,
-                passphrase: Some("passphrase".to_owned()),
+                passphrase: Some(Secret::from("passphrase".to_owned())),
                 private_key: Some(private_key.clone()),
                 public_key: Some(public_key.clone()),
-                disabled: false
+                disabled: false,
+                connect_timeout: None,
+                command_timeout: None,
+                host_key_check: HostKeyCheck::AcceptNew,
+                auth_methods: None,
+                proxy_jump: None,
+                tags: vec![],
+                jump_hosts: vec![],
             },
             SshConfiguration::new_with_public_key(
                 "localhost",
@@ -292,15 +529,22 @@ mod tests {
         assert_eq!(
             SshConfiguration {
                 host: "localhost".to_owned(),
-                port: 22,
+                port: Some(22),
                 command: "cmd".to_owned(),
                 update_command: None,
                 username: "daniel".to_owned(),
-                password: Some("password".to_owned()),
+                password: Some(Secret::from("password".to_owned())),
                 passphrase: None,
                 private_key: None,
                 public_key: None,
-                disabled: false
+                disabled: false,
+                connect_timeout: None,
+                command_timeout: None,
+                host_key_check: HostKeyCheck::AcceptNew,
+                auth_methods: None,
+                proxy_jump: None,
+                tags: vec![],
+                jump_hosts: vec![],
             },
             SshConfiguration::new_with_password(
                 "localhost",
@@ -317,7 +561,7 @@ mod tests {
         assert_eq!(
             SshConfiguration {
                 host: "".to_owned(),
-                port: 0,
+                port: None,
                 command: "".to_owned(),
                 update_command: None,
                 username: "".to_owned(),
@@ -325,7 +569,14 @@ mod tests {
                 passphrase: None,
                 private_key: None,
                 public_key: None,
-                disabled: false
+                disabled: false,
+                connect_timeout: None,
+                command_timeout: None,
+                host_key_check: HostKeyCheck::AcceptNew,
+                auth_methods: None,
+                proxy_jump: None,
+                tags: vec![],
+                jump_hosts: vec![],
             },
             SshConfiguration::new_empty()
         );
@@ -356,4 +607,101 @@ mod tests {
             c.public_key().unwrap().to_string_lossy()
         );
     }
+
+    #[test]
+    fn host_key_check_test() {
+        let c: SshConfiguration = serde_json::from_str(
+            r#"{"host": "h", "command": "c", "username": "u"}"#,
+        )
+        .unwrap();
+        assert_eq!(HostKeyCheck::AcceptNew, c.host_key_check());
+
+        let c: SshConfiguration = serde_json::from_str(
+            r#"{"host": "h", "command": "c", "username": "u", "host_key_check": "strict"}"#,
+        )
+        .unwrap();
+        assert_eq!(HostKeyCheck::Strict, c.host_key_check());
+
+        let c: SshConfiguration = serde_json::from_str(
+            r#"{"host": "h", "command": "c", "username": "u", "host_key_check": "off"}"#,
+        )
+        .unwrap();
+        assert_eq!(HostKeyCheck::Off, c.host_key_check());
+    }
+
+    #[test]
+    fn with_ssh_options_test() {
+        let options = SshHostOptions {
+            host_name: Some("shop.example.com".to_owned()),
+            user: Some("deploy".to_owned()),
+            port: Some(2222),
+            identity_file: Some(PathBuf::from("/keys/shop")),
+            proxy_jump: Some("bastion".to_owned()),
+        };
+
+        let c: SshConfiguration =
+            serde_json::from_str(r#"{"host": "shop", "command": "c"}"#)
+                .unwrap();
+        let c = c.with_ssh_options(options.clone());
+        assert_eq!("shop.example.com", c.host());
+        assert_eq!("deploy", c.username());
+        assert_eq!(2222, c.port());
+        assert_eq!(Some(PathBuf::from("/keys/shop")), c.private_key());
+        assert_eq!(Some("bastion"), c.proxy_jump());
+
+        let c = SshConfiguration::new_with_public_key(
+            "shop",
+            22,
+            "c",
+            "admin",
+            "/keys/admin",
+            None,
+            None,
+        )
+        .with_ssh_options(options);
+        assert_eq!("shop.example.com", c.host());
+        assert_eq!("admin", c.username());
+        assert_eq!(22, c.port());
+        assert_eq!(Some(PathBuf::from("/keys/admin")), c.private_key());
+    }
+
+    #[test]
+    fn with_empty_ssh_options_test() {
+        let c: SshConfiguration = serde_json::from_str(
+            r#"{"host": "shop", "command": "c", "username": "u"}"#,
+        )
+        .unwrap();
+        let c = c.with_ssh_options(SshHostOptions::default());
+        assert_eq!("shop", c.host());
+        assert_eq!("u", c.username());
+        assert_eq!(22, c.port());
+        assert_eq!(None, c.private_key());
+    }
+
+    #[test]
+    fn auth_methods_test() {
+        // Without a password only the key based methods are tried by default
+        assert_eq!(
+            vec![AuthMethod::Publickey, AuthMethod::Agent],
+            SshConfiguration::new_empty().auth_methods()
+        );
+        assert_eq!(
+            DEFAULT_AUTH_METHODS.to_vec(),
+            SshConfiguration::new_with_password(
+                "localhost",
+                22,
+                "cmd",
+                "daniel",
+                "password"
+            )
+            .auth_methods()
+        );
+
+        // Configured methods are used as is (the password is asked for)
+        let configuration: SshConfiguration = serde_json::from_str(
+            r#"{"host": "a", "command": "b", "auth_methods": ["password"]}"#,
+        )
+        .unwrap();
+        assert_eq!(vec![AuthMethod::Password], configuration.auth_methods());
+    }
 }
@@ -1,3 +1,8 @@
 pub const PROTOCOL: &str = "0.2.0";
+/// Protocols of providers that can be decoded (older ones are migrated)
+pub const SUPPORTED_PROTOCOLS: &[&str] = &["0.1.0", PROTOCOL];
 pub const PROVIDER_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const PROVIDER_NAME: &str = "fleet-rust";
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+pub const DEFAULT_COMMAND_TIMEOUT: u64 = 300;
@@ -0,0 +1,242 @@
+use serde::Serialize;
+use ssh2::Error as Ssh2Error;
+use ssh2::ErrorCode;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::ErrorKind as IoErrorKind;
+
+/// Libssh2's error code for timeouts (`LIBSSH2_ERROR_TIMEOUT`)
+const SSH2_ERROR_TIMEOUT: i32 = -9;
+
+/// Kind of an error
+///
+/// The kind is serialized as the `kind` field, followed by the variant's data
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The connection to the host could not be established
+    Connect,
+    /// The authentication on the host failed
+    Auth,
+    /// The host key could not be verified
+    HostKey,
+    /// The command on the host failed
+    Exec {
+        exit_code: i32,
+        stderr: String,
+    },
+    /// The output of the provider could not be decoded
+    Protocol {
+        raw_output: String,
+    },
+    /// The configuration is invalid or could not be loaded
+    Config,
+    /// The operation did not finish in time
+    Timeout,
+    Other,
+}
+
+impl ErrorKind {
+    /// Return the machine-readable name of the kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Connect => "connect",
+            ErrorKind::Auth => "auth",
+            ErrorKind::HostKey => "host_key",
+            ErrorKind::Exec { .. } => "exec",
+            ErrorKind::Protocol { .. } => "protocol",
+            ErrorKind::Config => "config",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Other => "other",
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Error {
+    #[serde(flatten)]
+    kind: ErrorKind,
+    message: String,
+}
+
+impl Error {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::Other, message)
+    }
+
+    pub fn with_kind<S: Into<String>>(kind: ErrorKind, message: S) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn connect<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::Connect, message)
+    }
+
+    pub fn auth<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::Auth, message)
+    }
+
+    pub fn host_key<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::HostKey, message)
+    }
+
+    pub fn exec<S: Into<String>>(
+        exit_code: i32,
+        stderr: S,
+        message: S,
+    ) -> Self {
+        Self::with_kind(
+            ErrorKind::Exec {
+                exit_code,
+                stderr: stderr.into(),
+            },
+            message,
+        )
+    }
+
+    pub fn protocol<S: Into<String>>(message: S, raw_output: S) -> Self {
+        Self::with_kind(
+            ErrorKind::Protocol {
+                raw_output: raw_output.into(),
+            },
+            message,
+        )
+    }
+
+    pub fn config<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::Config, message)
+    }
+
+    pub fn timeout<S: Into<String>>(message: S) -> Self {
+        Self::with_kind(ErrorKind::Timeout, message)
+    }
+
+    pub fn from_error(error: &dyn StdError) -> Self {
+        Self::new(error.to_string())
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Return a copy of the error with the given kind, unless it is a timeout
+    ///
+    /// Timeouts are kept, so they can be distinguished from other failures
+    /// of the same operation
+    pub fn or_kind(self, kind: ErrorKind) -> Self {
+        match self.kind {
+            ErrorKind::Timeout => self,
+            _ => Self { kind, ..self },
+        }
+    }
+}
+
+impl StdError for Error {
+    fn description(&self) -> &str {
+        self.message()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl From<&dyn StdError> for Error {
+    fn from(error: &dyn StdError) -> Self {
+        Error::from_error(error)
+    }
+}
+
+impl From<Ssh2Error> for Error {
+    fn from(error: Ssh2Error) -> Self {
+        Error::from(&error)
+    }
+}
+
+impl From<&Ssh2Error> for Error {
+    fn from(error: &Ssh2Error) -> Self {
+        if error.code() == ErrorCode::Session(SSH2_ERROR_TIMEOUT) {
+            Error::timeout(error.to_string())
+        } else {
+            Error::from_error(error)
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            IoErrorKind::TimedOut | IoErrorKind::WouldBlock => {
+                Error::timeout(error.to_string())
+            }
+            _ => Error::from_error(&error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_io_error_test() {
+        let error = Error::from(std::io::Error::new(
+            IoErrorKind::TimedOut,
+            "connection timed out",
+        ));
+        assert_eq!(&ErrorKind::Timeout, error.kind());
+
+        let error = Error::from(std::io::Error::new(
+            IoErrorKind::ConnectionRefused,
+            "connection refused",
+        ));
+        assert_eq!(&ErrorKind::Other, error.kind());
+    }
+
+    #[test]
+    fn from_ssh2_error_test() {
+        let error = Error::from(Ssh2Error::new(
+            ErrorCode::Session(SSH2_ERROR_TIMEOUT),
+            "timed out",
+        ));
+        assert_eq!(&ErrorKind::Timeout, error.kind());
+
+        let error =
+            Error::from(Ssh2Error::new(ErrorCode::Session(-18), "auth"));
+        assert_eq!(&ErrorKind::Other, error.kind());
+    }
+
+    #[test]
+    fn or_kind_test() {
+        let error = Error::new("refused").or_kind(ErrorKind::Connect);
+        assert_eq!(&ErrorKind::Connect, error.kind());
+        assert_eq!("refused", error.message());
+
+        let error = Error::timeout("timed out").or_kind(ErrorKind::Connect);
+        assert_eq!(&ErrorKind::Timeout, error.kind());
+    }
+
+    #[test]
+    fn serialize_test() {
+        let error = Error::exec(2, "not found", "failed");
+        assert_eq!(
+            r#"{"kind":"exec","exit_code":2,"stderr":"not found","message":"failed"}"#,
+            serde_json::to_string(&error).unwrap()
+        );
+
+        let error = Error::auth("denied");
+        assert_eq!(
+            r#"{"kind":"auth","message":"denied"}"#,
+            serde_json::to_string(&error).unwrap()
+        );
+    }
+}
@@ -1,8 +1,8 @@
 #[allow(clippy::module_inception)]
-mod flat_error;
+mod error;
 
 use std::collections::HashMap;
 
-pub use self::flat_error::FlatError as Error;
+pub use self::error::{Error, ErrorKind};
 
 pub type ErrorCollection = HashMap<String, Error>;
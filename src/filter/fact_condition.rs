@@ -0,0 +1,88 @@
+use super::wildcard_match;
+use crate::error::Error;
+use crate::information::Information;
+
+/// Condition on a fact of the form `path=value`
+///
+/// The value may contain the wildcards `*` and `?`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactCondition {
+    path: String,
+    pattern: String,
+}
+
+impl FactCondition {
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        match source.split_once('=') {
+            Some((path, pattern)) if !path.trim().is_empty() => {
+                Ok(FactCondition {
+                    path: path.trim().to_owned(),
+                    pattern: pattern.trim().to_owned(),
+                })
+            }
+            _ => Err(Error::new(format!(
+                "Invalid fact condition '{}' (expected `path=value`)",
+                source
+            ))),
+        }
+    }
+
+    /// Check if the host's fact at the path matches the value
+    pub fn matches(&self, information: &Information) -> bool {
+        information
+            .facts
+            .get_text(&self.path)
+            .is_some_and(|text| wildcard_match(&self.pattern, &text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_information() -> Information {
+        let mut information = Information::new_for_current_env();
+        information.facts = serde_json::from_value(serde_json::json!({
+            "php": {"version": "8.1.2", "extensions": ["intl", "gd"]},
+            "cron": {"running": true}
+        }))
+        .unwrap();
+
+        information
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            FactCondition {
+                path: "php.version".to_owned(),
+                pattern: "8.1".to_owned()
+            },
+            FactCondition::parse(" php.version = 8.1").unwrap()
+        );
+        assert_eq!(
+            "a=b",
+            FactCondition::parse("path=a=b").unwrap().pattern.as_str()
+        );
+        assert!(FactCondition::parse("php.version").is_err());
+        assert!(FactCondition::parse("=8.1").is_err());
+    }
+
+    #[test]
+    fn matches_test() {
+        let information = build_information();
+        let matches = |condition: &str| {
+            FactCondition::parse(condition)
+                .unwrap()
+                .matches(&information)
+        };
+
+        assert!(matches("php.version=8.1.2"));
+        assert!(matches("php.version=8.1.*"));
+        assert!(!matches("php.version=8.1"));
+        assert!(matches("cron.running=true"));
+        assert!(matches("php.extensions=*gd*"));
+        assert!(matches("php.extensions.0=intl"));
+        assert!(!matches("php.unknown=*"));
+    }
+}
@@ -0,0 +1,85 @@
+use super::wildcard_match;
+use crate::error::Error;
+use regex::Regex;
+use std::fmt;
+
+/// Pattern to match configuration keys of hosts
+///
+/// Patterns enclosed in slashes (`/^shop-\d+$/`) are regular expressions, all
+/// other patterns are shell-style globs with the wildcards `*` and `?`
+#[derive(Debug, Clone)]
+pub enum HostPattern {
+    Glob(String),
+    Regex(Regex),
+}
+
+impl HostPattern {
+    /// Parse the pattern
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        match source.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+            Some(expression) => match Regex::new(expression) {
+                Ok(regex) => Ok(HostPattern::Regex(regex)),
+                Err(e) => Err(Error::new(format!(
+                    "Invalid regular expression {}: {}",
+                    source, e
+                ))),
+            },
+            None => Ok(HostPattern::Glob(source.to_owned())),
+        }
+    }
+
+    /// Check if the configuration key matches the pattern
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            HostPattern::Glob(pattern) => wildcard_match(pattern, name),
+            HostPattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl fmt::Display for HostPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HostPattern::Glob(pattern) => write!(f, "{}", pattern),
+            HostPattern::Regex(regex) => write!(f, "/{}/", regex.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_test() {
+        let pattern = HostPattern::parse("shop-*").unwrap();
+        assert!(pattern.matches("shop-1"));
+        assert!(pattern.matches("shop-staging"));
+        assert!(!pattern.matches("web-1"));
+
+        let pattern = HostPattern::parse("shop").unwrap();
+        assert!(pattern.matches("shop"));
+        assert!(!pattern.matches("shop-1"));
+    }
+
+    #[test]
+    fn regex_test() {
+        let pattern = HostPattern::parse(r"/^shop-\d+$/").unwrap();
+        assert!(pattern.matches("shop-1"));
+        assert!(pattern.matches("shop-12"));
+        assert!(!pattern.matches("shop-staging"));
+        assert_eq!(r"/^shop-\d+$/", pattern.to_string());
+
+        let pattern = HostPattern::parse("/staging/").unwrap();
+        assert!(pattern.matches("shop-staging-2"));
+    }
+
+    #[test]
+    fn invalid_regex_test() {
+        assert!(HostPattern::parse("/shop-(/").is_err());
+        assert!(matches!(
+            HostPattern::parse("/").unwrap(),
+            HostPattern::Glob(_)
+        ));
+    }
+}
@@ -0,0 +1,516 @@
+use super::{wildcard_match, HostPattern};
+use crate::configuration::{Configuration, ConfigurationCollection, Groups};
+use crate::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expression {
+    /// The host has a matching tag
+    Tag(String),
+    /// The host's configuration key matches
+    Name(String),
+    /// The host is a member of the group
+    Group(String),
+    /// The host's address matches
+    Host(String),
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    fn matches(
+        &self,
+        name: &str,
+        configuration: &Configuration,
+        groups: &Groups,
+    ) -> bool {
+        match self {
+            Expression::Tag(pattern) => configuration
+                .tags()
+                .iter()
+                .any(|tag| wildcard_match(pattern, tag)),
+            Expression::Name(pattern) => wildcard_match(pattern, name),
+            Expression::Group(group) => match groups.get(group) {
+                Some(members) => {
+                    members.iter().any(|member| wildcard_match(member, name))
+                }
+                None => false,
+            },
+            Expression::Host(pattern) => {
+                wildcard_match(pattern, configuration.host())
+            }
+            Expression::Not(inner) => {
+                !inner.matches(name, configuration, groups)
+            }
+            Expression::And(left, right) => {
+                left.matches(name, configuration, groups)
+                    && right.matches(name, configuration, groups)
+            }
+            Expression::Or(left, right) => {
+                left.matches(name, configuration, groups)
+                    || right.matches(name, configuration, groups)
+            }
+        }
+    }
+
+    fn collect_groups<'a>(&'a self, result: &mut Vec<&'a str>) {
+        match self {
+            Expression::Group(group) => result.push(group),
+            Expression::Not(inner) => inner.collect_groups(result),
+            Expression::And(left, right) | Expression::Or(left, right) => {
+                left.collect_groups(result);
+                right.collect_groups(result);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Expression to select hosts, e.g. `tag:prod and not tag:legacy`
+///
+/// Terms are `tag:<pattern>`, `name:<pattern>`, `group:<name>` and
+/// `host:<pattern>` (a bare pattern is treated as `name:<pattern>`). They can
+/// be combined with `and`, `or`, `not` and parentheses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostSelector {
+    source: String,
+    expression: Expression,
+}
+
+impl HostSelector {
+    /// Parse the selector expression
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let tokens = tokenize(source);
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let expression = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(Error::new(format!(
+                "Unexpected '{}' in selector '{}'",
+                token, source
+            )));
+        }
+
+        Ok(HostSelector {
+            source: source.to_owned(),
+            expression,
+        })
+    }
+
+    /// Check if the host with the configuration key `name` is selected
+    pub fn matches(
+        &self,
+        name: &str,
+        configuration: &Configuration,
+        groups: &Groups,
+    ) -> bool {
+        self.expression.matches(name, configuration, groups)
+    }
+
+    /// Make sure all groups used in the expression are defined
+    fn validate_groups(&self, groups: &Groups) -> Result<(), Error> {
+        let mut used = vec![];
+        self.expression.collect_groups(&mut used);
+        match used.into_iter().find(|group| !groups.contains_key(*group)) {
+            Some(group) => Err(Error::config(format!(
+                "Group \"{}\" is not defined",
+                group
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for HostSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// Hosts to process, given through host patterns and/or a selector
+#[derive(Debug, Clone, Default)]
+pub struct HostSelection {
+    hosts: Vec<HostPattern>,
+    selector: Option<HostSelector>,
+}
+
+impl HostSelection {
+    pub fn new(
+        hosts: Vec<HostPattern>,
+        selector: Option<HostSelector>,
+    ) -> Self {
+        HostSelection { hosts, selector }
+    }
+
+    /// Return if neither host names nor a selector are given
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty() && self.selector.is_none()
+    }
+
+    /// Return the host patterns that do not match any host in the collection
+    pub fn unmatched_patterns(
+        &self,
+        collection: &ConfigurationCollection,
+    ) -> Vec<&HostPattern> {
+        self.hosts
+            .iter()
+            .filter(|p| !collection.keys().any(|host| p.matches(host)))
+            .collect()
+    }
+
+    /// Return the configurations of the selected hosts
+    ///
+    /// If host patterns are given, only the matching hosts are considered. An
+    /// error is returned if no host is selected
+    pub fn select(
+        &self,
+        collection: ConfigurationCollection,
+        groups: &Groups,
+    ) -> Result<ConfigurationCollection, Error> {
+        let hosts = &self.hosts;
+        let collection: ConfigurationCollection = if hosts.is_empty() {
+            collection
+        } else {
+            let filtered: ConfigurationCollection = collection
+                .into_iter()
+                .filter(|(host, _)| {
+                    !host.is_empty() && hosts.iter().any(|p| p.matches(host))
+                })
+                .collect();
+            if filtered.is_empty() {
+                return Err(Error::config(format!(
+                    "{}: {}",
+                    if hosts.len() > 1 {
+                        "No configurations found for hosts"
+                    } else {
+                        "No configuration found for host"
+                    },
+                    hosts
+                        .iter()
+                        .map(HostPattern::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+            filtered
+        };
+
+        let selector = match self.selector {
+            Some(ref selector) => selector,
+            None => return Ok(collection),
+        };
+        selector.validate_groups(groups)?;
+
+        let selected: ConfigurationCollection = collection
+            .into_iter()
+            .filter(|(host, c)| selector.matches(host, c, groups))
+            .collect();
+        if selected.is_empty() {
+            Err(Error::config(format!(
+                "No hosts match the selector '{}'",
+                selector
+            )))
+        } else {
+            Ok(selected)
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    for c in source.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.position += 1;
+
+        token
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, Error> {
+        let mut expression = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.next();
+            expression = Expression::Or(
+                Box::new(expression),
+                Box::new(self.parse_and()?),
+            );
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, Error> {
+        let mut expression = self.parse_not()?;
+        while self.next_is_keyword("and") {
+            self.next();
+            expression = Expression::And(
+                Box::new(expression),
+                Box::new(self.parse_not()?),
+            );
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, Error> {
+        if self.next_is_keyword("not") {
+            self.next();
+            Ok(Expression::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_term()
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expression, Error> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => {
+                return Err(Error::new("Unexpected end of the selector"));
+            }
+        };
+
+        if token == "(" {
+            let expression = self.parse_or()?;
+            return match self.next() {
+                Some(")") => Ok(expression),
+                _ => Err(Error::new("Missing ')' in the selector")),
+            };
+        }
+        if token == ")"
+            || ["and", "or", "not"]
+                .iter()
+                .any(|k| token.eq_ignore_ascii_case(k))
+        {
+            return Err(Error::new(format!(
+                "Unexpected '{}' in the selector",
+                token
+            )));
+        }
+
+        let (kind, value) = match token.split_once(':') {
+            Some((kind, value)) => (kind, value),
+            None => ("name", token),
+        };
+        if value.is_empty() {
+            return Err(Error::new(format!(
+                "Missing value for '{}' in the selector",
+                kind
+            )));
+        }
+
+        match kind {
+            "tag" => Ok(Expression::Tag(value.to_owned())),
+            "name" => Ok(Expression::Name(value.to_owned())),
+            "group" => Ok(Expression::Group(value.to_owned())),
+            "host" => Ok(Expression::Host(value.to_owned())),
+            _ => Err(Error::new(format!(
+                "Unknown term '{}' in the selector (expected tag, name, group or host)",
+                kind
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn build_collection() -> ConfigurationCollection {
+        let mut collection = ConfigurationCollection::new();
+        for (name, host, tags) in [
+            ("web-1", "10.0.0.1", vec!["prod"]),
+            ("web-2", "10.0.0.2", vec!["prod", "legacy"]),
+            ("db-1", "10.0.1.1", vec!["prod", "database"]),
+            ("staging", "staging.example.com", vec!["staging"]),
+        ] {
+            let configuration = serde_json::from_value(serde_json::json!({
+                "host": host,
+                "command": "command",
+                "username": "user",
+                "tags": tags,
+            }))
+            .unwrap();
+            collection.insert(name.to_owned(), configuration);
+        }
+
+        collection
+    }
+
+    fn build_groups() -> Groups {
+        let mut groups = Groups::new();
+        groups.insert(
+            "frontend".to_owned(),
+            vec!["web-*".to_owned(), "staging".to_owned()],
+        );
+
+        groups
+    }
+
+    fn select(selector: &str) -> Vec<String> {
+        let selection = HostSelection::new(
+            vec![],
+            Some(HostSelector::parse(selector).unwrap()),
+        );
+        let mut hosts: Vec<String> = selection
+            .select(build_collection(), &build_groups())
+            .unwrap()
+            .into_keys()
+            .collect();
+        hosts.sort();
+
+        hosts
+    }
+
+    #[test]
+    fn select_test() {
+        assert_eq!(vec!["db-1", "web-1", "web-2"], select("tag:prod"));
+        assert_eq!(
+            vec!["db-1", "web-1"],
+            select("tag:prod and not tag:legacy")
+        );
+        assert_eq!(vec!["web-1", "web-2"], select("name:web-*"));
+        assert_eq!(vec!["web-1", "web-2"], select("web-?"));
+        assert_eq!(
+            vec!["staging", "web-1"],
+            select("group:frontend AND NOT tag:legacy")
+        );
+        assert_eq!(
+            vec!["db-1", "staging"],
+            select("tag:staging or tag:database")
+        );
+        assert_eq!(
+            vec!["db-1", "web-1"],
+            select("(name:web-* or host:10.0.1.*) and not tag:legacy")
+        );
+    }
+
+    #[test]
+    fn select_with_hosts_test() {
+        let selection = HostSelection::new(
+            vec![
+                HostPattern::parse("web-2").unwrap(),
+                HostPattern::parse("staging").unwrap(),
+            ],
+            Some(HostSelector::parse("tag:prod").unwrap()),
+        );
+        let selected = selection
+            .select(build_collection(), &build_groups())
+            .unwrap();
+        assert_eq!(1, selected.len());
+        assert!(selected.contains_key("web-2"));
+
+        let selection = HostSelection::new(
+            vec![HostPattern::parse("unknown").unwrap()],
+            None,
+        );
+        let error = selection
+            .select(build_collection(), &build_groups())
+            .unwrap_err();
+        assert_eq!("No configuration found for host: unknown", error.message());
+    }
+
+    #[test]
+    fn select_with_patterns_test() {
+        let selection = HostSelection::new(
+            vec![
+                HostPattern::parse("web-*").unwrap(),
+                HostPattern::parse("/^db-\\d$/").unwrap(),
+                HostPattern::parse("mail-*").unwrap(),
+            ],
+            None,
+        );
+        let collection = build_collection();
+
+        let unmatched: Vec<String> = selection
+            .unmatched_patterns(&collection)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(vec!["mail-*"], unmatched);
+
+        let mut hosts: Vec<String> = selection
+            .select(collection, &build_groups())
+            .unwrap()
+            .into_keys()
+            .collect();
+        hosts.sort();
+        assert_eq!(vec!["db-1", "web-1", "web-2"], hosts);
+    }
+
+    #[test]
+    fn select_errors_test() {
+        let selection = HostSelection::new(
+            vec![],
+            Some(HostSelector::parse("tag:unknown").unwrap()),
+        );
+        let error = selection
+            .select(build_collection(), &build_groups())
+            .unwrap_err();
+        assert_eq!(&ErrorKind::Config, error.kind());
+
+        let selection = HostSelection::new(
+            vec![],
+            Some(HostSelector::parse("group:unknown").unwrap()),
+        );
+        let error = selection
+            .select(build_collection(), &build_groups())
+            .unwrap_err();
+        assert_eq!("Group \"unknown\" is not defined", error.message());
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        for selector in [
+            "",
+            "tag:prod and",
+            "(tag:prod",
+            "tag:prod)",
+            "tag:",
+            "role:web",
+            "and tag:prod",
+        ] {
+            assert!(
+                HostSelector::parse(selector).is_err(),
+                "'{}' should be invalid",
+                selector
+            );
+        }
+    }
+}
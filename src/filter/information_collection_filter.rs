@@ -1,23 +1,20 @@
 use crate::filter::package_filter::PackageFilter;
+use crate::filter::{FactCondition, PackageQuery};
 use crate::information::InformationCollection;
 
 pub struct InformationCollectionFilter {}
 
 impl InformationCollectionFilter {
-    /// Search the collection of [`Information`] instances for [`Packages`] with given search string
-    ///
-    /// If `exact` is `TRUE` only the package's key is tested and has to be the same as the search
-    /// If `exact` is `FALSE` packages are returned that contain the search string in either the key or description
+    /// Search the collection of [`Information`] instances for [`Packages`] matching the query
     pub fn filter_by_package(
         collection: InformationCollection,
-        search: &str,
-        exact: bool,
+        query: &PackageQuery,
     ) -> InformationCollection {
         collection
             .into_iter()
             .filter_map(|(host, information)| {
                 let packages = information.packages.clone();
-                if !PackageFilter::filter(packages, search, exact).is_empty() {
+                if !PackageFilter::filter(packages, query).is_empty() {
                     Some((host, information))
                 } else {
                     None
@@ -25,10 +22,42 @@ impl InformationCollectionFilter {
             })
             .collect()
     }
+
+    /// Keep only the hosts matching all fact conditions
+    pub fn filter_by_facts(
+        collection: InformationCollection,
+        conditions: &[FactCondition],
+    ) -> InformationCollection {
+        collection
+            .into_iter()
+            .filter(|(_, information)| {
+                conditions.iter().all(|c| c.matches(information))
+            })
+            .collect()
+    }
+
+    /// Keep only the [`Packages`] matching the query for each host
+    ///
+    /// Unlike [`InformationCollectionFilter::filter_by_package`] hosts without
+    /// matching packages are kept
+    pub fn filter_packages(
+        collection: InformationCollection,
+        query: &PackageQuery,
+    ) -> InformationCollection {
+        collection
+            .into_iter()
+            .map(|(host, mut information)| {
+                information.packages =
+                    PackageFilter::filter(information.packages, query);
+                (host, information)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::filter::PackageState;
     use crate::information::Information;
 
     use super::*;
@@ -48,10 +77,59 @@ mod tests {
         let collection = build_test_collection();
         let result = InformationCollectionFilter::filter_by_package(
             collection,
-            "not-a-package",
-            false,
+            &PackageQuery::new("not-a-package", false),
         );
 
         assert_eq!(0, result.len());
     }
+
+    #[test]
+    fn filter_packages_test() {
+        let mut information = Information::new_for_current_env();
+        information.packages = crate::test_helpers::get_test_packages();
+        let mut collection = InformationCollection::new();
+        collection.insert("localhost".to_owned(), information);
+        collection
+            .insert("127.0.0.1".to_owned(), Information::new_for_current_env());
+
+        let result = InformationCollectionFilter::filter_packages(
+            collection,
+            &PackageQuery::all().with_state(PackageState::Inactive),
+        );
+
+        assert_eq!(2, result.len());
+        let packages = &result["localhost"].packages;
+        assert!(!packages.is_empty());
+        assert!(packages.iter().all(|(_, package)| !package.is_active()));
+        assert!(packages.all.contains_key("workspaces"));
+        assert!(result["127.0.0.1"].packages.is_empty());
+    }
+
+    #[test]
+    fn filter_by_facts_test() {
+        let mut information = Information::new_for_current_env();
+        information.facts = serde_json::from_value(
+            serde_json::json!({"php": {"version": "8.1"}}),
+        )
+        .unwrap();
+        let mut collection = build_test_collection();
+        collection.insert("web".to_owned(), information);
+
+        let condition = |source| FactCondition::parse(source).unwrap();
+        let result = InformationCollectionFilter::filter_by_facts(
+            collection.clone(),
+            &[condition("php.version=8.*")],
+        );
+        assert_eq!(vec!["web"], result.keys().collect::<Vec<_>>());
+
+        let result = InformationCollectionFilter::filter_by_facts(
+            collection.clone(),
+            &[condition("php.version=8.*"), condition("php.sapi=cli")],
+        );
+        assert!(result.is_empty());
+
+        let result =
+            InformationCollectionFilter::filter_by_facts(collection, &[]);
+        assert_eq!(3, result.len());
+    }
 }
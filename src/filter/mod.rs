@@ -1,5 +1,17 @@
+mod fact_condition;
+mod host_pattern;
+mod host_selector;
 mod information_collection_filter;
 mod package_filter;
+mod package_query;
+mod version_constraint;
+mod wildcard;
 
+pub use self::fact_condition::FactCondition;
+pub use self::host_pattern::HostPattern;
+pub use self::host_selector::{HostSelection, HostSelector};
 pub use self::information_collection_filter::InformationCollectionFilter;
 pub use self::package_filter::PackageFilter;
+pub use self::package_query::{PackageQuery, PackageState};
+pub use self::version_constraint::{Version, VersionConstraint};
+pub use self::wildcard::wildcard_match;
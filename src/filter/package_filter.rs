@@ -1,3 +1,4 @@
+use super::PackageQuery;
 use crate::information::Package;
 use crate::information::Packages;
 use std::collections::HashMap;
@@ -5,21 +6,11 @@ use std::collections::HashMap;
 pub struct PackageFilter {}
 
 impl PackageFilter {
-    /// Search for [`Package`s] matching the given search string
-    ///
-    /// If `exact` is `TRUE` only the package's key is tested and has to be the same as the search
-    /// If `exact` is `FALSE` packages are returned that contain the search string in either the key or description
-    pub fn filter(packages: Packages, search: &str, exact: bool) -> Packages {
+    /// Search for [`Package`s] matching the given query
+    pub fn filter(packages: Packages, query: &PackageQuery) -> Packages {
         let filtered: HashMap<String, Package> = packages
             .into_iter()
-            .filter(|(_, package)| {
-                if exact {
-                    package.key == search
-                } else {
-                    package.description.contains(search)
-                        || package.key.contains(search)
-                }
-            })
+            .filter(|(_, package)| query.matches(package))
             .collect();
 
         Packages::new_with_packages(filtered)
@@ -28,6 +19,7 @@ impl PackageFilter {
 
 #[cfg(test)]
 mod tests {
+    use crate::filter::VersionConstraint;
     use crate::test_helpers::*;
 
     use super::*;
@@ -36,35 +28,44 @@ mod tests {
     fn filter_by_package_empty_test() {
         assert_eq!(
             0,
-            PackageFilter::filter(get_test_packages(), "not-a-package", false)
-                .len()
+            PackageFilter::filter(
+                get_test_packages(),
+                &PackageQuery::new("not-a-package", false)
+            )
+            .len()
         );
         assert!(PackageFilter::filter(
             get_test_packages(),
-            "not-a-package",
-            false
+            &PackageQuery::new("not-a-package", false)
         )
         .is_empty());
         assert_eq!(
             0,
-            PackageFilter::filter(get_test_packages(), "not-a-package", true)
-                .len()
+            PackageFilter::filter(
+                get_test_packages(),
+                &PackageQuery::new("not-a-package", true)
+            )
+            .len()
         );
         assert!(PackageFilter::filter(
             get_test_packages(),
-            "not-a-package",
-            true
+            &PackageQuery::new("not-a-package", true)
         )
         .is_empty());
     }
 
     #[test]
     fn filter_by_package_test() {
-        let result = PackageFilter::filter(get_test_packages(), "news", false);
+        let result = PackageFilter::filter(
+            get_test_packages(),
+            &PackageQuery::new("news", false),
+        );
         assert_eq!(3, result.len());
 
-        let result =
-            PackageFilter::filter(get_test_packages(), "newsletter", false);
+        let result = PackageFilter::filter(
+            get_test_packages(),
+            &PackageQuery::new("newsletter", false),
+        );
         assert_eq!(1, result.len());
         assert!(!result.all.contains_key("news"));
         assert!(result.all.contains_key("newsletter"));
@@ -73,8 +74,23 @@ mod tests {
 
     #[test]
     fn filter_by_package_exact_test() {
-        let result = PackageFilter::filter(get_test_packages(), "news", true);
+        let result = PackageFilter::filter(
+            get_test_packages(),
+            &PackageQuery::new("news", true),
+        );
 
         assert_eq!(1, result.len());
     }
+
+    #[test]
+    fn filter_by_version_test() {
+        let query = PackageQuery::new("", false)
+            .with_version(Some(VersionConstraint::parse(">=2,<8").unwrap()));
+        let result = PackageFilter::filter(get_test_packages(), &query);
+
+        assert!(!result.is_empty());
+        assert!(result.all.contains_key("tika"));
+        assert!(!result.all.contains_key("backend"));
+        assert!(!result.all.contains_key("pagebrowse"));
+    }
 }
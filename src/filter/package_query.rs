@@ -0,0 +1,147 @@
+use super::VersionConstraint;
+use crate::information::Package;
+
+/// State a package must be in to match a query
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageState {
+    Active,
+    /// Any state other than `active`
+    Inactive,
+    #[default]
+    Any,
+}
+
+impl PackageState {
+    pub fn matches(&self, package: &Package) -> bool {
+        match self {
+            PackageState::Active => package.is_active(),
+            PackageState::Inactive => !package.is_active(),
+            PackageState::Any => true,
+        }
+    }
+}
+
+/// Criteria for searching [`Package`s]
+#[derive(Debug, Clone)]
+pub struct PackageQuery {
+    search: String,
+    exact: bool,
+    version: Option<VersionConstraint>,
+    state: PackageState,
+}
+
+impl PackageQuery {
+    /// Create a query for the search string
+    ///
+    /// If `exact` is `TRUE` only the package's key is tested and has to be the same as the search
+    /// If `exact` is `FALSE` packages match that contain the search string in either the key or description
+    pub fn new(search: &str, exact: bool) -> Self {
+        PackageQuery {
+            search: search.to_owned(),
+            exact,
+            version: None,
+            state: PackageState::Any,
+        }
+    }
+
+    /// Create a query that matches all packages
+    pub fn all() -> Self {
+        PackageQuery::new("", false)
+    }
+
+    /// Additionally require the package's version to match the constraint
+    pub fn with_version(self, version: Option<VersionConstraint>) -> Self {
+        PackageQuery { version, ..self }
+    }
+
+    /// Additionally require the package to be in the state
+    pub fn with_state(self, state: PackageState) -> Self {
+        PackageQuery { state, ..self }
+    }
+
+    /// Check if the package matches all criteria of the query
+    pub fn matches(&self, package: &Package) -> bool {
+        let search_matches = if self.exact {
+            package.key == self.search
+        } else {
+            package.description.contains(&self.search)
+                || package.key.contains(&self.search)
+        };
+
+        search_matches
+            && self.state.matches(package)
+            && self
+                .version
+                .as_ref()
+                .is_none_or(|version| version.matches(&package.version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_package(key: &str, version: &str) -> Package {
+        build_package_with_state(key, version, "active")
+    }
+
+    fn build_package_with_state(
+        key: &str,
+        version: &str,
+        state: &str,
+    ) -> Package {
+        Package {
+            key: key.to_owned(),
+            version: version.to_owned(),
+            description: "TYPO3 core".to_owned(),
+            state: state.to_owned(),
+        }
+    }
+
+    #[test]
+    fn matches_test() {
+        let package = build_package("typo3/cms-core", "10.4.3");
+        assert!(PackageQuery::new("cms-core", false).matches(&package));
+        assert!(PackageQuery::new("core", false).matches(&package));
+        assert!(!PackageQuery::new("cms-core", true).matches(&package));
+        assert!(PackageQuery::new("typo3/cms-core", true).matches(&package));
+    }
+
+    #[test]
+    fn matches_version_test() {
+        let query = PackageQuery::new("typo3/cms-core", true)
+            .with_version(Some(VersionConstraint::parse("<10.4.20").unwrap()));
+
+        assert!(query.matches(&build_package("typo3/cms-core", "10.4.3")));
+        assert!(!query.matches(&build_package("typo3/cms-core", "10.4.20")));
+        assert!(!query.matches(&build_package("typo3/cms-backend", "10.4.3")));
+    }
+
+    #[test]
+    fn matches_state_test() {
+        let active = build_package_with_state("news", "8.0.0", "active");
+        let inactive = build_package_with_state("news", "8.0.0", "inactive");
+
+        let query = PackageQuery::all().with_state(PackageState::Active);
+        assert!(query.matches(&active));
+        assert!(!query.matches(&inactive));
+
+        let query = PackageQuery::all().with_state(PackageState::Inactive);
+        assert!(!query.matches(&active));
+        assert!(query.matches(&inactive));
+
+        let query = PackageQuery::all().with_state(PackageState::Any);
+        assert!(query.matches(&active));
+        assert!(query.matches(&inactive));
+    }
+}
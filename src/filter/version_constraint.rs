@@ -0,0 +1,446 @@
+use crate::error::Error;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Package version parsed leniently
+///
+/// Besides semantic versions, composer and TYPO3 style versions are accepted:
+/// a leading `v`, any number of numeric components (`9.5.31.1`), missing
+/// components (`10.4` is `10.4.0`) and stability suffixes (`1.0.0-beta2`,
+/// `2.0.0RC1`). Build metadata (`+...`) is ignored
+#[derive(Debug, Clone)]
+pub struct Version {
+    numbers: Vec<u64>,
+    pre_release: Option<String>,
+}
+
+impl Version {
+    /// Parse the version, returning `None` for branch names like `dev-main`
+    pub fn parse(source: &str) -> Option<Self> {
+        let source = source.trim();
+        let source = source
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(source)
+            .split('+')
+            .next()
+            .unwrap_or_default();
+
+        let numeric_end = source
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(source.len());
+        let (numeric, rest) = source.split_at(numeric_end);
+        if numeric.is_empty() || (numeric.ends_with('.') && !rest.is_empty()) {
+            return None;
+        }
+        let numeric = numeric.trim_end_matches('.');
+
+        let mut numbers = vec![];
+        for part in numeric.split('.') {
+            numbers.push(part.parse().ok()?);
+        }
+
+        let pre_release = rest.trim_start_matches(['-', '.', '_']);
+        if pre_release.is_empty() && !rest.is_empty() {
+            return None;
+        }
+
+        Some(Version {
+            numbers,
+            pre_release: if pre_release.is_empty() {
+                None
+            } else {
+                Some(pre_release.to_lowercase())
+            },
+        })
+    }
+
+    fn new(numbers: Vec<u64>) -> Self {
+        Version {
+            numbers,
+            pre_release: None,
+        }
+    }
+
+    fn number(&self, index: usize) -> u64 {
+        self.numbers.get(index).copied().unwrap_or(0)
+    }
+
+    /// Return the version with the component at `index` incremented and all
+    /// following components removed (e.g. `1.2.3` with index 1 is `1.3`)
+    fn bump(&self, index: usize) -> Self {
+        let mut numbers: Vec<u64> =
+            (0..=index).map(|i| self.number(i)).collect();
+        numbers[index] += 1;
+
+        Version::new(numbers)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let length = self.numbers.len().max(other.numbers.len());
+        for index in 0..length {
+            match self.number(index).cmp(&other.number(index)) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+
+        // A pre-release is lower than the release
+        match (&self.pre_release, &other.pre_release) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_pre_release(a, b),
+        }
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare pre-release labels by stability first (`dev` < `alpha` < `beta`
+/// < `rc`), then by the number following the label
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    fn split(label: &str) -> (u8, u64, &str) {
+        let digits = label
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(label.len());
+        let (name, number) = label.split_at(digits);
+        let name = name.trim_end_matches(['.', '-', '_']);
+        let stability = match name {
+            "dev" => 0,
+            "alpha" | "a" => 1,
+            "beta" | "b" => 2,
+            "rc" => 3,
+            _ => 4,
+        };
+        let number = number
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0);
+
+        (stability, number, label)
+    }
+
+    split(a).cmp(&split(b))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparison {
+    operator: Operator,
+    version: Version,
+}
+
+impl Comparison {
+    fn matches(&self, version: &Version) -> bool {
+        let ordering = version.cmp(&self.version);
+        match self.operator {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::NotEqual => ordering != Ordering::Equal,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessOrEqual => ordering != Ordering::Greater,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterOrEqual => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Constraint on package versions, e.g. `<10.4.20` or `>=8.7,<9`
+///
+/// Comparisons separated by `,` or whitespace must all match, alternatives
+/// are separated by `||`. Composer's `^1.2`, `~1.2` and `1.2.*` ranges are
+/// supported as well
+#[derive(Debug, Clone)]
+pub struct VersionConstraint {
+    source: String,
+    alternatives: Vec<Vec<Comparison>>,
+}
+
+impl VersionConstraint {
+    /// Parse the constraint
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let mut alternatives = vec![];
+        for alternative in source.split("||") {
+            let mut comparisons = vec![];
+            let mut pending_operator: Option<String> = None;
+            for token in alternative
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|t| !t.is_empty())
+            {
+                // Join operators separated from their version (`>= 8.7`)
+                let token = match pending_operator.take() {
+                    Some(operator) => operator + token,
+                    None => token.to_owned(),
+                };
+                if token.chars().all(|c| "<>=!^~".contains(c)) {
+                    pending_operator = Some(token);
+                    continue;
+                }
+
+                comparisons.extend(parse_comparison(&token).ok_or_else(
+                    || {
+                        build_error(
+                            source,
+                            &format!("invalid part '{}'", token),
+                        )
+                    },
+                )?);
+            }
+
+            if let Some(operator) = pending_operator {
+                return Err(build_error(
+                    source,
+                    &format!("missing version after '{}'", operator),
+                ));
+            }
+            if comparisons.is_empty() && alternative.trim() != "*" {
+                return Err(build_error(source, "empty constraint"));
+            }
+
+            alternatives.push(comparisons);
+        }
+
+        Ok(VersionConstraint {
+            source: source.trim().to_owned(),
+            alternatives,
+        })
+    }
+
+    /// Check if the version matches the constraint
+    ///
+    /// Versions that can not be parsed (e.g. `dev-main`) never match
+    pub fn matches(&self, version: &str) -> bool {
+        match Version::parse(version) {
+            Some(version) => self.alternatives.iter().any(|comparisons| {
+                comparisons.iter().all(|c| c.matches(&version))
+            }),
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn build_error(source: &str, reason: &str) -> Error {
+    Error::new(format!(
+        "Invalid version constraint '{}': {}",
+        source.trim(),
+        reason
+    ))
+}
+
+/// Parse a single comparison, which may expand to a range of two
+fn parse_comparison(token: &str) -> Option<Vec<Comparison>> {
+    let comparison = |operator, version| Comparison { operator, version };
+
+    if token == "*" {
+        return Some(vec![]);
+    }
+    if let Some(rest) = token.strip_prefix('^') {
+        let version = Version::parse(rest)?;
+        // The first non-zero component must not change
+        let significant = version
+            .numbers
+            .iter()
+            .position(|n| *n != 0)
+            .unwrap_or(version.numbers.len() - 1);
+        let upper = version.bump(significant);
+
+        return Some(vec![
+            comparison(Operator::GreaterOrEqual, version),
+            comparison(Operator::Less, upper),
+        ]);
+    }
+    if let Some(rest) = token.strip_prefix('~') {
+        let version = Version::parse(rest)?;
+        // The last given component may increase (`~1.2` is `>=1.2,<2.0`)
+        let upper = version.bump(version.numbers.len().saturating_sub(2));
+
+        return Some(vec![
+            comparison(Operator::GreaterOrEqual, version),
+            comparison(Operator::Less, upper),
+        ]);
+    }
+
+    let operators = [
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("!=", Operator::NotEqual),
+        ("==", Operator::Equal),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+        ("=", Operator::Equal),
+    ];
+    let (operator, rest) = operators
+        .iter()
+        .find_map(|(prefix, operator)| {
+            token.strip_prefix(prefix).map(|rest| (*operator, rest))
+        })
+        .unwrap_or((Operator::Equal, token));
+
+    // Wildcards (`10.4.*` or `10.4.x`) describe a range
+    if let Some(prefix) = rest
+        .strip_suffix(".*")
+        .or_else(|| rest.strip_suffix(".x"))
+        .or_else(|| rest.strip_suffix(".X"))
+    {
+        if operator != Operator::Equal {
+            return None;
+        }
+        let version = Version::parse(prefix)?;
+        let upper = version.bump(version.numbers.len() - 1);
+
+        return Some(vec![
+            comparison(Operator::GreaterOrEqual, version),
+            comparison(Operator::Less, upper),
+        ]);
+    }
+
+    Some(vec![comparison(operator, Version::parse(rest)?)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(source: &str) -> Version {
+        Version::parse(source).unwrap()
+    }
+
+    #[test]
+    fn parse_version_test() {
+        assert_eq!(vec![10, 4, 1], version("10.4.1").numbers);
+        assert_eq!(vec![10, 4, 1], version("v10.4.1").numbers);
+        assert_eq!(vec![9, 5, 31, 1], version("9.5.31.1").numbers);
+        assert_eq!(vec![10, 4], version("10.4").numbers);
+        assert_eq!(
+            Some("beta2".to_owned()),
+            version("1.0.0-beta2").pre_release
+        );
+        assert_eq!(Some("rc1".to_owned()), version("2.0.0RC1").pre_release);
+        assert_eq!(None, version("1.0.0+build.5").pre_release);
+
+        assert_eq!(None, Version::parse("dev-main"));
+        assert_eq!(None, Version::parse(""));
+        assert_eq!(None, Version::parse("1.a.0"));
+    }
+
+    #[test]
+    fn compare_version_test() {
+        assert!(version("10.4.3") < version("10.4.20"));
+        assert!(version("10.4") == version("10.4.0"));
+        assert!(version("9.5.31.1") > version("9.5.31"));
+        assert!(version("1.0.0-beta1") < version("1.0.0"));
+        assert!(version("1.0.0-alpha3") < version("1.0.0-beta1"));
+        assert!(version("1.0.0-beta2") < version("1.0.0-beta10"));
+        assert!(version("1.0.0-RC1") > version("1.0.0-beta3"));
+        assert!(version("1.0.0-dev") < version("1.0.0-alpha1"));
+    }
+
+    #[test]
+    fn matches_test() {
+        let constraint = VersionConstraint::parse("<10.4.20").unwrap();
+        assert!(constraint.matches("10.4.3"));
+        assert!(constraint.matches("v9.5.0"));
+        assert!(!constraint.matches("10.4.20"));
+        assert!(!constraint.matches("11.5.0"));
+        assert!(!constraint.matches("dev-main"));
+
+        let constraint = VersionConstraint::parse(">=8.7,<9").unwrap();
+        assert!(constraint.matches("8.7.0"));
+        assert!(constraint.matches("8.7.32"));
+        assert!(!constraint.matches("8.6.9"));
+        assert!(!constraint.matches("9.0.0"));
+
+        let constraint = VersionConstraint::parse(">= 8.7 < 9").unwrap();
+        assert!(constraint.matches("8.7.32"));
+        assert!(!constraint.matches("9.5.0"));
+
+        let constraint = VersionConstraint::parse("10.4.1").unwrap();
+        assert!(constraint.matches("10.4.1"));
+        assert!(!constraint.matches("10.4.2"));
+
+        let constraint = VersionConstraint::parse("!=10.4.1").unwrap();
+        assert!(!constraint.matches("10.4.1"));
+        assert!(constraint.matches("10.4.2"));
+    }
+
+    #[test]
+    fn matches_range_test() {
+        let constraint = VersionConstraint::parse("^10.4").unwrap();
+        assert!(constraint.matches("10.4.0"));
+        assert!(constraint.matches("10.9.9"));
+        assert!(!constraint.matches("11.0.0"));
+
+        let constraint = VersionConstraint::parse("^0.3.1").unwrap();
+        assert!(constraint.matches("0.3.5"));
+        assert!(!constraint.matches("0.4.0"));
+
+        let constraint = VersionConstraint::parse("~1.2").unwrap();
+        assert!(constraint.matches("1.9.0"));
+        assert!(!constraint.matches("2.0.0"));
+
+        let constraint = VersionConstraint::parse("~1.2.3").unwrap();
+        assert!(constraint.matches("1.2.9"));
+        assert!(!constraint.matches("1.3.0"));
+
+        let constraint = VersionConstraint::parse("10.4.*").unwrap();
+        assert!(constraint.matches("10.4.12"));
+        assert!(!constraint.matches("10.5.0"));
+
+        let constraint = VersionConstraint::parse("*").unwrap();
+        assert!(constraint.matches("1.0.0"));
+        assert!(!constraint.matches("dev-main"));
+    }
+
+    #[test]
+    fn matches_alternatives_test() {
+        let constraint =
+            VersionConstraint::parse("<8.7.40 || >=9,<9.5.30").unwrap();
+        assert!(constraint.matches("8.7.39"));
+        assert!(constraint.matches("9.5.29"));
+        assert!(!constraint.matches("8.7.40"));
+        assert!(!constraint.matches("9.5.30"));
+    }
+
+    #[test]
+    fn parse_invalid_test() {
+        assert!(VersionConstraint::parse("").is_err());
+        assert!(VersionConstraint::parse("<").is_err());
+        assert!(VersionConstraint::parse(">=8.7,<").is_err());
+        assert!(VersionConstraint::parse("latest").is_err());
+        assert!(VersionConstraint::parse("<10.*").is_err());
+        assert!(VersionConstraint::parse("1.0 ||").is_err());
+        assert_eq!(
+            ">=8.7,<9",
+            VersionConstraint::parse(" >=8.7,<9 ").unwrap().to_string()
+        );
+    }
+}
@@ -3,9 +3,15 @@ mod matrix;
 mod table;
 use self::matrix::Matrix;
 use self::table::Table;
+use crate::audit::{AuditReport, Severity};
+use crate::configuration::{ConfigurationField, FieldSource};
+use crate::constants;
 use crate::information::*;
+use crate::policy::{PolicyReport, RuleResult, RuleStatus};
+use crate::prepare_message;
 use crate::shell::ShellOutputCollection;
-use std::collections::BTreeMap;
+use ansi_term::Colour;
+use std::collections::{BTreeMap, HashSet};
 use std::io::IsTerminal;
 
 use super::ErrorCollection;
@@ -18,6 +24,14 @@ impl ConsoleFormatter {
     pub fn new(use_colors: bool) -> Self {
         Self { use_colors }
     }
+
+    fn paint(&self, colour: Colour, text: String) -> String {
+        if self.use_colors {
+            colour.paint(text).to_string()
+        } else {
+            text
+        }
+    }
 }
 
 const HEADERS: &[&str] = &[
@@ -35,6 +49,12 @@ const HEADERS: &[&str] = &[
 
 const PACKAGE_HEADERS: &[&str] = &["Key", "Version", "State", "Description"];
 
+/// Header of the package column in the package matrix
+const MATRIX_HEADER: &str = "Package";
+
+/// Cell content for hosts without the package in the package matrix
+const MISSING_VERSION: &str = "-";
+
 impl super::FormatterTrait for ConsoleFormatter {
     fn format_information(
         &self,
@@ -42,6 +62,8 @@ impl super::FormatterTrait for ConsoleFormatter {
         information: &Information,
         show_packages: bool,
     ) -> super::FormatterResult {
+        let facts: Vec<String> =
+            information.facts.flatten().into_keys().collect();
         let mut information_collection: InformationCollection =
             InformationCollection::new();
         information_collection.insert(host.to_owned(), information.clone());
@@ -49,6 +71,7 @@ impl super::FormatterTrait for ConsoleFormatter {
         let matrix = Matrix::from_information_collection(
             information_collection,
             show_packages,
+            &facts,
         );
         Ok(Table::left_header(&matrix, self.use_colors))
     }
@@ -57,9 +80,13 @@ impl super::FormatterTrait for ConsoleFormatter {
         &self,
         information: InformationCollection,
         show_packages: bool,
+        facts: &[String],
     ) -> super::FormatterResult {
-        let matrix =
-            Matrix::from_information_collection(information, show_packages);
+        let matrix = Matrix::from_information_collection(
+            information,
+            show_packages,
+            facts,
+        );
         Ok(Table::top_header(&matrix, self.use_colors))
     }
 
@@ -89,6 +116,22 @@ impl super::FormatterTrait for ConsoleFormatter {
         Ok(output)
     }
 
+    fn format_package_matrix(
+        &self,
+        information_collection: &InformationCollection,
+        only_drift: bool,
+    ) -> super::FormatterResult {
+        let matrix =
+            Matrix::from_package_versions(information_collection, only_drift);
+        let highlighted = prevailing_version_mismatches(&matrix);
+
+        Ok(Table::top_header_highlighted(
+            &matrix,
+            self.use_colors,
+            |row, column| highlighted.contains(&(row, column)),
+        ))
+    }
+
     fn format_shell_output_collection(
         &self,
         outputs: ShellOutputCollection,
@@ -97,7 +140,13 @@ impl super::FormatterTrait for ConsoleFormatter {
         let mut sorted_rows = BTreeMap::new();
 
         for (host, error) in errors {
-            sorted_rows.insert(host, ("Error".to_string(), error.to_string()));
+            sorted_rows.insert(
+                host,
+                (
+                    format!("Error ({})", error.kind().name()),
+                    error.to_string(),
+                ),
+            );
         }
         for (host, output) in outputs {
             sorted_rows.insert(host, ("Ok".to_string(), output));
@@ -113,6 +162,341 @@ impl super::FormatterTrait for ConsoleFormatter {
 
         Ok(table.to_string())
     }
+
+    fn format_check_result(
+        &self,
+        information: &InformationCollection,
+        errors: &ErrorCollection,
+    ) -> super::FormatterResult {
+        let mut lines = BTreeMap::new();
+        for (host, information) in information {
+            let protocol = &information.fleet.protocol;
+            let line = match ProtocolCompatibility::of(protocol) {
+                Ok(ProtocolCompatibility::Current) => self.paint(
+                    Colour::Green,
+                    format!("[OK] Host \"{}\" (protocol {})", host, protocol),
+                ),
+                Ok(ProtocolCompatibility::Migrated) => self.paint(
+                    Colour::Green,
+                    format!(
+                        "[OK] Host \"{}\" (protocol {}, migrated to {})",
+                        host,
+                        protocol,
+                        constants::PROTOCOL
+                    ),
+                ),
+                Ok(ProtocolCompatibility::Newer) => self.paint(
+                    Colour::Yellow,
+                    format!(
+                        "[WARNING] Host \"{}\" (protocol {} is newer than the \
+                         supported protocol {})",
+                        host,
+                        protocol,
+                        constants::PROTOCOL
+                    ),
+                ),
+                Err(e) => self.paint(
+                    Colour::Yellow,
+                    format!("[WARNING] Host \"{}\": {}", host, e),
+                ),
+            };
+            lines.insert(host, line);
+        }
+        for (host, error) in errors {
+            lines.insert(
+                host,
+                self.paint(
+                    Colour::Red,
+                    format!(
+                        "[ERROR] Host \"{}\" ({}): {}",
+                        host,
+                        error.kind().name(),
+                        prepare_message(error.message())
+                    ),
+                ),
+            );
+        }
+
+        Ok(lines.into_values().collect::<Vec<_>>().join("\n"))
+    }
+
+    fn format_policy_report(
+        &self,
+        report: &PolicyReport,
+        errors: &ErrorCollection,
+    ) -> super::FormatterResult {
+        let mut blocks = BTreeMap::new();
+        for (host, results) in report {
+            let failed = results.iter().filter(|r| !r.passed()).count();
+            let mut lines = vec![if failed == 0 {
+                self.paint(
+                    Colour::Green,
+                    format!(
+                        "[PASS] Host \"{}\": {} rule(s) passed",
+                        host,
+                        results.len()
+                    ),
+                )
+            } else {
+                self.paint(
+                    Colour::Red,
+                    format!(
+                        "[FAIL] Host \"{}\": {} of {} rule(s) failed",
+                        host,
+                        failed,
+                        results.len()
+                    ),
+                )
+            }];
+            for result in results {
+                lines.push(self.format_rule_result(result));
+            }
+            blocks.insert(host, lines.join("\n"));
+        }
+        for (host, error) in errors {
+            blocks.insert(
+                host,
+                self.paint(
+                    Colour::Red,
+                    format!(
+                        "[ERROR] Host \"{}\" ({}): {}",
+                        host,
+                        error.kind().name(),
+                        prepare_message(error.message())
+                    ),
+                ),
+            );
+        }
+
+        Ok(blocks.into_values().collect::<Vec<_>>().join("\n"))
+    }
+
+    fn format_audit_report(
+        &self,
+        report: &AuditReport,
+    ) -> super::FormatterResult {
+        let mut lines = vec![];
+        for (host, findings) in report {
+            if findings.is_empty() {
+                lines.push(self.paint(
+                    Colour::Green,
+                    format!("[OK] Host \"{}\": no known advisories", host),
+                ));
+                continue;
+            }
+
+            lines.push(self.paint(
+                Colour::Red,
+                format!(
+                    "[VULNERABLE] Host \"{}\": {} advisory(s)",
+                    host,
+                    findings.len()
+                ),
+            ));
+            for finding in findings {
+                let severity = format!(
+                    "{:8}",
+                    finding.severity.to_string().to_uppercase()
+                );
+                lines.push(format!(
+                    "    {} {} {} {}{}",
+                    self.paint(severity_colour(finding.severity), severity),
+                    finding.package,
+                    finding.version,
+                    finding.advisory,
+                    if finding.summary.is_empty() {
+                        "".to_owned()
+                    } else {
+                        format!(": {}", finding.summary)
+                    }
+                ));
+            }
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    fn format_configuration(
+        &self,
+        host: &str,
+        fields: &[ConfigurationField],
+    ) -> super::FormatterResult {
+        let values: Vec<String> = fields
+            .iter()
+            .map(|field| match field.value {
+                serde_json::Value::String(ref text) => text.clone(),
+                ref value => value.to_string(),
+            })
+            .collect();
+        let name_width = fields.iter().map(|f| f.name.len()).max();
+        let value_width = values.iter().map(|v| v.chars().count()).max();
+
+        let mut lines = vec![format!("Configuration of host \"{}\"", host)];
+        for (field, value) in fields.iter().zip(values) {
+            let source = match field.source {
+                FieldSource::BuiltIn => {
+                    self.paint(Colour::Fixed(8), field.source.to_string())
+                }
+                FieldSource::Host => field.source.to_string(),
+                _ => self.paint(Colour::Cyan, field.source.to_string()),
+            };
+            lines.push(format!(
+                "    {:name_width$}  {:value_width$}  {}",
+                field.name,
+                value,
+                source,
+                name_width = name_width.unwrap_or_default(),
+                value_width = value_width.unwrap_or_default(),
+            ));
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    fn format_diff(
+        &self,
+        from: &str,
+        to: &str,
+        diff: &CollectionDiff,
+    ) -> super::FormatterResult {
+        let mut lines = vec![format!("Changes from {} to {}", from, to)];
+        if diff.is_empty() {
+            lines.push("No changes".to_owned());
+        }
+
+        for (host, host_diff) in diff {
+            match host_diff {
+                HostDiff::Added => lines.push(self.paint(
+                    Colour::Green,
+                    format!("+ Host \"{}\" was added", host),
+                )),
+                HostDiff::Removed => lines.push(self.paint(
+                    Colour::Red,
+                    format!("- Host \"{}\" was removed", host),
+                )),
+                HostDiff::Changed {
+                    application_version,
+                    os_version,
+                    packages,
+                } => {
+                    lines.push(format!("~ Host \"{}\"", host));
+                    if let Some(change) = application_version {
+                        lines.push(self.paint(
+                            Colour::Yellow,
+                            format!(
+                                "    ~ Application version {} -> {}",
+                                change.from, change.to
+                            ),
+                        ));
+                    }
+                    if let Some(change) = os_version {
+                        lines.push(self.paint(
+                            Colour::Yellow,
+                            format!(
+                                "    ~ OS version {} -> {}",
+                                change.from, change.to
+                            ),
+                        ));
+                    }
+                    for (key, change) in packages {
+                        lines.push(self.format_package_change(key, change));
+                    }
+                }
+            }
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+impl ConsoleFormatter {
+    fn format_rule_result(&self, result: &RuleResult) -> String {
+        let status = match result.status {
+            RuleStatus::Pass => self.paint(Colour::Green, "PASS".to_owned()),
+            RuleStatus::Fail => self.paint(Colour::Red, "FAIL".to_owned()),
+        };
+        let severity =
+            format!("{:8}", result.severity.to_string().to_uppercase());
+
+        format!(
+            "    {} {} {}: {}",
+            status,
+            self.paint(severity_colour(result.severity), severity),
+            result.rule,
+            result.message
+        )
+    }
+
+    fn format_package_change(
+        &self,
+        key: &str,
+        change: &PackageChange,
+    ) -> String {
+        match change {
+            PackageChange::Added { version, state } => self.paint(
+                Colour::Green,
+                format!("    + {} {} ({})", key, version, state),
+            ),
+            PackageChange::Removed { version, state } => self.paint(
+                Colour::Red,
+                format!("    - {} {} ({})", key, version, state),
+            ),
+            PackageChange::Changed { version, state } => {
+                let mut parts = vec![];
+                if let Some(version) = version {
+                    parts.push(format!("{} -> {}", version.from, version.to));
+                }
+                if let Some(state) = state {
+                    parts.push(format!("{} -> {}", state.from, state.to));
+                }
+
+                self.paint(
+                    Colour::Yellow,
+                    format!("    ~ {} {}", key, parts.join(", ")),
+                )
+            }
+        }
+    }
+}
+
+fn severity_colour(severity: Severity) -> Colour {
+    match severity {
+        Severity::Critical | Severity::High => Colour::Red,
+        Severity::Medium => Colour::Yellow,
+        Severity::Low | Severity::Unknown => Colour::Blue,
+    }
+}
+
+/// Return the positions of the version cells, that differ from the version
+/// found on most hosts
+fn prevailing_version_mismatches(
+    matrix: &Matrix<String>,
+) -> HashSet<(usize, usize)> {
+    let mut mismatches = HashSet::new();
+    for (row_index, row) in matrix.data().iter().enumerate().skip(1) {
+        let versions = &row[1..];
+        let mut counts: Vec<(&String, usize)> = vec![];
+        for version in versions {
+            match counts.iter_mut().find(|(v, _)| *v == version) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((version, 1)),
+            }
+        }
+
+        // `max_by_key` returns the last maximum, prefer the first one seen
+        let prevailing = counts
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(version, _)| *version);
+        for (column_index, version) in versions.iter().enumerate() {
+            if Some(version) != prevailing {
+                mismatches.insert((row_index, column_index + 1));
+            }
+        }
+    }
+
+    mismatches
 }
 
 fn crop_cell_content(content: &str) -> String {
@@ -123,17 +507,28 @@ fn crop_cell_content(content: &str) -> String {
 }
 
 impl Matrix<String> {
+    /// Build a matrix with a row per host
+    ///
+    /// The values of the `facts` paths are added as additional columns
     fn from_information_collection(
         information_collection: InformationCollection,
         _show_packages: bool,
+        facts: &[String],
     ) -> Matrix<String> {
         let mut rows: Vec<Vec<String>> =
             Vec::with_capacity(information_collection.len() + 1);
 
-        rows.push(HEADERS.iter().map(|x| String::from(x.to_owned())).collect());
+        rows.push(
+            HEADERS
+                .iter()
+                .map(|x| String::from(x.to_owned()))
+                .chain(facts.iter().cloned())
+                .collect(),
+        );
 
         for (host, info) in information_collection {
-            let mut cells: Vec<String> = Vec::with_capacity(HEADERS.len());
+            let mut cells: Vec<String> =
+                Vec::with_capacity(HEADERS.len() + facts.len());
 
             cells.push(host);
             cells.push(info.system.application.name);
@@ -154,6 +549,9 @@ impl Matrix<String> {
                 info.system.platform.os.version,
                 info.system.platform.os.machine
             ));
+            for path in facts {
+                cells.push(info.facts.get_text(path).unwrap_or_default());
+            }
 
             rows.push(cells);
         }
@@ -161,6 +559,53 @@ impl Matrix<String> {
         Matrix::from_vec(rows)
     }
 
+    /// Build a matrix with the packages as rows and the hosts as columns
+    ///
+    /// If `only_drift` is set, packages with the same version on all hosts are
+    /// omitted
+    fn from_package_versions(
+        information_collection: &InformationCollection,
+        only_drift: bool,
+    ) -> Matrix<String> {
+        let package_versions = collect_package_versions(information_collection);
+
+        // Build one row per host and transpose them into one row per package
+        let mut rows: Vec<Vec<String>> =
+            Vec::with_capacity(information_collection.len() + 1);
+        rows.push(
+            std::iter::once(MATRIX_HEADER.to_owned())
+                .chain(package_versions.keys().cloned())
+                .collect(),
+        );
+        for host in information_collection.keys() {
+            let mut cells: Vec<String> =
+                Vec::with_capacity(package_versions.len() + 1);
+            cells.push(host.clone());
+            for versions in package_versions.values() {
+                cells.push(match &versions[host] {
+                    Some(version) => version.clone(),
+                    None => MISSING_VERSION.to_owned(),
+                });
+            }
+
+            rows.push(cells);
+        }
+
+        let transposed = Matrix::from_vec(rows).transpose();
+        if !only_drift {
+            return transposed;
+        }
+
+        Matrix::from_vec(
+            transposed
+                .into_iter()
+                .enumerate()
+                .filter(|(index, row)| *index == 0 || has_drift(&row[1..]))
+                .map(|(_, row)| row)
+                .collect(),
+        )
+    }
+
     fn from_packages(packages: &Packages) -> Matrix<String> {
         let mut rows: Vec<Vec<String>> = Vec::with_capacity(packages.len() + 1);
 
@@ -186,3 +631,176 @@ impl Matrix<String> {
         Matrix::from_vec(rows)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn build_collection() -> InformationCollection {
+        let hosts: &[(&str, &[(&str, &str)])] = &[
+            ("a", &[("core", "10.4.1"), ("news", "8.0.0")]),
+            ("b", &[("core", "10.4.2"), ("news", "8.0.0")]),
+            ("c", &[("core", "10.4.1")]),
+        ];
+
+        let mut collection = InformationCollection::new();
+        for (host, packages) in hosts {
+            let mut information = Information::new_for_current_env();
+            information.packages = Packages::new_with_packages(
+                packages
+                    .iter()
+                    .map(|(key, version)| {
+                        (
+                            key.to_string(),
+                            Package {
+                                key: key.to_string(),
+                                version: version.to_string(),
+                                description: "".to_owned(),
+                                state: "active".to_owned(),
+                            },
+                        )
+                    })
+                    .collect::<HashMap<_, _>>(),
+            );
+            collection.insert(host.to_string(), information);
+        }
+
+        collection
+    }
+
+    #[test]
+    fn from_package_versions_test() {
+        let matrix = Matrix::from_package_versions(&build_collection(), false);
+        assert_eq!(
+            &vec![
+                vec!["Package", "a", "b", "c"],
+                vec!["core", "10.4.1", "10.4.2", "10.4.1"],
+                vec!["news", "8.0.0", "8.0.0", "-"],
+            ],
+            matrix.data()
+        );
+    }
+
+    #[test]
+    fn from_package_versions_only_drift_test() {
+        let mut collection = build_collection();
+        collection.remove("c");
+
+        let matrix = Matrix::from_package_versions(&collection, true);
+        assert_eq!(
+            &vec![vec!["Package", "a", "b"], vec!["core", "10.4.1", "10.4.2"],],
+            matrix.data()
+        );
+    }
+
+    #[test]
+    fn prevailing_version_mismatches_test() {
+        let matrix = Matrix::from_package_versions(&build_collection(), false);
+        let mismatches = prevailing_version_mismatches(&matrix);
+
+        assert_eq!(HashSet::from([(1, 2), (2, 3)]), mismatches);
+    }
+
+    #[test]
+    fn format_policy_report_test() {
+        use crate::error::Error;
+        use crate::formatter::FormatterTrait;
+
+        let result = |rule: &str, severity, passed: bool| RuleResult {
+            rule: rule.to_owned(),
+            severity,
+            status: if passed {
+                RuleStatus::Pass
+            } else {
+                RuleStatus::Fail
+            },
+            message: "os.vendor is 'Linux'".to_owned(),
+        };
+        let report = PolicyReport::from([
+            ("a".to_owned(), vec![result("Linux", Severity::Low, true)]),
+            (
+                "b".to_owned(),
+                vec![
+                    result("Linux", Severity::Low, true),
+                    result("Modern", Severity::High, false),
+                ],
+            ),
+        ]);
+        let errors = ErrorCollection::from([(
+            "c".to_owned(),
+            Error::connect("Connection refused"),
+        )]);
+
+        let output = ConsoleFormatter::new(false)
+            .format_policy_report(&report, &errors)
+            .unwrap();
+        assert_eq!(
+            "[PASS] Host \"a\": 1 rule(s) passed\n\
+             \x20   PASS LOW      Linux: os.vendor is 'Linux'\n\
+             [FAIL] Host \"b\": 1 of 2 rule(s) failed\n\
+             \x20   PASS LOW      Linux: os.vendor is 'Linux'\n\
+             \x20   FAIL HIGH     Modern: os.vendor is 'Linux'\n\
+             [ERROR] Host \"c\" (connect): Connection refused",
+            output
+        );
+    }
+
+    #[test]
+    fn format_check_result_test() {
+        use crate::formatter::FormatterTrait;
+
+        let mut collection = InformationCollection::new();
+        for (host, protocol) in [("a", "0.2.0"), ("b", "0.1.0"), ("c", "0.3.0")]
+        {
+            let mut information = Information::new_for_current_env();
+            information.fleet.protocol = protocol.to_owned();
+            collection.insert(host.to_owned(), information);
+        }
+
+        let output = ConsoleFormatter::new(false)
+            .format_check_result(&collection, &ErrorCollection::new())
+            .unwrap();
+        assert_eq!(
+            "[OK] Host \"a\" (protocol 0.2.0)\n\
+             [OK] Host \"b\" (protocol 0.1.0, migrated to 0.2.0)\n\
+             [WARNING] Host \"c\" (protocol 0.3.0 is newer than the supported \
+             protocol 0.2.0)",
+            output
+        );
+    }
+
+    #[test]
+    fn format_configuration_test() {
+        use crate::formatter::FormatterTrait;
+        use serde_json::json;
+
+        let field = |name: &str, value, source| ConfigurationField {
+            name: name.to_owned(),
+            value,
+            source,
+        };
+        let fields = [
+            field(
+                "command",
+                json!("fleet provide"),
+                FieldSource::Template("web".to_owned()),
+            ),
+            field("disabled", json!(false), FieldSource::BuiltIn),
+            field("port", json!(2222), FieldSource::Defaults),
+            field("username", json!("deploy"), FieldSource::Host),
+        ];
+
+        let output = ConsoleFormatter::new(false)
+            .format_configuration("shop", &fields)
+            .unwrap();
+        assert_eq!(
+            "Configuration of host \"shop\"\n\
+             \x20   command   fleet provide  template:web\n\
+             \x20   disabled  false          built-in\n\
+             \x20   port      2222           defaults\n\
+             \x20   username  deploy         host",
+            output
+        );
+    }
+}
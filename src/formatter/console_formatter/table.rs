@@ -11,9 +11,23 @@ impl Table {
     pub fn top_header<S>(input: &Matrix<S>, colorize: bool) -> String
     where
         S: Into<String> + Clone + Display + Debug,
+    {
+        Table::top_header_highlighted(input, colorize, |_, _| false)
+    }
+
+    /// Build the table like [`Table::top_header`], but with the cells for
+    /// which `highlight(row, column)` returns `true` highlighted
+    pub fn top_header_highlighted<S, H>(
+        input: &Matrix<S>,
+        colorize: bool,
+        highlight: H,
+    ) -> String
+    where
+        S: Into<String> + Clone + Display + Debug,
+        H: Fn(usize, usize) -> bool,
     {
         let column_widths = calc_column_widths(input);
-        build_layout_top(&column_widths, input, colorize)
+        build_layout_top(&column_widths, input, colorize, highlight)
     }
 
     pub fn left_header<S>(input: &Matrix<S>, colorize: bool) -> String
@@ -26,13 +40,15 @@ impl Table {
     }
 }
 
-fn build_layout_top<S>(
+fn build_layout_top<S, H>(
     column_widths: &[usize],
     input: &Matrix<S>,
     colorize: bool,
+    highlight: H,
 ) -> String
 where
     S: Into<String> + Clone + Display + Debug,
+    H: Fn(usize, usize) -> bool,
 {
     let mut output = "".to_owned();
 
@@ -40,9 +56,18 @@ where
         let is_even = i % 2 == 0;
         output += colorize_row("│", is_even, colorize).as_str();
 
-        for (cell, width) in row.iter().zip(column_widths) {
-            let text = format!(" {:width$} │", cell, width = width);
-            output += colorize_row(&text, is_even, colorize).as_str()
+        for (j, (cell, width)) in row.iter().zip(column_widths).enumerate() {
+            if colorize && highlight(i, j) {
+                output += &colorize_highlighted(&format!(
+                    " {:width$} ",
+                    cell,
+                    width = width
+                ));
+                output += colorize_row("│", is_even, colorize).as_str();
+            } else {
+                let text = format!(" {:width$} │", cell, width = width);
+                output += colorize_row(&text, is_even, colorize).as_str()
+            }
         }
         output += "\n";
 
@@ -122,6 +147,13 @@ fn colorize_row(text: &str, is_even: bool, colorize: bool) -> String {
     }
 }
 
+fn colorize_highlighted(text: &str) -> String {
+    Colour::RGB(40, 40, 40)
+        .on(Colour::RGB(255, 200, 120))
+        .paint(text)
+        .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,4 +199,25 @@ mod tests {
 
         assert_eq!(expected, output);
     }
+
+    #[test]
+    fn top_header_highlighted_test() {
+        let matrix = Matrix::from_vec(vec![
+            vec!["Package", "a", "b"],
+            vec!["core", "10.4.1", "10.4.2"],
+        ]);
+
+        let plain =
+            Table::top_header_highlighted(&matrix, false, |row, column| {
+                row == 1 && column == 2
+            });
+        assert_eq!(Table::top_header(&matrix, false), plain);
+
+        let colorized =
+            Table::top_header_highlighted(&matrix, true, |row, column| {
+                row == 1 && column == 2
+            });
+        assert!(colorized.contains(&colorize_highlighted(" 10.4.2 ")));
+        assert!(!colorized.contains(&colorize_highlighted(" 10.4.1 ")));
+    }
 }
@@ -1,8 +1,12 @@
 use super::FormatterResult;
+use crate::audit::AuditReport;
+use crate::configuration::ConfigurationField;
 use crate::error::*;
 use crate::information::*;
+use crate::policy::{PolicyReport, RuleResult};
 use crate::shell::ShellOutputCollection;
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 pub struct JsonFormatter;
 
@@ -32,6 +36,7 @@ impl super::FormatterTrait for JsonFormatter {
         &self,
         information: InformationCollection,
         show_packages: bool,
+        _: &[String],
     ) -> FormatterResult {
         let information_collection = if !show_packages {
             collection_without_packages(information)
@@ -58,6 +63,20 @@ impl super::FormatterTrait for JsonFormatter {
         self.format_data(packages_collection)
     }
 
+    fn format_package_matrix(
+        &self,
+        information_collection: &InformationCollection,
+        only_drift: bool,
+    ) -> FormatterResult {
+        let mut package_versions =
+            collect_package_versions(information_collection);
+        if only_drift {
+            package_versions.retain(|_, versions| has_drift(versions.values()));
+        }
+
+        self.format_data(package_versions)
+    }
+
     fn format_shell_output_collection(
         &self,
         outputs: ShellOutputCollection,
@@ -66,14 +85,109 @@ impl super::FormatterTrait for JsonFormatter {
         #[derive(Serialize)]
         struct ShellOutputJson {
             outputs: ShellOutputCollection,
-            errors: Vec<String>,
+            errors: ErrorCollection,
+        }
+        self.format_data(ShellOutputJson { outputs, errors })
+    }
+
+    fn format_check_result(
+        &self,
+        information: &InformationCollection,
+        errors: &ErrorCollection,
+    ) -> FormatterResult {
+        #[derive(Serialize)]
+        #[serde(tag = "status", rename_all = "snake_case")]
+        enum CheckResultJson<'a> {
+            Ok {
+                protocol: &'a str,
+                compatibility: Option<ProtocolCompatibility>,
+            },
+            Error {
+                error: &'a Error,
+            },
+        }
+
+        let mut result = BTreeMap::new();
+        for (host, information) in information {
+            let protocol = information.fleet.protocol.as_str();
+            result.insert(
+                host,
+                CheckResultJson::Ok {
+                    protocol,
+                    compatibility: ProtocolCompatibility::of(protocol).ok(),
+                },
+            );
+        }
+        for (host, error) in errors {
+            result.insert(host, CheckResultJson::Error { error });
+        }
+
+        self.format_data(result)
+    }
+
+    fn format_audit_report(&self, report: &AuditReport) -> FormatterResult {
+        self.format_data(report)
+    }
+
+    fn format_policy_report(
+        &self,
+        report: &PolicyReport,
+        errors: &ErrorCollection,
+    ) -> FormatterResult {
+        #[derive(Serialize)]
+        #[serde(tag = "status", rename_all = "snake_case")]
+        enum PolicyResultJson<'a> {
+            Pass { rules: &'a [RuleResult] },
+            Fail { rules: &'a [RuleResult] },
+            Error { error: &'a Error },
+        }
+
+        let mut result = BTreeMap::new();
+        for (host, rules) in report {
+            if rules.iter().all(RuleResult::passed) {
+                result.insert(host, PolicyResultJson::Pass { rules });
+            } else {
+                result.insert(host, PolicyResultJson::Fail { rules });
+            }
+        }
+        for (host, error) in errors {
+            result.insert(host, PolicyResultJson::Error { error });
         }
-        self.format_data(ShellOutputJson {
-            outputs,
-            errors: errors
-                .iter()
-                .map(|(h, e)| format!("{}: {}", h, e))
-                .collect(),
+
+        self.format_data(result)
+    }
+
+    fn format_configuration(
+        &self,
+        host: &str,
+        fields: &[ConfigurationField],
+    ) -> FormatterResult {
+        #[derive(Serialize)]
+        struct ConfigurationJson<'a> {
+            host: &'a str,
+            fields: &'a [ConfigurationField],
+        }
+
+        self.format_data(ConfigurationJson { host, fields })
+    }
+
+    fn format_diff(
+        &self,
+        from: &str,
+        to: &str,
+        diff: &CollectionDiff,
+    ) -> FormatterResult {
+        #[derive(Serialize)]
+        struct DiffJson<'a> {
+            from: &'a str,
+            to: &'a str,
+            hosts: &'a CollectionDiff,
+        }
+
+        self.format_data(DiffJson {
+            from,
+            to,
+            hosts: diff,
         })
     }
 }
@@ -3,8 +3,11 @@ mod json_formatter;
 
 pub use self::console_formatter::ConsoleFormatter;
 pub use self::json_formatter::JsonFormatter;
+use crate::audit::AuditReport;
+use crate::configuration::ConfigurationField;
 use crate::error::*;
 use crate::information::*;
+use crate::policy::PolicyReport;
 use crate::shell::ShellOutputCollection;
 
 type FormatterResult = Result<String, Error>;
@@ -23,11 +26,13 @@ pub trait FormatterTrait {
 
     /// Format all [`Information`] objects in the collection
     ///
-    /// Some implementations may ignore `show_packages`
+    /// `facts` are the paths of [`Facts`] to show in addition to the default
+    /// fields. Some implementations may ignore `show_packages` and `facts`
     fn format_information_collection(
         &self,
         information: InformationCollection,
         show_packages: bool,
+        facts: &[String],
     ) -> FormatterResult;
 
     /// Format the given [`Packages`]
@@ -39,12 +44,59 @@ pub trait FormatterTrait {
         information_collection: InformationCollection,
     ) -> FormatterResult;
 
+    /// Format the versions of the packages of all hosts side by side
+    ///
+    /// If `only_drift` is set, packages with the same version on all hosts are
+    /// omitted
+    fn format_package_matrix(
+        &self,
+        information_collection: &InformationCollection,
+        only_drift: bool,
+    ) -> FormatterResult;
+
     /// Format all the output from shell execution in the collection
     fn format_shell_output_collection(
         &self,
         outputs: ShellOutputCollection,
         errors: ErrorCollection,
     ) -> FormatterResult;
+
+    /// Format the result of checking the hosts
+    fn format_check_result(
+        &self,
+        information: &InformationCollection,
+        errors: &ErrorCollection,
+    ) -> FormatterResult;
+
+    /// Format the advisories found for the packages of each host
+    fn format_audit_report(&self, report: &AuditReport) -> FormatterResult;
+
+    /// Format the result of each policy rule for each host
+    ///
+    /// Hosts that could not be checked are listed with their error
+    fn format_policy_report(
+        &self,
+        report: &PolicyReport,
+        errors: &ErrorCollection,
+    ) -> FormatterResult;
+
+    /// Format the properties of the host's configuration and their sources
+    fn format_configuration(
+        &self,
+        host: &str,
+        fields: &[ConfigurationField],
+    ) -> FormatterResult;
+
+    /// Format the changes between two states of the hosts
+    ///
+    /// `from` and `to` describe the compared states (e.g. snapshot
+    /// identifiers)
+    fn format_diff(
+        &self,
+        from: &str,
+        to: &str,
+        diff: &CollectionDiff,
+    ) -> FormatterResult;
 }
 
 /// Wrapper around the different formatter types
@@ -74,14 +126,19 @@ impl FormatterTrait for Formatter {
         &self,
         information: InformationCollection,
         show_packages: bool,
+        facts: &[String],
     ) -> FormatterResult {
         match self {
-            Formatter::Json(ref f) => {
-                f.format_information_collection(information, show_packages)
-            }
-            Formatter::Console(ref f) => {
-                f.format_information_collection(information, show_packages)
-            }
+            Formatter::Json(ref f) => f.format_information_collection(
+                information,
+                show_packages,
+                facts,
+            ),
+            Formatter::Console(ref f) => f.format_information_collection(
+                information,
+                show_packages,
+                facts,
+            ),
         }
     }
 
@@ -108,6 +165,21 @@ impl FormatterTrait for Formatter {
         }
     }
 
+    fn format_package_matrix(
+        &self,
+        information_collection: &InformationCollection,
+        only_drift: bool,
+    ) -> FormatterResult {
+        match self {
+            Formatter::Json(ref f) => {
+                f.format_package_matrix(information_collection, only_drift)
+            }
+            Formatter::Console(ref f) => {
+                f.format_package_matrix(information_collection, only_drift)
+            }
+        }
+    }
+
     fn format_shell_output_collection(
         &self,
         outputs: ShellOutputCollection,
@@ -122,6 +194,62 @@ impl FormatterTrait for Formatter {
             }
         }
     }
+
+    fn format_check_result(
+        &self,
+        information: &InformationCollection,
+        errors: &ErrorCollection,
+    ) -> FormatterResult {
+        match self {
+            Formatter::Json(ref f) => {
+                f.format_check_result(information, errors)
+            }
+            Formatter::Console(ref f) => {
+                f.format_check_result(information, errors)
+            }
+        }
+    }
+
+    fn format_audit_report(&self, report: &AuditReport) -> FormatterResult {
+        match self {
+            Formatter::Json(ref f) => f.format_audit_report(report),
+            Formatter::Console(ref f) => f.format_audit_report(report),
+        }
+    }
+
+    fn format_policy_report(
+        &self,
+        report: &PolicyReport,
+        errors: &ErrorCollection,
+    ) -> FormatterResult {
+        match self {
+            Formatter::Json(ref f) => f.format_policy_report(report, errors),
+            Formatter::Console(ref f) => f.format_policy_report(report, errors),
+        }
+    }
+
+    fn format_configuration(
+        &self,
+        host: &str,
+        fields: &[ConfigurationField],
+    ) -> FormatterResult {
+        match self {
+            Formatter::Json(ref f) => f.format_configuration(host, fields),
+            Formatter::Console(ref f) => f.format_configuration(host, fields),
+        }
+    }
+
+    fn format_diff(
+        &self,
+        from: &str,
+        to: &str,
+        diff: &CollectionDiff,
+    ) -> FormatterResult {
+        match self {
+            Formatter::Json(ref f) => f.format_diff(from, to, diff),
+            Formatter::Console(ref f) => f.format_diff(from, to, diff),
+        }
+    }
 }
 
 /// Returns the formatter for the given format string
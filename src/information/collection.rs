@@ -3,6 +3,8 @@ use crate::error::*;
 use std::collections::BTreeMap;
 
 pub type InformationCollection = BTreeMap<String, Information>;
+/// Warnings about the information of each host
+pub type WarningCollection = BTreeMap<String, Vec<String>>;
 pub type CollectionResult =
     Result<(InformationCollection, ErrorCollection), Error>;
 
@@ -0,0 +1,439 @@
+use super::protocol::migrate_0_1_to_0_2;
+use super::{Information, Package, ProtocolCompatibility};
+use crate::constants;
+use crate::error::Error;
+use serde_json::{Deserializer, Value};
+
+/// Decode the provider output into [`Information`]
+///
+/// The decoder is lenient: non-JSON output around the information (e.g. log
+/// messages) is ignored, and missing or invalid fields are replaced with
+/// defaults. Each repair is described in the returned warnings.
+///
+/// Output of older protocols is migrated to the current protocol. The
+/// `fleet.protocol` field keeps the protocol spoken by the provider
+pub fn decode_information(
+    content: &str,
+) -> Result<(Information, Vec<String>), Error> {
+    let build_error =
+        |message: String| Error::protocol(message, content.to_owned());
+    let mut warnings = vec![];
+
+    let mut value = match extract_json(content, &mut warnings) {
+        Ok(value) => value,
+        Err(e) => {
+            return Err(build_error(format!(
+                "Could not decode the provider output: {}",
+                e
+            )))
+        }
+    };
+
+    let protocol = match value.pointer("/fleet/protocol") {
+        Some(Value::String(protocol)) => protocol.clone(),
+        _ => {
+            return Err(build_error(
+                "The provider output does not define `fleet.protocol`"
+                    .to_owned(),
+            ))
+        }
+    };
+    let provider = describe_provider(&value);
+    let compatibility = ProtocolCompatibility::of(&protocol).map_err(|e| {
+        build_error(format!("{} uses an unsupported protocol: {}", provider, e))
+    })?;
+
+    match compatibility {
+        ProtocolCompatibility::Current => {}
+        ProtocolCompatibility::Migrated => migrate_0_1_to_0_2(&mut value),
+        ProtocolCompatibility::Newer => warnings.push(format!(
+            "{} uses protocol {}, which is newer than the supported protocol \
+             {}",
+            provider,
+            protocol,
+            constants::PROTOCOL
+        )),
+    }
+    repair_information(&mut value, &mut warnings);
+
+    match serde_json::from_value(value) {
+        Ok(information) => Ok((information, warnings)),
+        Err(e) => Err(build_error(format!(
+            "Could not decode the provider output: {}",
+            e
+        ))),
+    }
+}
+
+/// Return the first JSON object with a `fleet` key in the content
+///
+/// Output before and after the object is ignored, including other JSON
+/// objects (e.g. log messages of the application)
+fn extract_json(
+    content: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Value, serde_json::Error> {
+    let error = match serde_json::from_str(content) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    // Objects nested in other objects are never the information
+    let mut skip_until = 0;
+    for (start, _) in content.match_indices('{') {
+        if start < skip_until {
+            continue;
+        }
+        let mut stream =
+            Deserializer::from_str(&content[start..]).into_iter::<Value>();
+        let value = match stream.next() {
+            Some(Ok(value @ Value::Object(_))) => value,
+            _ => continue,
+        };
+        let end = start + stream.byte_offset();
+        if value.get("fleet").is_none() {
+            skip_until = end;
+            continue;
+        }
+
+        for (noise, position) in
+            [(&content[..start], "before"), (&content[end..], "after")]
+        {
+            let lines = noise.trim().lines().count();
+            if lines > 0 {
+                warnings.push(format!(
+                    "Ignored {} line(s) of non-JSON output {} the information",
+                    lines, position
+                ));
+            }
+        }
+
+        return Ok(value);
+    }
+
+    Err(error)
+}
+
+/// Add missing fields and replace invalid values with defaults
+fn repair_information(value: &mut Value, warnings: &mut Vec<String>) {
+    let template = serde_json::to_value(Information::default())
+        .expect("Default information must be serializable");
+    repair(value, &template, "", warnings);
+
+    // Facts are optional, but must be an object
+    match value.get("facts") {
+        None | Some(Value::Object(_)) => {}
+        Some(facts) => {
+            if !facts.is_null() {
+                warnings.push("Invalid `facts`, ignoring them".to_owned());
+            }
+            if let Some(object) = value.as_object_mut() {
+                object.remove("facts");
+            }
+        }
+    }
+
+    // Entries of maps are not defined by the template
+    if let Some(Value::Object(meta)) =
+        value.pointer_mut("/system/application/meta")
+    {
+        for (key, entry) in meta.iter_mut() {
+            let path = format!("system.application.meta.{}", key);
+            repair(entry, &Value::String(String::new()), &path, warnings);
+        }
+    }
+    if let Some(Value::Object(packages)) = value.pointer_mut("/packages/all") {
+        let template = serde_json::to_value(Package::default())
+            .expect("Default package must be serializable");
+        for (key, entry) in packages.iter_mut() {
+            let path = format!("packages.all.{}", key);
+            repair(entry, &template, &path, warnings);
+            if entry["key"] == "" {
+                entry["key"] = Value::String(key.clone());
+            }
+        }
+    }
+}
+
+/// Make `value` match the structure of `template`
+///
+/// `null` in the template marks an optional string
+fn repair(
+    value: &mut Value,
+    template: &Value,
+    path: &str,
+    warnings: &mut Vec<String>,
+) {
+    match (template, &mut *value) {
+        (Value::Object(template), Value::Object(object)) => {
+            for (key, default) in template {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match object.get_mut(key) {
+                    Some(field) => {
+                        repair(field, default, &field_path, warnings)
+                    }
+                    None => {
+                        if !default.is_null() {
+                            warnings.push(format!(
+                                "Missing `{}`, using the default",
+                                field_path
+                            ));
+                        }
+                        object.insert(key.clone(), default.clone());
+                    }
+                }
+            }
+        }
+        (Value::String(_) | Value::Null, Value::String(_))
+        | (Value::Null, Value::Null) => {}
+        (Value::String(_) | Value::Null, Value::Number(_) | Value::Bool(_)) => {
+            *value = Value::String(value.to_string());
+        }
+        (_, Value::Null) => {
+            warnings.push(format!("Missing `{}`, using the default", path));
+            *value = template.clone();
+        }
+        _ => {
+            warnings.push(format!("Invalid `{}`, using the default", path));
+            *value = template.clone();
+        }
+    }
+}
+
+fn describe_provider(value: &Value) -> String {
+    let field = |name: &str| {
+        value
+            .pointer(&format!("/fleet/{}", name))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+
+    match (field("providerName"), field("providerVersion")) {
+        (name, _) if name.is_empty() => "The provider".to_owned(),
+        (name, version) if version.is_empty() => format!("Provider {}", name),
+        (name, version) => format!("Provider {} {}", name, version),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::get_test_resource_path;
+    use std::fs;
+
+    fn build_output(protocol: &str, packages: Value) -> String {
+        let mut value = serde_json::to_value(
+            Information::new_for_current_env().without_packages(),
+        )
+        .unwrap();
+        value["fleet"]["protocol"] = Value::String(protocol.to_owned());
+        value["fleet"]["providerName"] = Value::String("fleet-typo3".into());
+        value["fleet"]["providerVersion"] = Value::String("1.2.0".into());
+        value["packages"] = packages;
+
+        value.to_string()
+    }
+
+    fn decode(content: &str) -> (Information, Vec<String>) {
+        decode_information(content).unwrap()
+    }
+
+    #[test]
+    fn decode_0_1_0_test() {
+        let content = fs::read_to_string(get_test_resource_path(
+            "protocol-test-0.1.0.json",
+        ))
+        .unwrap();
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("0.1.0", information.fleet.protocol);
+        assert_eq!(56, information.packages.len());
+        assert!(information.packages["core"].is_active());
+        assert!(!information.packages["recycler"].is_active());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn migrate_without_all_test() {
+        let content = build_output(
+            "0.1.0",
+            serde_json::json!({
+                "active": {
+                    "core": {"key": "core", "version": "8.7.0", "description": ""}
+                },
+                "inactive": {
+                    "news": {"key": "news", "version": "6.0.0", "description": ""}
+                }
+            }),
+        );
+        let (information, _) = decode(&content);
+
+        assert_eq!(2, information.packages.len());
+        assert!(information.packages["core"].is_active());
+        assert_eq!("inactive", information.packages["news"].state);
+    }
+
+    #[test]
+    fn decode_current_test() {
+        let content = build_output(
+            "0.2.0",
+            serde_json::json!({
+                "all": {
+                    "core": {
+                        "key": "core",
+                        "version": "11.5.0",
+                        "description": "",
+                        "state": "active"
+                    }
+                }
+            }),
+        );
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("0.2.0", information.fleet.protocol);
+        assert_eq!(1, information.packages.len());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn decode_with_noise_test() {
+        let output = build_output("0.2.0", serde_json::json!({"all": {}}));
+        let content = format!(
+            "PHP Deprecated: {{deprecated}} in Command.php\nLoading...\n{}\n\
+             Done\n",
+            output
+        );
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("0.2.0", information.fleet.protocol);
+        assert_eq!(
+            vec![
+                "Ignored 2 line(s) of non-JSON output before the information",
+                "Ignored 1 line(s) of non-JSON output after the information",
+            ],
+            warnings
+        );
+    }
+
+    #[test]
+    fn decode_after_other_json_test() {
+        let output = build_output("0.2.0", serde_json::json!({"all": {}}));
+        let content = format!(
+            "{{\"level\": \"info\", \"context\": {{\"fleet\": 1}}}}\n{}\n",
+            output
+        );
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("0.2.0", information.fleet.protocol);
+        assert_eq!(
+            vec!["Ignored 1 line(s) of non-JSON output before the information"],
+            warnings
+        );
+    }
+
+    #[test]
+    fn decode_partial_test() {
+        let content = serde_json::json!({
+            "fleet": {"protocol": "0.2.0", "providerName": "fleet-typo3"},
+            "system": {
+                "platform": {"language": "php", "version": 8.1},
+                "application": {
+                    "name": "TYPO3",
+                    "version": "11.5.3",
+                    "installMode": null,
+                    "meta": {"debug": false, "context": "Production"}
+                }
+            }
+        })
+        .to_string();
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("fleet-typo3", information.fleet.provider_name);
+        assert_eq!("", information.fleet.provider_version);
+        assert_eq!("8.1", information.system.platform.version);
+        assert_eq!("", information.system.platform.os.vendor);
+        assert_eq!(None, information.system.application.install_mode);
+        assert_eq!("false", information.system.application.meta["debug"]);
+        assert!(information.packages.is_empty());
+        assert_eq!(
+            vec![
+                "Missing `fleet.providerVersion`, using the default",
+                "Missing `packages`, using the default",
+                "Missing `system.platform.host`, using the default",
+                "Missing `system.platform.os`, using the default",
+                "Missing `system.platform.sapi`, using the default",
+            ],
+            warnings
+        );
+    }
+
+    #[test]
+    fn decode_invalid_packages_test() {
+        let content = build_output(
+            "0.2.0",
+            serde_json::json!({
+                "all": {
+                    "core": {"version": "11.5.0", "state": "active"},
+                    "news": "9.0.0"
+                }
+            }),
+        );
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("core", information.packages["core"].key);
+        assert_eq!("", information.packages["core"].description);
+        assert_eq!("", information.packages["news"].version);
+        assert_eq!(
+            vec![
+                "Missing `packages.all.core.description`, using the default",
+                "Missing `packages.all.core.key`, using the default",
+                "Invalid `packages.all.news`, using the default",
+            ],
+            warnings
+        );
+    }
+
+    #[test]
+    fn decode_error_test() {
+        let message = |content: &str| {
+            let error = decode_information(content).unwrap_err();
+            assert_eq!("protocol", error.kind().name());
+            assert!(matches!(
+                error.kind(),
+                ErrorKind::Protocol { raw_output } if raw_output == content
+            ));
+            error.message().to_owned()
+        };
+
+        assert!(message("Welcome!").contains("Could not decode"));
+        assert!(message("{}").contains("does not define `fleet.protocol`"));
+        assert_eq!(
+            "Provider fleet-typo3 1.2.0 uses an unsupported protocol: \
+             Protocol 0.0.1 is not supported (supported protocols: 0.1.0, \
+             0.2.0)",
+            message(&build_output("0.0.1", serde_json::json!({"all": {}})))
+        );
+    }
+
+    #[test]
+    fn decode_newer_test() {
+        let content =
+            build_output("0.3.0", serde_json::json!({"all": {}, "new": 1}));
+        let (information, warnings) = decode(&content);
+
+        assert_eq!("0.3.0", information.fleet.protocol);
+        assert_eq!(
+            vec![
+                "Provider fleet-typo3 1.2.0 uses protocol 0.3.0, which is \
+                 newer than the supported protocol 0.2.0"
+            ],
+            warnings
+        );
+    }
+}
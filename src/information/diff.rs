@@ -0,0 +1,314 @@
+use super::{Information, InformationCollection, Package, Packages};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Change of a value between two points in time
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq + Clone> Change<T> {
+    /// Return the change if the values differ
+    fn between(from: &T, to: &T) -> Option<Self> {
+        if from == to {
+            None
+        } else {
+            Some(Change {
+                from: from.clone(),
+                to: to.clone(),
+            })
+        }
+    }
+}
+
+/// Change of a single package
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum PackageChange {
+    Added {
+        version: String,
+        state: String,
+    },
+    Removed {
+        version: String,
+        state: String,
+    },
+    Changed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        version: Option<Change<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        state: Option<Change<String>>,
+    },
+}
+
+/// Changes of a single host
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum HostDiff {
+    /// The host only exists in the newer information
+    Added,
+    /// The host only exists in the older information
+    Removed,
+    Changed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        application_version: Option<Change<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        os_version: Option<Change<String>>,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        packages: BTreeMap<String, PackageChange>,
+    },
+}
+
+/// Changes of all hosts (hosts without changes are omitted)
+pub type CollectionDiff = BTreeMap<String, HostDiff>;
+
+/// Compare two information collections
+pub fn diff_collections(
+    from: &InformationCollection,
+    to: &InformationCollection,
+) -> CollectionDiff {
+    let mut diff = CollectionDiff::new();
+    for (host, from_information) in from {
+        match to.get(host) {
+            Some(to_information) => {
+                if let Some(host_diff) =
+                    diff_information(from_information, to_information)
+                {
+                    diff.insert(host.clone(), host_diff);
+                }
+            }
+            None => {
+                diff.insert(host.clone(), HostDiff::Removed);
+            }
+        }
+    }
+    for host in to.keys().filter(|host| !from.contains_key(*host)) {
+        diff.insert(host.clone(), HostDiff::Added);
+    }
+
+    diff
+}
+
+/// Compare the information of a single host
+///
+/// `None` is returned if nothing changed
+pub fn diff_information(
+    from: &Information,
+    to: &Information,
+) -> Option<HostDiff> {
+    let application_version = Change::between(
+        &from.system.application.version,
+        &to.system.application.version,
+    );
+    let os_version = Change::between(
+        &from.system.platform.os.version,
+        &to.system.platform.os.version,
+    );
+    let packages = diff_packages(&from.packages, &to.packages);
+
+    if application_version.is_none()
+        && os_version.is_none()
+        && packages.is_empty()
+    {
+        None
+    } else {
+        Some(HostDiff::Changed {
+            application_version,
+            os_version,
+            packages,
+        })
+    }
+}
+
+fn diff_packages(
+    from: &Packages,
+    to: &Packages,
+) -> BTreeMap<String, PackageChange> {
+    let mut changes = BTreeMap::new();
+    for (key, from_package) in from.iter() {
+        match to.all.get(key) {
+            Some(to_package) => {
+                if let Some(change) = diff_package(from_package, to_package) {
+                    changes.insert(key.clone(), change);
+                }
+            }
+            None => {
+                changes.insert(
+                    key.clone(),
+                    PackageChange::Removed {
+                        version: from_package.version.clone(),
+                        state: from_package.state.clone(),
+                    },
+                );
+            }
+        }
+    }
+    for (key, to_package) in to.iter() {
+        if !from.all.contains_key(key) {
+            changes.insert(
+                key.clone(),
+                PackageChange::Added {
+                    version: to_package.version.clone(),
+                    state: to_package.state.clone(),
+                },
+            );
+        }
+    }
+
+    changes
+}
+
+fn diff_package(from: &Package, to: &Package) -> Option<PackageChange> {
+    let version = Change::between(&from.version, &to.version);
+    let state = Change::between(&from.state, &to.state);
+    if version.is_none() && state.is_none() {
+        None
+    } else {
+        Some(PackageChange::Changed { version, state })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn build_package(key: &str, version: &str, state: &str) -> Package {
+        Package {
+            key: key.to_owned(),
+            version: version.to_owned(),
+            description: "".to_owned(),
+            state: state.to_owned(),
+        }
+    }
+
+    fn build_information(
+        application_version: &str,
+        packages: Vec<Package>,
+    ) -> Information {
+        let mut information = Information::new_for_current_env();
+        information.system.application.version = application_version.to_owned();
+        information.packages = Packages::new_with_packages(
+            packages
+                .into_iter()
+                .map(|p| (p.key.clone(), p))
+                .collect::<HashMap<_, _>>(),
+        );
+
+        information
+    }
+
+    #[test]
+    fn diff_information_without_changes_test() {
+        let information = build_information(
+            "10.4.1",
+            vec![build_package("typo3/cms-core", "10.4.1", "active")],
+        );
+        assert_eq!(None, diff_information(&information, &information));
+    }
+
+    #[test]
+    fn diff_information_test() {
+        let from = build_information(
+            "10.4.1",
+            vec![
+                build_package("typo3/cms-core", "10.4.1", "active"),
+                build_package("news", "8.0.0", "active"),
+                build_package("removed", "1.0.0", "inactive"),
+            ],
+        );
+        let mut to = build_information(
+            "10.4.2",
+            vec![
+                build_package("typo3/cms-core", "10.4.2", "active"),
+                build_package("news", "8.0.0", "inactive"),
+                build_package("added", "2.0.0", "active"),
+            ],
+        );
+        to.system.platform.os.version = "6.1.0".to_owned();
+
+        let mut packages = BTreeMap::new();
+        packages.insert(
+            "typo3/cms-core".to_owned(),
+            PackageChange::Changed {
+                version: Some(Change {
+                    from: "10.4.1".to_owned(),
+                    to: "10.4.2".to_owned(),
+                }),
+                state: None,
+            },
+        );
+        packages.insert(
+            "news".to_owned(),
+            PackageChange::Changed {
+                version: None,
+                state: Some(Change {
+                    from: "active".to_owned(),
+                    to: "inactive".to_owned(),
+                }),
+            },
+        );
+        packages.insert(
+            "removed".to_owned(),
+            PackageChange::Removed {
+                version: "1.0.0".to_owned(),
+                state: "inactive".to_owned(),
+            },
+        );
+        packages.insert(
+            "added".to_owned(),
+            PackageChange::Added {
+                version: "2.0.0".to_owned(),
+                state: "active".to_owned(),
+            },
+        );
+
+        assert_eq!(
+            Some(HostDiff::Changed {
+                application_version: Some(Change {
+                    from: "10.4.1".to_owned(),
+                    to: "10.4.2".to_owned(),
+                }),
+                os_version: Some(Change {
+                    from: from.system.platform.os.version.clone(),
+                    to: "6.1.0".to_owned(),
+                }),
+                packages,
+            }),
+            diff_information(&from, &to)
+        );
+    }
+
+    #[test]
+    fn diff_collections_test() {
+        let information = build_information("1.0.0", vec![]);
+        let mut from = InformationCollection::new();
+        from.insert("unchanged".to_owned(), information.clone());
+        from.insert("removed".to_owned(), information.clone());
+        let mut to = InformationCollection::new();
+        to.insert("unchanged".to_owned(), information.clone());
+        to.insert("added".to_owned(), information);
+
+        let diff = diff_collections(&from, &to);
+        assert_eq!(2, diff.len());
+        assert_eq!(Some(&HostDiff::Removed), diff.get("removed"));
+        assert_eq!(Some(&HostDiff::Added), diff.get("added"));
+    }
+
+    #[test]
+    fn serialize_test() {
+        let change = PackageChange::Changed {
+            version: Some(Change {
+                from: "1.0".to_owned(),
+                to: "1.1".to_owned(),
+            }),
+            state: None,
+        };
+        assert_eq!(
+            r#"{"change":"changed","version":{"from":"1.0","to":"1.1"}}"#,
+            serde_json::to_string(&change).unwrap()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Additional key/value information reported by the provider
+///
+/// Facts are (nested) JSON values like disk usage, PHP extensions or database
+/// versions. They are addressed by paths of keys separated by dots (e.g.
+/// `database.version`), list items by their index (e.g. `disks.0.free`)
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Facts {
+    values: BTreeMap<String, Value>,
+}
+
+impl Facts {
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Set the value of the top-level key
+    pub fn insert<S: Into<String>>(&mut self, key: S, value: Value) {
+        self.values.insert(key.into(), value);
+    }
+
+    /// Return the value at the path
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        let mut segments = path.split('.');
+        let mut current = self.values.get(segments.next()?)?;
+        for segment in segments {
+            current = match current {
+                Value::Object(object) => object.get(segment)?,
+                Value::Array(items) => {
+                    items.get(segment.parse::<usize>().ok()?)?
+                }
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+
+    /// Return the value at the path as text (see [`Facts::format_value`])
+    pub fn get_text(&self, path: &str) -> Option<String> {
+        self.get(path).map(Facts::format_value)
+    }
+
+    /// Return the text of every value that is not an object by its path
+    pub fn flatten(&self) -> BTreeMap<String, String> {
+        let mut flattened = BTreeMap::new();
+        for (key, value) in &self.values {
+            flatten_value(key.clone(), value, &mut flattened);
+        }
+
+        flattened
+    }
+
+    /// Return the value as text
+    ///
+    /// Strings are returned without quotes and lists of scalar values are
+    /// separated by commas. Other lists are returned as JSON
+    pub fn format_value(value: &Value) -> String {
+        match value {
+            Value::String(text) => text.clone(),
+            Value::Null => String::new(),
+            Value::Array(items) if items.iter().all(is_scalar) => items
+                .iter()
+                .map(Facts::format_value)
+                .collect::<Vec<_>>()
+                .join(", "),
+            other => other.to_string(),
+        }
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+fn flatten_value(
+    path: String,
+    value: &Value,
+    flattened: &mut BTreeMap<String, String>,
+) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten_value(format!("{}.{}", path, key), value, flattened);
+            }
+        }
+        value => {
+            flattened.insert(path, Facts::format_value(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn build_facts() -> Facts {
+        serde_json::from_value(json!({
+            "php": {
+                "extensions": ["intl", "gd", "zip"],
+                "memory_limit": "512M",
+                "opcache": true
+            },
+            "database": {"vendor": "MariaDB", "version": "10.6.12"},
+            "disks": [{"mount": "/", "free": 12.5}],
+            "cron": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn get_test() {
+        let facts = build_facts();
+
+        assert_eq!(Some(&json!("MariaDB")), facts.get("database.vendor"));
+        assert_eq!(Some(&json!(12.5)), facts.get("disks.0.free"));
+        assert_eq!(Some(&json!("gd")), facts.get("php.extensions.1"));
+        assert_eq!(None, facts.get("php.extensions.3"));
+        assert_eq!(None, facts.get("php.memory_limit.value"));
+        assert_eq!(None, facts.get("disks.first"));
+        assert_eq!(None, facts.get("unknown"));
+    }
+
+    #[test]
+    fn get_text_test() {
+        let facts = build_facts();
+
+        assert_eq!(Some("512M".to_owned()), facts.get_text("php.memory_limit"));
+        assert_eq!(Some("true".to_owned()), facts.get_text("php.opcache"));
+        assert_eq!(
+            Some("intl, gd, zip".to_owned()),
+            facts.get_text("php.extensions")
+        );
+        assert_eq!(
+            Some(r#"[{"free":12.5,"mount":"/"}]"#.to_owned()),
+            facts.get_text("disks")
+        );
+        assert_eq!(Some("".to_owned()), facts.get_text("cron"));
+    }
+
+    #[test]
+    fn flatten_test() {
+        let flattened = build_facts().flatten();
+
+        assert_eq!(
+            vec![
+                "cron",
+                "database.vendor",
+                "database.version",
+                "disks",
+                "php.extensions",
+                "php.memory_limit",
+                "php.opcache",
+            ],
+            flattened.keys().collect::<Vec<_>>()
+        );
+        assert_eq!("10.6.12", flattened["database.version"]);
+    }
+
+    #[test]
+    fn serialize_test() {
+        let facts = build_facts();
+        let value = serde_json::to_value(&facts).unwrap();
+
+        assert_eq!(json!("512M"), value["php"]["memory_limit"]);
+        assert_eq!(facts, serde_json::from_value(value).unwrap());
+    }
+}
@@ -2,11 +2,14 @@ use super::*;
 use crate::constants;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Information {
     pub fleet: Fleet,
     pub system: System,
     pub packages: Packages,
+    /// Additional information reported by the provider
+    #[serde(default, skip_serializing_if = "Facts::is_empty")]
+    pub facts: Facts,
 }
 
 impl Information {
@@ -20,6 +23,7 @@ impl Information {
             ),
             packages: Packages::new_for_current_env(),
             system: System::new_for_current_env(),
+            facts: Facts::default(),
         }
     }
 
@@ -29,6 +33,7 @@ impl Information {
             fleet: self.fleet.clone(),
             system: self.system.clone(),
             packages: Packages::new(),
+            facts: self.facts.clone(),
         }
     }
 }
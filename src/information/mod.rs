@@ -1,15 +1,25 @@
 mod application;
 mod collection;
+mod decoder;
+mod diff;
+mod facts;
 mod fleet;
 #[allow(clippy::module_inception)]
 mod information;
+mod package_versions;
 mod packages;
 mod platform;
+mod protocol;
 mod system;
 
 pub use self::collection::*;
+pub use self::decoder::decode_information;
+pub use self::diff::*;
+pub use self::facts::Facts;
 pub use self::fleet::*;
 pub use self::information::Information;
+pub use self::package_versions::*;
 pub use self::packages::*;
 pub use self::platform::Platform;
+pub use self::protocol::*;
 pub use self::system::*;
@@ -0,0 +1,93 @@
+use super::InformationCollection;
+use std::collections::BTreeMap;
+
+/// Version of each package on each host (`None` if the package is missing on
+/// the host)
+pub type PackageVersions = BTreeMap<String, BTreeMap<String, Option<String>>>;
+
+/// Collect the versions of all packages of all hosts
+pub fn collect_package_versions(
+    information_collection: &InformationCollection,
+) -> PackageVersions {
+    let mut package_versions = PackageVersions::new();
+    for information in information_collection.values() {
+        for key in information.packages.all.keys() {
+            package_versions.entry(key.clone()).or_default();
+        }
+    }
+
+    for (key, versions) in package_versions.iter_mut() {
+        for (host, information) in information_collection {
+            versions.insert(
+                host.clone(),
+                information.packages.all.get(key).map(|p| p.version.clone()),
+            );
+        }
+    }
+
+    package_versions
+}
+
+/// Check if the values differ (e.g. the versions of a package on the hosts)
+pub fn has_drift<T: PartialEq, I: IntoIterator<Item = T>>(values: I) -> bool {
+    let mut values = values.into_iter();
+    match values.next() {
+        Some(first) => values.any(|value| value != first),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::information::{Information, Package, Packages};
+    use std::collections::HashMap;
+
+    fn build_information(packages: &[(&str, &str)]) -> Information {
+        let mut information = Information::new_for_current_env();
+        information.packages = Packages::new_with_packages(
+            packages
+                .iter()
+                .map(|(key, version)| {
+                    (
+                        key.to_string(),
+                        Package {
+                            key: key.to_string(),
+                            version: version.to_string(),
+                            description: "".to_owned(),
+                            state: "active".to_owned(),
+                        },
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+        );
+
+        information
+    }
+
+    #[test]
+    fn collect_package_versions_test() {
+        let mut collection = InformationCollection::new();
+        collection.insert(
+            "a".to_owned(),
+            build_information(&[("core", "10.4.1"), ("news", "8.0.0")]),
+        );
+        collection
+            .insert("b".to_owned(), build_information(&[("core", "10.4.2")]));
+
+        let versions = collect_package_versions(&collection);
+        assert_eq!(vec!["core", "news"], versions.keys().collect::<Vec<_>>());
+        assert_eq!(Some("10.4.1".to_owned()), versions["core"]["a"]);
+        assert_eq!(Some("10.4.2".to_owned()), versions["core"]["b"]);
+        assert_eq!(Some("8.0.0".to_owned()), versions["news"]["a"]);
+        assert_eq!(None, versions["news"]["b"]);
+    }
+
+    #[test]
+    fn has_drift_test() {
+        assert!(!has_drift(Vec::<&str>::new()));
+        assert!(!has_drift(vec!["1.0", "1.0"]));
+        assert!(has_drift(vec!["1.0", "1.1"]));
+        assert!(has_drift(vec![Some("1.0"), None]));
+    }
+}
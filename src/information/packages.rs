@@ -4,7 +4,7 @@ use std::collections::hash_map::Iter;
 use std::collections::HashMap;
 use std::ops::Index;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Package {
     pub key: String,
     pub version: String,
@@ -12,7 +12,6 @@ pub struct Package {
     pub state: String,
 }
 
-#[cfg(test)]
 impl Package {
     pub fn is_active(&self) -> bool {
         self.state == "active"
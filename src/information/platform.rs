@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fs;
 use std::process::Command;
 
 fn uname(key: &str) -> String {
@@ -12,7 +13,15 @@ fn uname(key: &str) -> String {
     String::from(stdout.trim())
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+/// Read the value from `/proc/sys/kernel` and fall back to `uname`
+fn kernel_value(name: &str, uname_key: &str) -> String {
+    match fs::read_to_string(format!("/proc/sys/kernel/{}", name)) {
+        Ok(value) if !value.trim().is_empty() => value.trim().to_owned(),
+        _ => uname(uname_key),
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct Os {
     pub vendor: String,
     pub version: String,
@@ -23,15 +32,15 @@ pub struct Os {
 impl Os {
     fn new_for_current_env() -> Self {
         Os {
-            vendor: uname("s"),
-            version: uname("r"),
-            machine: uname("m"),
-            info: uname("v"),
+            vendor: kernel_value("ostype", "s"),
+            version: kernel_value("osrelease", "r"),
+            machine: kernel_value("arch", "m"),
+            info: kernel_value("version", "v"),
         }
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct Platform {
     pub language: String,
     pub version: String,
@@ -44,9 +53,9 @@ impl Platform {
     pub fn new_for_current_env() -> Self {
         Platform {
             language: "rust".to_owned(),
-            version: "".to_owned(),
-            sapi: "".to_owned(),
-            host: uname("h"),
+            version: env!("FLEET_RUSTC_VERSION").to_owned(),
+            sapi: "cli".to_owned(),
+            host: kernel_value("hostname", "n"),
             os: Os::new_for_current_env(),
         }
     }
@@ -61,6 +70,10 @@ mod tests {
         let platform = Platform::new_for_current_env();
 
         assert_eq!("rust", platform.language);
-        assert_eq!("", platform.sapi);
+        assert_eq!("cli", platform.sapi);
+        assert!(!platform.version.is_empty());
+        assert!(!platform.host.is_empty());
+        assert!(!platform.os.vendor.is_empty());
+        assert_eq!(uname("m"), platform.os.machine);
     }
 }
@@ -0,0 +1,127 @@
+use crate::constants;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Relation between a provider's protocol and the protocols fleet supports
+///
+/// Protocols are compared by their major and minor version, patch versions
+/// are always compatible. A different major version is never compatible
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolCompatibility {
+    /// The provider speaks the current protocol
+    Current,
+    /// The provider speaks an older protocol that is migrated on decoding
+    Migrated,
+    /// The provider speaks a newer minor version of the protocol, that is
+    /// decoded on a best-effort basis
+    Newer,
+}
+
+impl ProtocolCompatibility {
+    /// Return the compatibility of the protocol or an error if it is not
+    /// supported
+    pub fn of(protocol: &str) -> Result<Self, String> {
+        let version = match parse_protocol(protocol) {
+            Some(version) => version,
+            None => return Err(format!("Invalid protocol '{}'", protocol)),
+        };
+        let current = parse_protocol(constants::PROTOCOL).unwrap_or_default();
+
+        if version == current {
+            Ok(ProtocolCompatibility::Current)
+        } else if version.0 > current.0 {
+            Err(format!(
+                "Protocol {} has a newer major version than the supported \
+                 protocol {}",
+                protocol,
+                constants::PROTOCOL
+            ))
+        } else if version > current {
+            Ok(ProtocolCompatibility::Newer)
+        } else if constants::SUPPORTED_PROTOCOLS
+            .iter()
+            .any(|p| parse_protocol(p) == Some(version))
+        {
+            Ok(ProtocolCompatibility::Migrated)
+        } else {
+            Err(format!(
+                "Protocol {} is not supported (supported protocols: {})",
+                protocol,
+                constants::SUPPORTED_PROTOCOLS.join(", ")
+            ))
+        }
+    }
+}
+
+/// Parse the major and minor version of the protocol
+fn parse_protocol(protocol: &str) -> Option<(u64, u64)> {
+    let mut parts = protocol.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some(patch) if patch.parse::<u64>().is_err() => None,
+        _ => Some((major, minor)),
+    }
+}
+
+/// Migrate protocol 0.1 output to protocol 0.2
+///
+/// Protocol 0.1 lists the packages in `active` and `inactive` in addition to
+/// `all` (which may be missing in older providers)
+pub(super) fn migrate_0_1_to_0_2(value: &mut Value) {
+    let packages =
+        match value.get_mut("packages").and_then(Value::as_object_mut) {
+            Some(packages) => packages,
+            None => return,
+        };
+
+    let mut all = match packages.remove("all") {
+        Some(Value::Object(all)) => all,
+        _ => Map::new(),
+    };
+    for state in ["active", "inactive"] {
+        let listed = match packages.remove(state) {
+            Some(Value::Object(listed)) => listed,
+            _ => continue,
+        };
+        for (key, mut package) in listed {
+            if let Some(package) = package.as_object_mut() {
+                package
+                    .entry("state")
+                    .or_insert_with(|| Value::String(state.to_owned()));
+            }
+            all.entry(key).or_insert(package);
+        }
+    }
+
+    packages.insert("all".to_owned(), Value::Object(all));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compatibility_test() {
+        use ProtocolCompatibility::*;
+
+        assert_eq!(Ok(Current), ProtocolCompatibility::of("0.2.0"));
+        assert_eq!(Ok(Current), ProtocolCompatibility::of("0.2.3"));
+        assert_eq!(Ok(Current), ProtocolCompatibility::of("0.2"));
+        assert_eq!(Ok(Migrated), ProtocolCompatibility::of("0.1.0"));
+        assert_eq!(Ok(Newer), ProtocolCompatibility::of("0.3.0"));
+        assert_eq!(Ok(Newer), ProtocolCompatibility::of("0.10.1"));
+        assert!(ProtocolCompatibility::of("1.0.0")
+            .unwrap_err()
+            .contains("newer major version"));
+        assert!(ProtocolCompatibility::of("1.2").is_err());
+        assert!(ProtocolCompatibility::of("0.0.1")
+            .unwrap_err()
+            .contains("not supported"));
+        assert!(ProtocolCompatibility::of("two")
+            .unwrap_err()
+            .contains("Invalid protocol"));
+        assert!(ProtocolCompatibility::of("0.2.x").is_err());
+    }
+}
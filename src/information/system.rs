@@ -2,7 +2,7 @@ use super::application::*;
 use super::platform::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq)]
 pub struct System {
     pub platform: Platform,
     pub application: Application,
@@ -1,3 +1,5 @@
+mod audit;
+mod cache;
 mod command;
 mod configuration;
 mod constants;
@@ -5,9 +7,12 @@ mod error;
 mod filter;
 mod formatter;
 mod information;
+mod outcome;
+mod policy;
 mod printer;
 mod provider;
 mod shell;
+mod snapshot;
 
 #[cfg(test)]
 mod test_helpers;
@@ -18,8 +23,10 @@ use crate::formatter::*;
 use crate::printer::*;
 use clap::{Parser, Subcommand};
 use error::Error;
+use outcome::Outcome;
 use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 /// Fleet Master
 #[derive(Parser)]
@@ -53,6 +60,7 @@ enum Commands {
     Provide(ProvideArgs),
 
     /// Search hosts for the given predicate
+    #[command(disable_version_flag = true)]
     Search(SearchArgs),
 
     /// Check configurations for all hosts
@@ -63,6 +71,21 @@ enum Commands {
 
     /// Execute a shell command on the hosts
     Exec(ExecArgs),
+
+    /// Fetch information from the hosts and store it in the cache
+    Refresh(RefreshArgs),
+
+    /// Save a snapshot of the information of the hosts
+    Snapshot(SnapshotArgs),
+
+    /// Show the changes between two snapshots (or a snapshot and now)
+    Diff(DiffArgs),
+
+    /// Check the packages of the hosts against an advisory database
+    Audit(AuditArgs),
+
+    /// Inspect the configuration
+    Config(ConfigArgs),
 }
 
 #[derive(Clone, Copy, Default, Debug, clap::ValueEnum)]
@@ -94,6 +117,11 @@ fn get_format(cli: &Cli) -> &str {
         Commands::Check(args) => &args.common.format,
         Commands::Update(args) => &args.common.format,
         Commands::Exec(args) => &args.common.format,
+        Commands::Refresh(args) => &args.common.format,
+        Commands::Snapshot(args) => &args.common.format,
+        Commands::Diff(args) => &args.common.format,
+        Commands::Audit(args) => &args.common.format,
+        Commands::Config(args) => &args.common().format,
         Commands::Provide(_) => return "json",
     }
     .as_ref()
@@ -113,17 +141,30 @@ fn get_configuration_file(cli: &Cli) -> Result<PathBuf, Error> {
         Commands::Check(args) => &args.common.config,
         Commands::Update(args) => &args.common.config,
         Commands::Exec(args) => &args.common.config,
+        Commands::Refresh(args) => &args.common.config,
+        Commands::Snapshot(args) => &args.common.config,
+        Commands::Diff(args) => &args.common.config,
+        Commands::Audit(args) => &args.common.config,
+        Commands::Config(args) => &args.common().config,
         Commands::Provide(_) => &None,
     }
     .as_ref()
     .map_or_else(detect_configuration_file, |p| Ok(p.clone()))
 }
 
-fn main() {
-    Printer::print_if_error(run());
+fn main() -> ExitCode {
+    match run() {
+        Ok(outcome) => outcome.exit_code(),
+        Err(e) => {
+            let outcome = Outcome::from_error(&e);
+            Printer::print_error(e);
+
+            outcome.exit_code()
+        }
+    }
 }
 
-fn run() -> Result<(), Error> {
+fn run() -> Result<Outcome, Error> {
     let cli = Cli::parse();
     let formatter = get_formatter(
         get_format(&cli),
@@ -162,6 +203,32 @@ fn run() -> Result<(), Error> {
         Commands::Exec(args) => {
             ExecCommand::default().exec(&formatter, config_file, args)
         }
+        Commands::Refresh(args) => {
+            RefreshCommand::default().exec(&formatter, config_file, args)
+        }
+        Commands::Snapshot(args) => {
+            SnapshotCommand::default().exec(&formatter, config_file, args)
+        }
+        Commands::Diff(args) => {
+            DiffCommand::default().exec(&formatter, config_file, args)
+        }
+        Commands::Audit(args) => {
+            AuditCommand::default().exec(&formatter, config_file, args)
+        }
+        Commands::Config(args) => {
+            ConfigCommand::default().exec(&formatter, config_file, args)
+        }
         Commands::Provide(_) => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn verify_cli_test() {
+        Cli::command().debug_assert();
+    }
+}
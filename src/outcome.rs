@@ -0,0 +1,119 @@
+use crate::error::{Error, ErrorKind};
+use std::process::ExitCode;
+
+/// Fleet-wide outcome of a command, reported through the process exit code
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    /// All hosts succeeded
+    Success,
+    /// The command could not be run
+    GeneralError,
+    /// Some of the hosts failed
+    PartialFailure,
+    /// All hosts failed
+    TotalFailure,
+    /// The configuration could not be loaded
+    ConfigError,
+    /// All hosts succeeded, but warnings were reported (only with
+    /// `--fail-on-any`)
+    Warnings,
+}
+
+impl Outcome {
+    /// Build the outcome from the number of succeeded and failed hosts
+    pub fn from_results(successes: usize, failures: usize) -> Self {
+        match (successes, failures) {
+            (_, 0) => Outcome::Success,
+            (0, _) => Outcome::TotalFailure,
+            _ => Outcome::PartialFailure,
+        }
+    }
+
+    /// Build the outcome for an error that aborted the command
+    pub fn from_error(error: &Error) -> Self {
+        match error.kind() {
+            ErrorKind::Config => Outcome::ConfigError,
+            ErrorKind::Other => Outcome::GeneralError,
+            _ => Outcome::TotalFailure,
+        }
+    }
+
+    /// Apply the `--fail-on-any` flag
+    ///
+    /// If `fail_on_any` is set, a successful run with any of the `warnings`
+    /// (e.g. host patterns that match no host or provider output that had to
+    /// be repaired) results in [`Outcome::Warnings`]. Otherwise warnings are
+    /// ignored. Failures are never changed
+    pub fn fail_on_any(self, fail_on_any: bool, warnings: usize) -> Self {
+        match self {
+            Outcome::Success if fail_on_any && warnings > 0 => {
+                Outcome::Warnings
+            }
+            _ => self,
+        }
+    }
+
+    /// Return the process exit code
+    ///
+    /// `2` is skipped, because it is used for invalid command line arguments
+    pub fn exit_code(self) -> ExitCode {
+        ExitCode::from(match self {
+            Outcome::Success => 0,
+            Outcome::GeneralError => 1,
+            Outcome::PartialFailure => 3,
+            Outcome::TotalFailure => 4,
+            Outcome::ConfigError => 5,
+            Outcome::Warnings => 6,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_results_test() {
+        assert_eq!(Outcome::Success, Outcome::from_results(0, 0));
+        assert_eq!(Outcome::Success, Outcome::from_results(3, 0));
+        assert_eq!(Outcome::PartialFailure, Outcome::from_results(3, 1));
+        assert_eq!(Outcome::TotalFailure, Outcome::from_results(0, 1));
+    }
+
+    #[test]
+    fn from_error_test() {
+        assert_eq!(
+            Outcome::ConfigError,
+            Outcome::from_error(&Error::config("invalid"))
+        );
+        assert_eq!(
+            Outcome::TotalFailure,
+            Outcome::from_error(&Error::timeout("timed out"))
+        );
+        assert_eq!(
+            Outcome::GeneralError,
+            Outcome::from_error(&Error::new("failed"))
+        );
+    }
+
+    #[test]
+    fn fail_on_any_test() {
+        assert_eq!(
+            Outcome::PartialFailure,
+            Outcome::PartialFailure.fail_on_any(true, 2)
+        );
+        assert_eq!(
+            Outcome::PartialFailure,
+            Outcome::PartialFailure.fail_on_any(false, 0)
+        );
+        assert_eq!(Outcome::Success, Outcome::Success.fail_on_any(true, 0));
+
+        // Warnings only fail the run with the flag
+        assert_eq!(Outcome::Success, Outcome::Success.fail_on_any(false, 2));
+        assert_eq!(Outcome::Warnings, Outcome::Success.fail_on_any(true, 2));
+        assert_eq!(
+            Outcome::ConfigError,
+            Outcome::ConfigError.fail_on_any(true, 2)
+        );
+    }
+}
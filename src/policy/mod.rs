@@ -0,0 +1,5 @@
+mod policy_file;
+mod rule;
+
+pub use self::policy_file::{Policy, PolicyReport};
+pub use self::rule::{RuleResult, RuleStatus};
@@ -0,0 +1,155 @@
+use super::rule::{Rule, RuleDefinition, RuleResult};
+use crate::error::Error;
+use crate::information::InformationCollection;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Results of each rule for each host (in the order of the rules)
+pub type PolicyReport = BTreeMap<String, Vec<RuleResult>>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    rules: Vec<RuleDefinition>,
+}
+
+/// List of rules the hosts must comply with
+///
+/// Policies are YAML or JSON files with a list of `rules`. Each rule has a
+/// `name`, a `severity` (`low`, `medium`, `high` or `critical`) and either
+///
+/// - a `check` comparing a field with a value (e.g. `application.version >=
+///   11.5` or `os.vendor == Linux`), or
+/// - a `package` that must be installed, optionally in the given `state`
+///   (`active` or `inactive`) and matching the `version` constraint (set
+///   `present: false` to forbid the package instead)
+#[derive(Debug)]
+pub struct Policy {
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    /// Load the policy from the YAML or JSON file
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let build_error = |e: String| {
+            Error::config(format!(
+                "Could not load the policy '{}': {}",
+                path.to_string_lossy(),
+                e
+            ))
+        };
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return Err(build_error(e.to_string())),
+        };
+        let file = parse(path, &content).map_err(build_error)?;
+
+        let mut rules = vec![];
+        for definition in file.rules {
+            rules.push(Rule::from_definition(definition)?);
+        }
+
+        Ok(Policy { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluate every rule for each host
+    pub fn evaluate(
+        &self,
+        information_collection: &InformationCollection,
+    ) -> PolicyReport {
+        information_collection
+            .iter()
+            .map(|(host, information)| {
+                // Fields are looked up in the serialized information
+                let value = serde_json::to_value(information)
+                    .expect("Information must be serializable");
+                let results = self
+                    .rules
+                    .iter()
+                    .map(|rule| rule.evaluate(information, &value))
+                    .collect();
+
+                (host.clone(), results)
+            })
+            .collect()
+    }
+}
+
+fn parse(path: &Path, content: &str) -> Result<PolicyFile, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => {
+            serde_json::from_str(content).map_err(|e| e.to_string())
+        }
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => {
+            serde_yaml::from_str(content).map_err(|e| e.to_string())
+        }
+        #[cfg(not(feature = "yaml"))]
+        Some("yaml" | "yml") => Err(
+            "YAML support is not enabled (build with the \"yaml\" feature)"
+                .to_owned(),
+        ),
+        _ => Err("Unsupported file format (expected JSON or YAML)".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "yaml")]
+    use crate::information::Information;
+    #[cfg(feature = "yaml")]
+    use crate::policy::RuleStatus;
+    use crate::test_helpers::get_test_resource_path;
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn load_and_evaluate_test() {
+        let policy =
+            Policy::load(&get_test_resource_path("policy.yaml")).unwrap();
+        assert_eq!(4, policy.rules.len());
+
+        let mut information = Information::new_for_current_env();
+        information.system.application.version = "11.5.3".to_owned();
+        information.system.platform.os.vendor = "Linux".to_owned();
+        let collection =
+            InformationCollection::from([("web".to_owned(), information)]);
+
+        let report = policy.evaluate(&collection);
+        let statuses: Vec<_> = report["web"]
+            .iter()
+            .map(|r| (r.rule.as_str(), r.status))
+            .collect();
+        assert_eq!(
+            vec![
+                ("TYPO3 11.5 or newer", RuleStatus::Pass),
+                ("Scheduler is installed and active", RuleStatus::Fail),
+                ("Runs on Linux", RuleStatus::Pass),
+                ("Current provider protocol", RuleStatus::Pass),
+            ],
+            statuses
+        );
+    }
+
+    #[test]
+    fn load_invalid_test() {
+        let error = Policy::load(&get_test_resource_path("missing.json"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Could not load the policy"));
+
+        let error = Policy::load(&get_test_resource_path(
+            "advisories/osv/GHSA-xxxx-typo3-core.json",
+        ))
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("unknown field"));
+    }
+}
@@ -0,0 +1,552 @@
+use crate::audit::Severity;
+use crate::error::Error;
+use crate::filter::{PackageState, Version, VersionConstraint};
+use crate::information::Information;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Outcome of a rule for a single host
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleStatus {
+    Pass,
+    Fail,
+}
+
+/// Result of evaluating a rule for a single host
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleResult {
+    pub rule: String,
+    pub severity: Severity,
+    pub status: RuleStatus,
+    /// Description of the found value
+    pub message: String,
+}
+
+impl RuleResult {
+    pub fn passed(&self) -> bool {
+        self.status == RuleStatus::Pass
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Matches,
+}
+
+impl Operator {
+    /// Operators in the order they have to be tried when parsing
+    const ALL: [(&'static str, Operator); 7] = [
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("=~", Operator::Matches),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ];
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = Operator::ALL
+            .iter()
+            .find(|(_, operator)| operator == self)
+            .map_or("", |(symbol, _)| symbol);
+
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    /// Compare a field of the information with a value
+    Compare {
+        field: String,
+        operator: Operator,
+        value: String,
+        regex: Option<Regex>,
+    },
+    /// Require a package to be installed (or not)
+    Package {
+        key: String,
+        present: bool,
+        state: PackageState,
+        version: Option<VersionConstraint>,
+    },
+}
+
+/// Rule of a policy
+#[derive(Debug, Clone)]
+pub struct Rule {
+    name: String,
+    severity: Severity,
+    condition: Condition,
+}
+
+/// Rule as defined in the policy file
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDefinition {
+    name: String,
+    severity: String,
+    /// Comparison like `application.version >= 11.5`
+    check: Option<String>,
+    /// Key of a package that must be installed
+    package: Option<String>,
+    /// Set to `false` to require the package not to be installed
+    present: Option<bool>,
+    state: Option<PackageState>,
+    version: Option<String>,
+}
+
+impl Rule {
+    /// Build the rule from its definition in the policy file
+    pub fn from_definition(definition: RuleDefinition) -> Result<Self, Error> {
+        let name = definition.name;
+        let build_error = |reason: String| {
+            Error::config(format!("Invalid rule \"{}\": {}", name, reason))
+        };
+
+        let severity = match Severity::parse(&definition.severity) {
+            Severity::Unknown => {
+                return Err(build_error(format!(
+                    "unknown severity '{}' (expected low, medium, high or \
+                     critical)",
+                    definition.severity
+                )))
+            }
+            severity => severity,
+        };
+
+        let condition = match (definition.check, definition.package) {
+            (Some(check), None) => {
+                if definition.present.is_some()
+                    || definition.state.is_some()
+                    || definition.version.is_some()
+                {
+                    return Err(build_error(
+                        "`present`, `state` and `version` require `package`"
+                            .to_owned(),
+                    ));
+                }
+                parse_check(&check).map_err(build_error)?
+            }
+            (None, Some(key)) => Condition::Package {
+                key,
+                present: definition.present.unwrap_or(true),
+                state: definition.state.unwrap_or_default(),
+                version: match definition.version {
+                    Some(version) => Some(
+                        VersionConstraint::parse(&version)
+                            .map_err(|e| build_error(e.to_string()))?,
+                    ),
+                    None => None,
+                },
+            },
+            _ => {
+                return Err(build_error(
+                    "exactly one of `check` or `package` must be given"
+                        .to_owned(),
+                ))
+            }
+        };
+
+        Ok(Rule {
+            name,
+            severity,
+            condition,
+        })
+    }
+
+    /// Evaluate the rule for the information of a host
+    ///
+    /// `serialized` is the information as JSON value, which is shared by the
+    /// rules of the host
+    pub fn evaluate(
+        &self,
+        information: &Information,
+        serialized: &serde_json::Value,
+    ) -> RuleResult {
+        let (passed, message) = match &self.condition {
+            Condition::Compare {
+                field,
+                operator,
+                value,
+                regex,
+            } => evaluate_comparison(
+                serialized,
+                field,
+                *operator,
+                value,
+                regex.as_ref(),
+            ),
+            Condition::Package {
+                key,
+                present,
+                state,
+                version,
+            } => evaluate_package(
+                information,
+                key,
+                *present,
+                *state,
+                version.as_ref(),
+            ),
+        };
+
+        RuleResult {
+            rule: self.name.clone(),
+            severity: self.severity,
+            status: if passed {
+                RuleStatus::Pass
+            } else {
+                RuleStatus::Fail
+            },
+            message,
+        }
+    }
+}
+
+/// Parse a comparison like `application.version >= 11.5`
+fn parse_check(check: &str) -> Result<Condition, String> {
+    let (position, symbol, operator) = Operator::ALL
+        .iter()
+        .filter_map(|(symbol, operator)| {
+            check.find(symbol).map(|p| (p, *symbol, *operator))
+        })
+        // Prefer the first operator, and the longer one at the same position
+        .min_by_key(|(position, symbol, _)| {
+            (*position, usize::MAX - symbol.len())
+        })
+        .ok_or_else(|| format!("missing operator in check '{}'", check))?;
+
+    let field = check[..position].trim();
+    let value = check[position + symbol.len()..].trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value);
+    if field.is_empty() || value.is_empty() {
+        return Err(format!("incomplete check '{}'", check));
+    }
+
+    let regex = if operator == Operator::Matches {
+        match Regex::new(value) {
+            Ok(regex) => Some(regex),
+            Err(e) => return Err(format!("invalid regular expression: {}", e)),
+        }
+    } else {
+        None
+    };
+
+    Ok(Condition::Compare {
+        field: field.to_owned(),
+        operator,
+        value: value.to_owned(),
+        regex,
+    })
+}
+
+fn evaluate_comparison(
+    serialized: &serde_json::Value,
+    field: &str,
+    operator: Operator,
+    expected: &str,
+    regex: Option<&Regex>,
+) -> (bool, String) {
+    let actual = match lookup_field(serialized, field) {
+        Ok(actual) => actual,
+        Err(message) => return (false, message),
+    };
+    let message = format!("{} is '{}'", field, actual);
+
+    let passed = match operator {
+        Operator::Matches => regex.is_some_and(|r| r.is_match(&actual)),
+        Operator::Equal => compare(&actual, expected) == Some(Ordering::Equal),
+        Operator::NotEqual => {
+            compare(&actual, expected) != Some(Ordering::Equal)
+        }
+        _ => match (Version::parse(&actual), Version::parse(expected)) {
+            (Some(actual), Some(expected)) => {
+                let ordering = actual.cmp(&expected);
+                match operator {
+                    Operator::Less => ordering == Ordering::Less,
+                    Operator::LessOrEqual => ordering != Ordering::Greater,
+                    Operator::Greater => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                }
+            }
+            _ => {
+                return (
+                    false,
+                    format!(
+                        "{} can not be compared with '{}' using {}",
+                        message, expected, operator
+                    ),
+                )
+            }
+        },
+    };
+
+    (passed, message)
+}
+
+/// Compare the values as versions if possible, as text otherwise
+fn compare(actual: &str, expected: &str) -> Option<Ordering> {
+    match (Version::parse(actual), Version::parse(expected)) {
+        (Some(actual), Some(expected)) => Some(actual.cmp(&expected)),
+        _ if actual == expected => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn evaluate_package(
+    information: &Information,
+    key: &str,
+    present: bool,
+    state: PackageState,
+    version: Option<&VersionConstraint>,
+) -> (bool, String) {
+    let package = match information.packages.all.get(key) {
+        Some(package) => package,
+        None => return (!present, format!("package {} is not installed", key)),
+    };
+
+    let message = format!(
+        "package {} {} is installed ({})",
+        key, package.version, package.state
+    );
+    let matches = state.matches(package)
+        && version.is_none_or(|v| v.matches(&package.version));
+
+    (matches == present, message)
+}
+
+/// Return the value of the field in the serialized information as text
+///
+/// Fields are paths into the information (e.g. `system.platform.os.vendor`).
+/// The shortcuts `application.`, `platform.`, `os.` and `provider.` are
+/// supported as well
+fn lookup_field(
+    serialized: &serde_json::Value,
+    field: &str,
+) -> Result<String, String> {
+    let mut segments: Vec<&str> = field.split('.').collect();
+    let prefix: &[&str] = match segments[0] {
+        "application" => &["system", "application"],
+        "platform" => &["system", "platform"],
+        "os" => &["system", "platform", "os"],
+        "provider" | "fleet" => {
+            if let Some(segment) = segments.get_mut(1) {
+                *segment = match *segment {
+                    "version" => "providerVersion",
+                    "name" => "providerName",
+                    other => other,
+                };
+            }
+            &["fleet"]
+        }
+        _ => &[],
+    };
+    let path = prefix
+        .iter()
+        .chain(segments.iter().skip(usize::from(!prefix.is_empty())));
+
+    let mut current = serialized;
+    for segment in path {
+        current = match current
+            .get(*segment)
+            .or_else(|| current.get(snake_to_camel_case(segment)))
+        {
+            Some(value) => value,
+            None => return Err(format!("field {} does not exist", field)),
+        };
+    }
+
+    match current {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Null => Err(format!("field {} is not set", field)),
+        serde_json::Value::Object(_) | serde_json::Value::Array(_) => {
+            Err(format!("field {} is not a single value", field))
+        }
+        other => Ok(other.to_string()),
+    }
+}
+
+fn snake_to_camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::information::{Package, Packages};
+    use std::collections::HashMap;
+
+    fn build_information() -> Information {
+        let mut information = Information::new_for_current_env();
+        information.fleet.protocol = "0.2.0".to_owned();
+        information.system.application.version = "11.5.3".to_owned();
+        information.system.application.install_mode = Some("composer".into());
+        information.system.platform.os.vendor = "Linux".to_owned();
+        information.packages = Packages::new_with_packages(HashMap::from([(
+            "scheduler".to_owned(),
+            Package {
+                key: "scheduler".to_owned(),
+                version: "11.5.3".to_owned(),
+                description: "".to_owned(),
+                state: "inactive".to_owned(),
+            },
+        )]));
+
+        information
+    }
+
+    fn build_rule(definition: serde_json::Value) -> Result<Rule, Error> {
+        Rule::from_definition(serde_json::from_value(definition).unwrap())
+    }
+
+    fn evaluate(rule: Rule) -> RuleResult {
+        let information = build_information();
+        let value = serde_json::to_value(&information).unwrap();
+
+        rule.evaluate(&information, &value)
+    }
+
+    fn check(check: &str) -> RuleResult {
+        evaluate(
+            build_rule(serde_json::json!({
+                "name": "rule",
+                "severity": "high",
+                "check": check,
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn check_test() {
+        assert!(check("application.version >= 11.5").passed());
+        assert!(!check("application.version < 11.5").passed());
+        assert!(check("application.version == 11.5.3").passed());
+        assert!(check("application.version != 11.5").passed());
+        assert!(check("os.vendor == Linux").passed());
+        assert!(check("os.vendor == 'Linux'").passed());
+        assert!(!check("os.vendor != Linux").passed());
+        assert!(check("os.vendor =~ ^Lin").passed());
+        assert!(check("provider.protocol == 0.2.0").passed());
+        assert!(check("fleet.protocol>=0.2").passed());
+        assert!(check("application.install_mode == composer").passed());
+        assert!(check("system.platform.os.vendor == Linux").passed());
+    }
+
+    #[test]
+    fn check_failure_message_test() {
+        let result = check("application.version >= 12");
+        assert_eq!(RuleStatus::Fail, result.status);
+        assert_eq!("application.version is '11.5.3'", result.message);
+
+        let result = check("os.vendor > Linux");
+        assert!(!result.passed());
+        assert!(result.message.contains("can not be compared"));
+
+        let result = check("os.unknown == Linux");
+        assert!(!result.passed());
+        assert_eq!("field os.unknown does not exist", result.message);
+
+        let result = check("application == fleet");
+        assert!(!result.passed());
+        assert_eq!("field application is not a single value", result.message);
+    }
+
+    #[test]
+    fn package_test() {
+        let evaluate = |definition: serde_json::Value| {
+            evaluate(build_rule(definition).unwrap())
+        };
+
+        let base = serde_json::json!({"name": "r", "severity": "low"});
+        let with = |extra: serde_json::Value| {
+            let mut definition = base.clone();
+            definition
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            definition
+        };
+
+        assert!(evaluate(with(serde_json::json!({"package": "scheduler"})))
+            .passed());
+        assert!(!evaluate(with(
+            serde_json::json!({"package": "scheduler", "state": "active"})
+        ))
+        .passed());
+        assert!(evaluate(with(
+            serde_json::json!({"package": "scheduler", "version": ">=11.5"})
+        ))
+        .passed());
+        assert!(
+            !evaluate(with(serde_json::json!({"package": "news"}))).passed()
+        );
+        assert!(evaluate(with(
+            serde_json::json!({"package": "news", "present": false})
+        ))
+        .passed());
+        assert!(!evaluate(with(
+            serde_json::json!({"package": "scheduler", "present": false})
+        ))
+        .passed());
+    }
+
+    #[test]
+    fn invalid_rule_test() {
+        let rule = |definition| build_rule(definition).unwrap_err().to_string();
+
+        assert!(rule(serde_json::json!({
+            "name": "r", "severity": "urgent", "check": "os.vendor == Linux"
+        }))
+        .contains("unknown severity"));
+        assert!(rule(serde_json::json!({"name": "r", "severity": "low"}))
+            .contains("exactly one"));
+        assert!(rule(serde_json::json!({
+            "name": "r", "severity": "low", "check": "os.vendor Linux"
+        }))
+        .contains("missing operator"));
+        assert!(rule(serde_json::json!({
+            "name": "r", "severity": "low", "check": "os.vendor == "
+        }))
+        .contains("incomplete"));
+        assert!(rule(serde_json::json!({
+            "name": "r", "severity": "low", "check": "os.vendor =~ ("
+        }))
+        .contains("regular expression"));
+        assert!(rule(serde_json::json!({
+            "name": "r", "severity": "low", "check": "os.vendor == Linux",
+            "state": "active"
+        }))
+        .contains("require `package`"));
+    }
+}
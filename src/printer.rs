@@ -1,5 +1,6 @@
 use crate::error::Error;
 use crate::error::ErrorCollection;
+use crate::error::ErrorKind;
 use ansi_term::Colour;
 
 pub struct Printer;
@@ -17,14 +18,18 @@ impl Printer {
 
     /// Prints an error's message to STDERR painted in red
     pub fn print_error(e: Error) {
-        eprintln!("{}", Colour::Red.paint(format!("error: {}", e.message())));
+        eprintln!(
+            "{}",
+            Colour::Red.paint(format!("{}: {}", error_label(&e), e.message()))
+        );
     }
 
-    /// Prints an error if the result is not ok. The Ok part of the result is ignored
-    pub fn print_if_error<A>(e: Result<A, Error>) {
-        if let Err(e) = e {
-            Self::print_error(e);
-        }
+    /// Prints a warning to STDERR painted in yellow
+    pub fn print_warning<S: AsRef<str>>(message: S) {
+        eprintln!(
+            "{}",
+            Colour::Yellow.paint(format!("warning: {}", message.as_ref()))
+        );
     }
 
     /// Prints all errors in the given collection
@@ -34,8 +39,9 @@ impl Printer {
             eprintln!(
                 "{}",
                 Colour::Red.paint(format!(
-                    "Error for host \"{}\": {}",
+                    "Error for host \"{}\" ({}): {}",
                     host,
+                    e.kind().name(),
                     prepare_message(e.message())
                 ))
             );
@@ -48,7 +54,8 @@ impl Printer {
         eprintln!(
             "{}",
             Colour::Red.paint(format!(
-                "error: {}: {}",
+                "{}: {}: {}",
+                error_label(&e),
                 message,
                 prepare_message(e.message())
             ))
@@ -56,6 +63,14 @@ impl Printer {
     }
 }
 
+/// Return the label for the error, including the kind if it is known
+fn error_label(e: &Error) -> String {
+    match e.kind() {
+        ErrorKind::Other => "error".to_owned(),
+        kind => format!("error[{}]", kind.name()),
+    }
+}
+
 pub fn prepare_message(message: &str) -> String {
     let single_line = message.replace('\n', " ");
     if single_line.chars().count() > 120 {
@@ -1,7 +1,6 @@
 use crate::error::Error;
 use crate::information::*;
 use std::fs;
-use std::fs::File;
 use std::path::PathBuf;
 
 pub struct FileProvider;
@@ -17,17 +16,12 @@ impl FileProvider {
             Err(e) => return Err(Error::from_error(&e)),
         };
 
-        let file = match File::open(absolute_file_path) {
-            Ok(file) => file,
+        let content = match fs::read_to_string(absolute_file_path) {
+            Ok(content) => content,
             Err(e) => return Err(Error::from_error(&e)),
         };
 
-        let information: Information = match serde_json::from_reader(file) {
-            Ok(information) => information,
-            Err(e) => return Err(Error::from_error(&e)),
-        };
-
-        Ok(information)
+        decode_information(&content).map(|(information, _)| information)
     }
 }
 
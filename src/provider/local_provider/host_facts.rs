@@ -0,0 +1,368 @@
+use crate::information::Facts;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fs;
+
+/// File systems that do not store data on a disk
+const VIRTUAL_FILESYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "squashfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+/// Collect the facts about the current host
+///
+/// Facts that are not available on the system (e.g. without `/proc`) are
+/// omitted
+pub fn collect() -> Facts {
+    let mut facts = Facts::default();
+
+    let os_release =
+        read("/etc/os-release").or_else(|| read("/usr/lib/os-release"));
+    if let Some(distribution) = os_release.as_deref().and_then(parse_os_release)
+    {
+        facts.insert("distribution", distribution);
+    }
+    if let Some(uptime) = read("/proc/uptime").as_deref().and_then(parse_uptime)
+    {
+        facts.insert("uptime", json!(uptime));
+    }
+    facts.insert("cpu", collect_cpu());
+    if let Some(memory) =
+        read("/proc/meminfo").as_deref().and_then(parse_meminfo)
+    {
+        facts.insert("memory", memory);
+    }
+    if let Some(mounts) = read("/proc/mounts") {
+        let disks = collect_disks(&parse_mounts(&mounts));
+        if !disks.is_empty() {
+            facts.insert("disks", Value::Object(disks));
+        }
+    }
+
+    facts
+}
+
+fn read(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Parse the distribution from the `os-release` file
+fn parse_os_release(content: &str) -> Option<Value> {
+    let values: BTreeMap<&str, String> = content
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .filter(|(key, _)| !key.starts_with('#'))
+        .map(|(key, value)| {
+            let value = value.trim();
+            let unquoted = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| {
+                    value.strip_prefix('\'').and_then(|v| v.strip_suffix('\''))
+                })
+                .unwrap_or(value);
+
+            (key.trim(), unquoted.replace("\\\"", "\""))
+        })
+        .collect();
+
+    let mut distribution = Map::new();
+    for (key, name) in [
+        ("ID", "id"),
+        ("NAME", "name"),
+        ("VERSION_ID", "version"),
+        ("VERSION_CODENAME", "codename"),
+        ("PRETTY_NAME", "pretty_name"),
+    ] {
+        if let Some(value) = values.get(key) {
+            distribution.insert(name.to_owned(), Value::String(value.clone()));
+        }
+    }
+
+    if distribution.is_empty() {
+        None
+    } else {
+        Some(Value::Object(distribution))
+    }
+}
+
+/// Parse the uptime in seconds from `/proc/uptime`
+fn parse_uptime(content: &str) -> Option<u64> {
+    let seconds: f64 = content.split_whitespace().next()?.parse().ok()?;
+
+    Some(seconds as u64)
+}
+
+fn collect_cpu() -> Value {
+    let (count, model) = match read("/proc/cpuinfo") {
+        Some(content) => parse_cpuinfo(&content),
+        None => (0, None),
+    };
+    let count = if count > 0 {
+        count
+    } else {
+        std::thread::available_parallelism().map_or(0, |count| count.get())
+    };
+
+    let mut cpu = Map::new();
+    cpu.insert("count".to_owned(), json!(count));
+    if let Some(model) = model {
+        cpu.insert("model".to_owned(), Value::String(model));
+    }
+
+    Value::Object(cpu)
+}
+
+/// Parse the number of processors and the model name from `/proc/cpuinfo`
+fn parse_cpuinfo(content: &str) -> (usize, Option<String>) {
+    let mut count = 0;
+    let mut model = None;
+    for (key, value) in content.lines().filter_map(|line| line.split_once(':'))
+    {
+        match key.trim() {
+            "processor" => count += 1,
+            "model name" if model.is_none() => {
+                model = Some(value.trim().to_owned())
+            }
+            _ => {}
+        }
+    }
+
+    (count, model)
+}
+
+/// Parse the memory and swap sizes (in bytes) from `/proc/meminfo`
+fn parse_meminfo(content: &str) -> Option<Value> {
+    let values: BTreeMap<&str, u64> = content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let kilobytes =
+                value.split_whitespace().next()?.parse::<u64>().ok()?;
+
+            Some((key.trim(), kilobytes * 1024))
+        })
+        .collect();
+
+    Some(json!({
+        "total": values.get("MemTotal")?,
+        "available": values.get("MemAvailable").or(values.get("MemFree"))?,
+        "swap_total": values.get("SwapTotal").copied().unwrap_or(0),
+        "swap_free": values.get("SwapFree").copied().unwrap_or(0),
+    }))
+}
+
+/// Mounted file system
+#[derive(Debug, PartialEq, Eq)]
+struct Mount {
+    device: String,
+    mount_point: String,
+    filesystem: String,
+}
+
+/// Parse the mounted disk file systems from `/proc/mounts`
+fn parse_mounts(content: &str) -> Vec<Mount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mount_point = fields.next()?;
+            let filesystem = fields.next()?;
+            if VIRTUAL_FILESYSTEMS.contains(&filesystem) {
+                return None;
+            }
+
+            Some(Mount {
+                device: unescape_mount_field(device),
+                mount_point: unescape_mount_field(mount_point),
+                filesystem: filesystem.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Replace the octal escapes of `/proc/mounts` (e.g. `\040` for a space)
+fn unescape_mount_field(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(position) = rest.find('\\') {
+        result.push_str(&rest[..position]);
+        let escape = rest.get(position + 1..position + 4);
+        match escape.and_then(|e| u8::from_str_radix(e, 8).ok()) {
+            Some(byte) => {
+                result.push(byte as char);
+                rest = &rest[position + 4..];
+            }
+            None => {
+                result.push('\\');
+                rest = &rest[position + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Return the disk usage of each mount (in bytes) by its mount point
+fn collect_disks(mounts: &[Mount]) -> Map<String, Value> {
+    let mut disks = Map::new();
+    for mount in mounts {
+        let (total, available, free) = match disk_usage(&mount.mount_point) {
+            Some(usage) if usage.0 > 0 => usage,
+            _ => continue,
+        };
+
+        disks.insert(
+            mount.mount_point.clone(),
+            json!({
+                "device": mount.device,
+                "filesystem": mount.filesystem,
+                "total": total,
+                "used": total - free,
+                "available": available,
+            }),
+        );
+    }
+
+    disks
+}
+
+/// Return the total, available and free bytes of the file system
+fn disk_usage(mount_point: &str) -> Option<(u64, u64, u64)> {
+    let path = CString::new(mount_point).ok()?;
+    // SAFETY: `statvfs` only writes to the zero-initialized struct
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let block_size = stat.f_frsize as u64;
+    Some((
+        stat.f_blocks as u64 * block_size,
+        stat.f_bavail as u64 * block_size,
+        stat.f_bfree as u64 * block_size,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_os_release_test() {
+        let content = r#"
+PRETTY_NAME="Debian GNU/Linux 12 (bookworm)"
+NAME="Debian GNU/Linux"
+VERSION_ID="12"
+VERSION="12 (bookworm)"
+VERSION_CODENAME=bookworm
+ID=debian
+# HOME_URL="https://www.debian.org/"
+"#;
+
+        assert_eq!(
+            Some(json!({
+                "id": "debian",
+                "name": "Debian GNU/Linux",
+                "version": "12",
+                "codename": "bookworm",
+                "pretty_name": "Debian GNU/Linux 12 (bookworm)",
+            })),
+            parse_os_release(content)
+        );
+        assert_eq!(None, parse_os_release("# empty"));
+    }
+
+    #[test]
+    fn parse_uptime_test() {
+        assert_eq!(Some(350735), parse_uptime("350735.47 234388.90\n"));
+        assert_eq!(None, parse_uptime(""));
+    }
+
+    #[test]
+    fn parse_cpuinfo_test() {
+        let content = "processor\t: 0\nmodel name\t: AMD EPYC 7B13\n\n\
+                       processor\t: 1\nmodel name\t: AMD EPYC 7B13\n";
+
+        assert_eq!(
+            (2, Some("AMD EPYC 7B13".to_owned())),
+            parse_cpuinfo(content)
+        );
+        assert_eq!((0, None), parse_cpuinfo(""));
+    }
+
+    #[test]
+    fn parse_meminfo_test() {
+        let content = "MemTotal:       16314248 kB\nMemFree:         \
+                       1226044 kB\nMemAvailable:   11329812 kB\n\
+                       SwapTotal:             0 kB\n";
+
+        assert_eq!(
+            Some(json!({
+                "total": 16314248u64 * 1024,
+                "available": 11329812u64 * 1024,
+                "swap_total": 0,
+                "swap_free": 0,
+            })),
+            parse_meminfo(content)
+        );
+        assert_eq!(None, parse_meminfo("SwapTotal: 0 kB\n"));
+    }
+
+    #[test]
+    fn parse_mounts_test() {
+        let content = "/dev/sda1 / ext4 rw,relatime 0 0\n\
+                       proc /proc proc rw,nosuid 0 0\n\
+                       tmpfs /run tmpfs rw,nosuid 0 0\n\
+                       /dev/sdb1 /mnt/my\\040disk xfs rw 0 0\n";
+
+        assert_eq!(
+            vec![
+                Mount {
+                    device: "/dev/sda1".to_owned(),
+                    mount_point: "/".to_owned(),
+                    filesystem: "ext4".to_owned(),
+                },
+                Mount {
+                    device: "/dev/sdb1".to_owned(),
+                    mount_point: "/mnt/my disk".to_owned(),
+                    filesystem: "xfs".to_owned(),
+                },
+            ],
+            parse_mounts(content)
+        );
+    }
+
+    #[test]
+    fn disk_usage_test() {
+        let (total, available, free) = disk_usage("/").unwrap();
+
+        assert!(total > 0);
+        assert!(available <= free && free <= total);
+        assert_eq!(None, disk_usage("/not/a/mount/point"));
+    }
+}
@@ -1,11 +1,23 @@
+mod host_facts;
+mod system_packages;
+
 use crate::error::Error;
 use crate::information::*;
 
 pub struct LocalProvider;
 
 impl LocalProvider {
+    /// Collect the information about the current host
+    ///
+    /// Besides the platform, the distribution, uptime, CPU, memory and disk
+    /// usage are reported as facts. The packages are the system packages from
+    /// the dpkg status file or the rpm database
     pub fn get_information(self) -> Result<Information, Error> {
-        Ok(Information::new_for_current_env())
+        let mut information = Information::new_for_current_env();
+        information.packages = system_packages::collect();
+        information.facts = host_facts::collect();
+
+        Ok(information)
     }
 }
 
@@ -37,6 +49,8 @@ mod tests {
             constants::PROVIDER_NAME,
             test_information.fleet.provider_name
         );
-        assert_eq!(test_information, provider_information);
+        assert_eq!(test_information.fleet, provider_information.fleet);
+        assert_eq!(test_information.system, provider_information.system);
+        assert!(provider_information.facts.get("cpu.count").is_some());
     }
 }
@@ -0,0 +1,206 @@
+use crate::information::{Package, Packages};
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+
+/// Status file of the Debian package manager
+const DPKG_STATUS_FILE: &str = "/var/lib/dpkg/status";
+
+/// Query format for `rpm -qa` (name, version, architecture and summary)
+const RPM_QUERY_FORMAT: &str =
+    "%{NAME}\\t%{VERSION}-%{RELEASE}\\t%{ARCH}\\t%{SUMMARY}\\n";
+
+/// Collect the installed system packages
+///
+/// The packages are read from the dpkg status file or the rpm database. If
+/// neither is available, no packages are returned
+pub fn collect() -> Packages {
+    if let Ok(content) = fs::read_to_string(DPKG_STATUS_FILE) {
+        return parse_dpkg_status(&content);
+    }
+
+    match Command::new("rpm")
+        .args(["-qa", "--queryformat", RPM_QUERY_FORMAT])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            parse_rpm_output(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Packages::new(),
+    }
+}
+
+/// Parse the packages of the dpkg status file
+///
+/// Installed packages are `active`, other packages have the dpkg state (e.g.
+/// `config-files` for removed packages with remaining configuration files)
+fn parse_dpkg_status(content: &str) -> Packages {
+    let mut packages = HashMap::new();
+    for stanza in content.split("\n\n") {
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+        for line in stanza.lines() {
+            // Continuation lines of multi-line fields start with a space
+            if line.starts_with([' ', '\t']) {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                fields.insert(key, value.trim());
+            }
+        }
+
+        let (name, version) =
+            match (fields.get("Package"), fields.get("Version")) {
+                (Some(name), Some(version)) => (*name, *version),
+                _ => continue,
+            };
+        let state = match fields.get("Status").and_then(|s| s.split(' ').nth(2))
+        {
+            Some("installed") => "active",
+            Some("not-installed") | None => continue,
+            Some(state) => state,
+        };
+        let architecture =
+            fields.get("Architecture").copied().unwrap_or_default();
+
+        insert_package(
+            &mut packages,
+            name,
+            &format!("{}:{}", name, architecture),
+            Package {
+                key: name.to_owned(),
+                version: version.to_owned(),
+                description: fields
+                    .get("Description")
+                    .copied()
+                    .unwrap_or_default()
+                    .to_owned(),
+                state: state.to_owned(),
+            },
+        );
+    }
+
+    Packages::new_with_packages(packages)
+}
+
+/// Parse the output of `rpm -qa` with the [`RPM_QUERY_FORMAT`]
+fn parse_rpm_output(output: &str) -> Packages {
+    let mut packages = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.splitn(4, '\t');
+        let (name, version, architecture, summary) = match (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) {
+            (Some(name), Some(version), Some(architecture), summary)
+                if !name.is_empty() =>
+            {
+                (name, version, architecture, summary.unwrap_or_default())
+            }
+            _ => continue,
+        };
+
+        insert_package(
+            &mut packages,
+            name,
+            &format!("{}.{}", name, architecture),
+            Package {
+                key: name.to_owned(),
+                version: version.to_owned(),
+                description: summary.to_owned(),
+                state: "active".to_owned(),
+            },
+        );
+    }
+
+    Packages::new_with_packages(packages)
+}
+
+/// Insert the package by its name, or by the name including the architecture
+/// if a package with the name is already known (multi-arch installations)
+fn insert_package(
+    packages: &mut HashMap<String, Package>,
+    name: &str,
+    qualified_name: &str,
+    mut package: Package,
+) {
+    if packages.contains_key(name) {
+        package.key = qualified_name.to_owned();
+        packages.insert(qualified_name.to_owned(), package);
+    } else {
+        packages.insert(name.to_owned(), package);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dpkg_status_test() {
+        let content = "\
+Package: nginx
+Status: install ok installed
+Architecture: amd64
+Version: 1.22.1-9
+Description: small, powerful, scalable web/proxy server
+ Nginx (\"engine X\") is a high-performance web and reverse proxy server.
+ .
+ Version: not a field
+
+Package: apache2
+Status: deinstall ok config-files
+Architecture: amd64
+Version: 2.4.57-2
+Description: Apache HTTP Server
+
+Package: libc6
+Status: install ok installed
+Architecture: amd64
+Version: 2.36-9
+Description: GNU C Library: Shared libraries
+
+Package: libc6
+Status: install ok installed
+Architecture: i386
+Version: 2.36-9
+Description: GNU C Library: Shared libraries
+
+Package: removed
+Status: purge ok not-installed
+Architecture: amd64
+";
+        let packages = parse_dpkg_status(content);
+
+        assert_eq!(4, packages.len());
+        let nginx = &packages["nginx"];
+        assert_eq!("1.22.1-9", nginx.version);
+        assert_eq!(
+            "small, powerful, scalable web/proxy server",
+            nginx.description
+        );
+        assert!(nginx.is_active());
+        assert_eq!("config-files", packages["apache2"].state);
+        assert!(!packages["apache2"].is_active());
+        assert_eq!("libc6:i386", packages["libc6:i386"].key);
+        assert_eq!("libc6", packages["libc6"].key);
+    }
+
+    #[test]
+    fn parse_rpm_output_test() {
+        let output = "bash\t5.1.8-6.el9\tx86_64\tThe GNU Bourne Again shell\n\
+                      glibc\t2.34-60.el9\tx86_64\tThe GNU libc libraries\n\
+                      glibc\t2.34-60.el9\ti686\tThe GNU libc libraries\n\
+                      gpg-pubkey\t8483c65d-5ccc5b19\t(none)\n\
+                      \n";
+        let packages = parse_rpm_output(output);
+
+        assert_eq!(4, packages.len());
+        assert_eq!("5.1.8-6.el9", packages["bash"].version);
+        assert_eq!("The GNU Bourne Again shell", packages["bash"].description);
+        assert!(packages["bash"].is_active());
+        assert_eq!("glibc.i686", packages["glibc.i686"].key);
+        assert_eq!("", packages["gpg-pubkey"].description);
+    }
+}
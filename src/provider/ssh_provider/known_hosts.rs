@@ -0,0 +1,303 @@
+use crate::configuration::{Configuration, HostKeyCheck};
+use crate::error::*;
+use ssh2::{CheckResult, KnownHostFileKind, KnownHosts, Session};
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{ErrorKind as IoErrorKind, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Lock to check and update the file as one step
+///
+/// Otherwise concurrent workers connecting to the same new host would each
+/// append its key
+static KNOWN_HOSTS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug)]
+enum Verdict {
+    Accept,
+    Add,
+    Reject(Error),
+}
+
+/// Verify the session's host key against `~/.ssh/known_hosts`
+///
+/// Depending on the configuration's `host_key_check` mode unknown hosts are
+/// rejected (`strict`), added to the file (`accept-new`) or the check is
+/// skipped entirely (`off`)
+pub fn verify_host_key(
+    session: &Session,
+    configuration: &Configuration,
+) -> Result<(), Error> {
+    let mode = configuration.host_key_check();
+    if mode == HostKeyCheck::Off {
+        return Ok(());
+    }
+
+    let (key, key_type) = match session.host_key() {
+        Some(host_key) => host_key,
+        None => {
+            return Err(Error::host_key(format!(
+                "The host {} did not send a host key",
+                configuration.host()
+            )))
+        }
+    };
+
+    let file = get_known_hosts_file()?;
+    let _lock = KNOWN_HOSTS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut known_hosts = session.known_hosts()?;
+    read_known_hosts(&mut known_hosts, &file)?;
+
+    let result =
+        known_hosts.check_port(configuration.host(), configuration.port(), key);
+    match evaluate(result, mode, configuration, &file) {
+        Verdict::Accept => Ok(()),
+        Verdict::Reject(e) => Err(e),
+        Verdict::Add => {
+            let mut new_hosts = session.known_hosts()?;
+            new_hosts.add(
+                &build_known_host_name(
+                    configuration.host(),
+                    configuration.port(),
+                ),
+                key,
+                "",
+                key_type.into(),
+            )?;
+
+            match new_hosts.hosts()?.first() {
+                Some(host) => append_line(
+                    &file,
+                    new_hosts.write_string(host, KnownHostFileKind::OpenSSH)?,
+                ),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Read the entries of the file into `known_hosts`
+///
+/// Lines with markers (`@cert-authority` and `@revoked`), which libssh2 does
+/// not support, and other invalid lines are skipped, so that the remaining
+/// entries can still be used
+fn read_known_hosts(
+    known_hosts: &mut KnownHosts,
+    file: &Path,
+) -> Result<(), Error> {
+    let content = match fs::read(file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(Error::host_key(format!(
+                "Could not read the known hosts file {}: {}",
+                file.to_string_lossy(),
+                e
+            )))
+        }
+    };
+
+    for line in String::from_utf8_lossy(&content).lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', '@']) {
+            continue;
+        }
+        let _ = known_hosts.read_str(line, KnownHostFileKind::OpenSSH);
+    }
+
+    Ok(())
+}
+
+fn evaluate(
+    result: CheckResult,
+    mode: HostKeyCheck,
+    configuration: &Configuration,
+    file: &Path,
+) -> Verdict {
+    match result {
+        CheckResult::Match => Verdict::Accept,
+        CheckResult::Mismatch => Verdict::Reject(Error::host_key(format!(
+            "The host key for {} does not match the key in {}. Someone could be eavesdropping (man-in-the-middle attack) or the host key has just been changed",
+            configuration.host(),
+            file.to_string_lossy()
+        ))),
+        CheckResult::NotFound if mode == HostKeyCheck::AcceptNew => {
+            Verdict::Add
+        }
+        CheckResult::NotFound => Verdict::Reject(Error::host_key(format!(
+            "No host key is known for {} in {} and the host key check is strict",
+            configuration.host(),
+            file.to_string_lossy()
+        ))),
+        CheckResult::Failure => Verdict::Reject(Error::host_key(format!(
+            "The host key for {} could not be checked",
+            configuration.host()
+        ))),
+    }
+}
+
+/// Return the host name as used in the known hosts file
+fn build_known_host_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_owned()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+/// Append the line to the file
+///
+/// The directory of the file (`~/.ssh`) is created if it does not exist
+fn append_line(file: &Path, mut line: String) -> Result<(), Error> {
+    if !line.ends_with('\n') {
+        line.push('\n');
+    }
+
+    if let Some(directory) = file.parent() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(directory)?;
+    }
+
+    let mut handle = OpenOptions::new().create(true).append(true).open(file)?;
+    handle.write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+fn get_known_hosts_file() -> Result<PathBuf, Error> {
+    match dirs::home_dir() {
+        Some(mut path) => {
+            path.push(".ssh");
+            path.push("known_hosts");
+
+            Ok(path)
+        }
+        None => Err(Error::host_key(
+            "Could not detect the home directory to read known_hosts",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_for_mode(result: CheckResult, mode: HostKeyCheck) -> Verdict {
+        evaluate(
+            result,
+            mode,
+            &Configuration::new_empty(),
+            Path::new("known_hosts"),
+        )
+    }
+
+    #[test]
+    fn evaluate_test() {
+        for mode in [HostKeyCheck::Strict, HostKeyCheck::AcceptNew] {
+            assert!(matches!(
+                evaluate_for_mode(CheckResult::Match, mode),
+                Verdict::Accept
+            ));
+            assert!(matches!(
+                evaluate_for_mode(CheckResult::Mismatch, mode),
+                Verdict::Reject(_)
+            ));
+            assert!(matches!(
+                evaluate_for_mode(CheckResult::Failure, mode),
+                Verdict::Reject(_)
+            ));
+        }
+
+        assert!(matches!(
+            evaluate_for_mode(CheckResult::NotFound, HostKeyCheck::AcceptNew),
+            Verdict::Add
+        ));
+        assert!(matches!(
+            evaluate_for_mode(CheckResult::NotFound, HostKeyCheck::Strict),
+            Verdict::Reject(_)
+        ));
+    }
+
+    #[test]
+    fn evaluate_mismatch_message_test() {
+        match evaluate_for_mode(CheckResult::Mismatch, HostKeyCheck::Strict) {
+            Verdict::Reject(e) => {
+                assert_eq!(&ErrorKind::HostKey, e.kind());
+                assert!(e.message().contains("does not match"));
+            }
+            v => panic!("Unexpected verdict {:?}", v),
+        }
+    }
+
+    #[test]
+    fn append_line_test() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = std::env::temp_dir()
+            .join(format!("fleet-known-hosts-test-{}", std::process::id()));
+        let file = directory.join(".ssh").join("known_hosts");
+
+        append_line(&file, "example.com ssh-ed25519 AAAA".to_owned()).unwrap();
+        append_line(&file, "example.org ssh-ed25519 AAAA\n".to_owned())
+            .unwrap();
+
+        assert_eq!(
+            "example.com ssh-ed25519 AAAA\nexample.org ssh-ed25519 AAAA\n",
+            std::fs::read_to_string(&file).unwrap()
+        );
+        let mode = std::fs::metadata(file.parent().unwrap())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(0o700, mode & 0o777);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn read_known_hosts_test() {
+        let file = std::env::temp_dir().join(format!(
+            "fleet-known-hosts-read-test-{}",
+            std::process::id()
+        ));
+        let key = "AAAAC3NzaC1lZDI1NTE5AAAAIAABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4f";
+        std::fs::write(
+            &file,
+            format!(
+                "# comment\n\
+                 @cert-authority *.example.com ssh-ed25519 {key}\n\
+                 @revoked example.net ssh-ed25519 {key}\n\
+                 example.com ssh-ed25519 {key}\n"
+            ),
+        )
+        .unwrap();
+
+        let session = Session::new().unwrap();
+        let mut known_hosts = session.known_hosts().unwrap();
+        read_known_hosts(&mut known_hosts, &file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let hosts = known_hosts.hosts().unwrap();
+        assert_eq!(1, hosts.len());
+        assert_eq!(Some("example.com"), hosts[0].name());
+
+        // A missing file has no entries
+        read_known_hosts(&mut known_hosts, &file).unwrap();
+
+        let error = read_known_hosts(&mut known_hosts, &std::env::temp_dir())
+            .unwrap_err();
+        assert_eq!(&ErrorKind::HostKey, error.kind());
+        assert!(error.message().contains("known hosts file"));
+    }
+
+    #[test]
+    fn build_known_host_name_test() {
+        assert_eq!("example.com", build_known_host_name("example.com", 22));
+        assert_eq!(
+            "[example.com]:2222",
+            build_known_host_name("example.com", 2222)
+        );
+    }
+}
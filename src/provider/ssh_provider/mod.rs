@@ -1,6 +1,11 @@
+mod credentials;
+mod known_hosts;
+mod proxy_jump;
 mod ssh_connector;
+mod worker_pool;
 
 use self::ssh_connector::SshConnector;
+use self::worker_pool::WorkerPool;
 use crate::configuration::*;
 use crate::error::*;
 use crate::information::*;
@@ -8,36 +13,120 @@ use crate::shell::ShellOutputCollection;
 use ssh2::Channel;
 use ssh2::Session;
 use std::io::prelude::*;
-use std::net::TcpStream;
-use std::sync::mpsc;
-use std::thread;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
 
-pub struct SshProvider;
+pub struct SshProvider {
+    settings: Settings,
+}
 
 /// Fetch information from the server defined in `configuration`
+///
+/// Returns the information and the warnings of the decoder
 fn fetch_information_through_ssh(
     configuration: &Configuration,
-) -> Result<Information, Error> {
-    let content =
-        execute_shell_through_ssh(configuration.command(), configuration)?;
-    let information: Information = match serde_json::from_str(&content) {
-        Ok(information) => information,
-        Err(e) => return Err(Error::with_error_and_details(&e, content)),
-    };
-
-    Ok(information)
+    settings: &Settings,
+) -> Result<(Information, Vec<String>), Error> {
+    let content = execute_shell_through_ssh(
+        configuration.command(),
+        configuration,
+        settings,
+        settings.command_timeout(configuration),
+    )?;
+
+    decode_information(&content)
 }
 
 /// Fetch information from the server defined in `configuration`
+///
+/// The command is aborted after `command_timeout`
 fn execute_shell_through_ssh<S: Into<String>>(
     command: S,
     configuration: &Configuration,
+    settings: &Settings,
+    command_timeout: Option<Duration>,
 ) -> Result<String, Error> {
     let address = format!("{}:{}", configuration.host(), configuration.port());
-    let tcp = TcpStream::connect(address)?;
-    let session: Session = SshConnector::new().connect(configuration, tcp)?;
+    let connect_timeout = settings.connect_timeout(configuration);
+
+    let stream = proxy_jump::open_stream(configuration, settings)?;
+    let session: Session = SshConnector::new(settings)
+        .connect(configuration, stream, connect_timeout)
+        .map_err(|e| {
+            describe_timeout(
+                e,
+                format!("Connecting to {}", address),
+                connect_timeout,
+            )
+        })?;
+
+    session.set_timeout(as_milliseconds(command_timeout));
+    call_ssh_command(command.into(), &session).map_err(|e| {
+        describe_timeout(e, format!("Command on {}", address), command_timeout)
+    })
+}
+
+/// Open a TCP connection to the address, waiting at most `timeout`
+fn connect_tcp(
+    address: &str,
+    timeout: Option<Duration>,
+) -> Result<TcpStream, Error> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            return TcpStream::connect(address)
+                .map_err(|e| Error::from(e).or_kind(ErrorKind::Connect))
+        }
+    };
+
+    let socket_addresses = match address.to_socket_addrs() {
+        Ok(socket_addresses) => socket_addresses,
+        Err(e) => return Err(Error::connect(e.to_string())),
+    };
+
+    let mut last_error = None;
+    for socket_address in socket_addresses {
+        match TcpStream::connect_timeout(&socket_address, timeout) {
+            Ok(tcp) => return Ok(tcp),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    match last_error {
+        Some(e) => Err(describe_timeout(
+            Error::from(e).or_kind(ErrorKind::Connect),
+            format!("Connecting to {}", address),
+            Some(timeout),
+        )),
+        None => Err(Error::connect(format!(
+            "Could not resolve address {}",
+            address
+        ))),
+    }
+}
 
-    call_ssh_command(command.into(), &session)
+/// Replace the message of a timeout error with a description of the operation
+fn describe_timeout(
+    error: Error,
+    operation: String,
+    timeout: Option<Duration>,
+) -> Error {
+    match (error.kind(), timeout) {
+        (ErrorKind::Timeout, Some(timeout)) => Error::timeout(format!(
+            "{} timed out after {} seconds",
+            operation,
+            timeout.as_secs()
+        )),
+        _ => error,
+    }
+}
+
+/// Convert the timeout into milliseconds for libssh2 (`0` means no timeout)
+fn as_milliseconds(timeout: Option<Duration>) -> u32 {
+    match timeout {
+        Some(timeout) => timeout.as_millis().min(u32::MAX as u128) as u32,
+        None => 0,
+    }
 }
 
 fn call_ssh_command<S: Into<String>>(
@@ -49,13 +138,11 @@ fn call_ssh_command<S: Into<String>>(
     // Open channel
     let mut channel: Channel = session.channel_session()?;
     // Execute the command
-    channel.exec(&command_string).unwrap();
+    channel.exec(&command_string)?;
 
     // Read the output
     let mut output = String::new();
-    if let Err(e) = Read::read_to_string(&mut channel, &mut output) {
-        return Err(Error::from_error(&e));
-    }
+    Read::read_to_string(&mut channel, &mut output)?;
 
     let exit_status = channel.exit_status()?;
     if exit_status == 0 && !output.is_empty() {
@@ -63,41 +150,71 @@ fn call_ssh_command<S: Into<String>>(
     }
 
     let mut error_output = String::new();
-    match Read::read_to_string(&mut channel.stderr(), &mut error_output) {
-        Ok(_) => {
-            let error_output_trimmed = error_output.trim();
-            if !error_output_trimmed.is_empty() {
-                Err(Error::new(error_output_trimmed))
-            } else {
-                Err(Error::new(output.trim()))
-            }
-        }
-        Err(error) => Err(Error::from_error(&error)),
-    }
+    Read::read_to_string(&mut channel.stderr(), &mut error_output)?;
+
+    let error_output_trimmed = error_output.trim();
+    let message = if exit_status == 0 {
+        "Command did not return any output".to_owned()
+    } else if !error_output_trimmed.is_empty() {
+        error_output_trimmed.to_owned()
+    } else {
+        output.trim().to_owned()
+    };
+
+    Err(Error::exec(
+        exit_status,
+        error_output_trimmed.to_owned(),
+        message,
+    ))
 }
 
 impl SshProvider {
+    /// Create a new SSH provider with the given settings
+    pub fn with_settings(settings: Settings) -> Self {
+        SshProvider { settings }
+    }
+
     /// Fetch information from the server defined in `configuration`
+    ///
+    /// Returns the information and warnings about the provider's output
     pub fn get_information(
         &self,
         configuration: &Configuration,
-    ) -> Result<Information, Error> {
-        fetch_information_through_ssh(configuration)
+    ) -> Result<(Information, Vec<String>), Error> {
+        fetch_information_through_ssh(configuration, &self.settings)
     }
 
     /// Fetch the information for all hosts in the given configuration collection
+    ///
+    /// Warnings about the providers' output are collected per host
     pub fn get_information_for_collection(
         &self,
         configuration_collection: ConfigurationCollection,
-    ) -> (InformationCollection, ErrorCollection) {
-        if configuration_collection.is_empty() {
-            return (InformationCollection::new(), ErrorCollection::new());
-        }
-        if configuration_collection.len() <= self.get_number_of_threads() {
-            return self
-                .get_information_for_collection_sync(configuration_collection);
+    ) -> (InformationCollection, ErrorCollection, WarningCollection) {
+        let mut error_collection = ErrorCollection::new();
+        let mut information_collection = InformationCollection::new();
+        let mut warning_collection = WarningCollection::new();
+
+        for (host, result) in self.worker_pool().run(
+            configuration_collection,
+            |_, configuration| {
+                fetch_information_through_ssh(configuration, &self.settings)
+            },
+        ) {
+            match result {
+                Ok((i, warnings)) => {
+                    if !warnings.is_empty() {
+                        warning_collection.insert(host.clone(), warnings);
+                    }
+                    let _ = information_collection.insert(host, i);
+                }
+                Err(e) => {
+                    let _ = error_collection.insert(host, e);
+                }
+            };
         }
-        self.get_information_for_collection_async(configuration_collection)
+
+        (information_collection, error_collection, warning_collection)
     }
 
     /// Execute the given shell command for all hosts in the given configuration collection asynchronously
@@ -106,46 +223,14 @@ impl SshProvider {
         command: String,
         configuration_collection: ConfigurationCollection,
     ) -> (ShellOutputCollection, ErrorCollection) {
-        let mut error_collection = ErrorCollection::new();
-        let mut output_collection = ShellOutputCollection::new();
-
-        let (split_count, split_configuration_collection) = self
-            .chunk_configuration_collection_for_threads(
-                configuration_collection,
-            );
-        let (tx, rx) = mpsc::channel();
-
-        for chunk in split_configuration_collection {
-            let tx = tx.clone();
-            let command_l = command.clone();
-
-            thread::spawn(move || {
-                let mut error_collection_l = ErrorCollection::new();
-                let mut output_collection_l = ShellOutputCollection::new();
-
-                for (host, configuration) in chunk {
-                    match execute_shell_through_ssh(&command_l, &configuration)
-                    {
-                        Ok(i) => {
-                            let _ = output_collection_l.insert(host, i);
-                        }
-                        Err(e) => {
-                            let _ = error_collection_l.insert(host, e);
-                        }
-                    };
-                }
-
-                tx.send((output_collection_l, error_collection_l)).unwrap();
-            });
-        }
-
-        for _ in 0..split_count {
-            let (output_collection_l, error_collection_l) = rx.recv().unwrap();
-            error_collection.extend(error_collection_l);
-            output_collection.extend(output_collection_l);
-        }
-
-        (output_collection, error_collection)
+        self.execute_for_collection(configuration_collection, |configuration| {
+            execute_shell_through_ssh(
+                &command,
+                configuration,
+                &self.settings,
+                self.settings.command_timeout(configuration),
+            )
+        })
     }
 
     /// Execute the update command for all hosts in the given configuration collection asynchronously
@@ -153,65 +238,43 @@ impl SshProvider {
         &self,
         configuration_collection: ConfigurationCollection,
     ) -> (ShellOutputCollection, ErrorCollection) {
-        let mut error_collection = ErrorCollection::new();
-        let mut output_collection = ShellOutputCollection::new();
-
         let filtered_collection: ConfigurationCollection =
             configuration_collection
                 .into_iter()
                 .filter(|(_, c)| c.update_command().is_some())
                 .collect();
 
-        let (split_count, split_configuration_collection) = self
-            .chunk_configuration_collection_for_threads(filtered_collection);
-        let (tx, rx) = mpsc::channel();
-
-        for chunk in split_configuration_collection {
-            let tx = tx.clone();
-
-            thread::spawn(move || {
-                let mut error_collection_l = ErrorCollection::new();
-                let mut output_collection_l = ShellOutputCollection::new();
-
-                for (host, configuration) in chunk {
-                    match execute_shell_through_ssh(
-                        configuration.update_command().unwrap(),
-                        &configuration,
-                    ) {
-                        Ok(i) => {
-                            let _ = output_collection_l.insert(host, i);
-                        }
-                        Err(e) => {
-                            let _ = error_collection_l.insert(host, e);
-                        }
-                    };
-                }
-
-                tx.send((output_collection_l, error_collection_l)).unwrap();
-            });
-        }
-
-        for _ in 0..split_count {
-            let (output_collection_l, error_collection_l) = rx.recv().unwrap();
-            error_collection.extend(error_collection_l);
-            output_collection.extend(output_collection_l);
-        }
-
-        (output_collection, error_collection)
+        self.execute_for_collection(filtered_collection, |configuration| {
+            execute_shell_through_ssh(
+                configuration.update_command().unwrap(),
+                configuration,
+                &self.settings,
+                self.settings.update_timeout(configuration),
+            )
+        })
     }
 
-    /// Fetch the information for all hosts in the given configuration collection synchronously
-    fn get_information_for_collection_sync(
+    /// Invoke `execute` for all hosts in the given configuration collection asynchronously
+    fn execute_for_collection<F>(
         &self,
         configuration_collection: ConfigurationCollection,
-    ) -> (InformationCollection, ErrorCollection) {
+        execute: F,
+    ) -> (ShellOutputCollection, ErrorCollection)
+    where
+        F: Fn(&Configuration) -> Result<String, Error> + Sync,
+    {
         let mut error_collection = ErrorCollection::new();
-        let mut information_collection = InformationCollection::new();
+        let mut output_collection = ShellOutputCollection::new();
 
-        for (host, configuration) in configuration_collection {
-            match self.get_information(&configuration) {
+        for (host, result) in self
+            .worker_pool()
+            .run(configuration_collection, |_, configuration| {
+                execute(configuration)
+            })
+        {
+            match result {
                 Ok(i) => {
-                    let _ = information_collection.insert(host, i);
+                    let _ = output_collection.insert(host, i);
                 }
                 Err(e) => {
                     let _ = error_collection.insert(host, e);
@@ -219,93 +282,19 @@ impl SshProvider {
             };
         }
 
-        (information_collection, error_collection)
-    }
-
-    /// Fetch the information for all hosts in the given configuration collection asynchronously
-    fn get_information_for_collection_async(
-        &self,
-        configuration_collection: ConfigurationCollection,
-    ) -> (InformationCollection, ErrorCollection) {
-        let mut error_collection = ErrorCollection::new();
-        let mut information_collection = InformationCollection::new();
-
-        let (tx, rx) = mpsc::channel();
-
-        // let number_of_threads = self.get_number_of_threads();
-        // let size_of_chunk: usize =
-        //     (configuration_collection.len() as f32 / number_of_threads as f32).ceil() as usize;
-        // let split_configuration_collection: Vec<ConfigurationCollection> =
-        //     chunk_configuration_collection(configuration_collection, size_of_chunk);
-        // let split_count = split_configuration_collection.len();
-        let (split_count, split_configuration_collection) = self
-            .chunk_configuration_collection_for_threads(
-                configuration_collection,
-            );
-
-        for chunk in split_configuration_collection {
-            let tx = tx.clone();
-
-            thread::spawn(move || {
-                let mut error_collection_l = ErrorCollection::new();
-                let mut information_collection_l = InformationCollection::new();
-
-                for (host, configuration) in chunk {
-                    match fetch_information_through_ssh(&configuration) {
-                        Ok(i) => {
-                            let _ = information_collection_l.insert(host, i);
-                        }
-                        Err(e) => {
-                            let _ = error_collection_l.insert(host, e);
-                        }
-                    };
-                }
-
-                tx.send((information_collection_l, error_collection_l))
-                    .unwrap();
-            });
-        }
-
-        for _ in 0..split_count {
-            let (information_collection_l, error_collection_l) =
-                rx.recv().unwrap();
-            error_collection.extend(error_collection_l);
-            information_collection.extend(information_collection_l);
-        }
-
-        (information_collection, error_collection)
-    }
-
-    fn get_number_of_threads(&self) -> usize {
-        4
+        (output_collection, error_collection)
     }
 
-    fn chunk_configuration_collection_for_threads(
-        &self,
-        configuration_collection: ConfigurationCollection,
-    ) -> (usize, Vec<ConfigurationCollection>) {
-        if configuration_collection.is_empty() {
-            return (0, vec![]);
-        }
-
-        let number_of_threads = self.get_number_of_threads();
-        let size_of_chunk: usize = (configuration_collection.len() as f32
-            / number_of_threads as f32)
-            .ceil() as usize;
-        let split_configuration_collection: Vec<ConfigurationCollection> =
-            chunk_configuration_collection(
-                configuration_collection,
-                size_of_chunk,
-            );
-        let split_count = split_configuration_collection.len();
-
-        (split_count, split_configuration_collection)
+    fn worker_pool(&self) -> WorkerPool {
+        WorkerPool::new(self.settings.concurrency())
     }
 }
 
 impl super::Provider for SshProvider {
     fn new() -> Self {
-        SshProvider {}
+        SshProvider {
+            settings: Settings::default(),
+        }
     }
 }
 
@@ -313,12 +302,13 @@ impl super::Provider for SshProvider {
 mod tests {
     use crate::configuration::helper::Helper;
     use crate::configuration::Configuration;
+    use crate::provider::Provider;
 
     use super::*;
 
     #[test]
     fn get_information_for_uri_test() {
-        let provider = SshProvider {};
+        let provider = SshProvider::new();
         let configuration = Configuration::new_with_public_key(
             "not-a-host",
             22,
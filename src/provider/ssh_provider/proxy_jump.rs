@@ -0,0 +1,412 @@
+use super::ssh_connector::SshConnector;
+use super::{connect_tcp, describe_timeout};
+use crate::configuration::{Configuration, Settings};
+use crate::error::*;
+use ssh2::{BlockDirections, Channel, Session};
+use std::io::{self, ErrorKind as IoErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Maximum number of bytes forwarded at once
+const BUFFER_SIZE: usize = 16384;
+
+/// Stream to the SSH server of a host
+pub enum HostStream {
+    /// TCP connection to the host
+    Direct(TcpStream),
+    /// Socket that is forwarded through the jump hosts
+    Tunneled(UnixStream),
+}
+
+impl AsRawFd for HostStream {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            HostStream::Direct(stream) => stream.as_raw_fd(),
+            HostStream::Tunneled(stream) => stream.as_raw_fd(),
+        }
+    }
+}
+
+/// Open a stream to the SSH server of the host in `configuration`
+///
+/// If the configuration defines jump hosts, a connection to each of them is
+/// established and the stream is tunneled through `direct-tcpip` channels.
+/// Otherwise a plain TCP connection is returned
+pub fn open_stream(
+    configuration: &Configuration,
+    settings: &Settings,
+) -> Result<HostStream, Error> {
+    let jump_hosts = configuration.jump_hosts();
+    let first = jump_hosts.first().unwrap_or(configuration);
+    let mut stream = HostStream::Direct(connect_tcp(
+        &build_address(first),
+        settings.connect_timeout(first),
+    )?);
+    let mut forwarder: Option<Forwarder> = None;
+
+    for (index, jump_host) in jump_hosts.iter().enumerate() {
+        let target = jump_hosts.get(index + 1).unwrap_or(configuration);
+        let connect_timeout = settings.connect_timeout(jump_host);
+        let operation =
+            format!("Connecting to jump host {}", build_address(jump_host));
+
+        let transport = stream.as_raw_fd();
+        let session = SshConnector::new(settings)
+            .connect(jump_host, stream, connect_timeout)
+            .map_err(|e| {
+                describe_timeout(
+                    prefix_message(e, &operation),
+                    operation.clone(),
+                    connect_timeout,
+                )
+            })?;
+        let channel = session
+            .channel_direct_tcpip(target.host(), target.port(), None)
+            .map_err(|e| {
+                prefix_message(
+                    Error::from(e).or_kind(ErrorKind::Connect),
+                    &format!(
+                        "Tunneling to {} through jump host {}",
+                        build_address(target),
+                        build_address(jump_host)
+                    ),
+                )
+            })?;
+
+        // The other end of the pair is only known to the tunnel
+        let (local, remote) = UnixStream::pair()?;
+        let tunnel = Tunnel::new(session, transport, channel, remote)?;
+        match forwarder {
+            Some(ref forwarder) => forwarder.add(tunnel)?,
+            None => forwarder = Some(Forwarder::start(tunnel)?),
+        }
+        stream = HostStream::Tunneled(local);
+    }
+
+    Ok(stream)
+}
+
+/// Forwards the tunnels of a connection in a background thread
+///
+/// The thread waits with `poll(2)` until one of the sockets is ready and ends
+/// once all tunnels are closed. Tunnels to further jump hosts are added while
+/// the thread is running, because their handshake runs through the previous
+/// tunnels
+struct Forwarder {
+    sender: Sender<Tunnel>,
+    /// Socket to wake up the thread when a tunnel was added
+    wake: UnixStream,
+}
+
+impl Forwarder {
+    fn start(tunnel: Tunnel) -> Result<Self, Error> {
+        let (wake, wake_receiver) = UnixStream::pair()?;
+        wake_receiver.set_nonblocking(true)?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || forward(vec![tunnel], receiver, wake_receiver));
+
+        Ok(Forwarder { sender, wake })
+    }
+
+    fn add(&self, tunnel: Tunnel) -> Result<(), Error> {
+        if self.sender.send(tunnel).is_err() {
+            return Err(Error::connect(
+                "The tunnel through the jump hosts was closed",
+            ));
+        }
+        (&self.wake).write_all(&[0])?;
+
+        Ok(())
+    }
+}
+
+fn forward(
+    mut tunnels: Vec<Tunnel>,
+    receiver: Receiver<Tunnel>,
+    wake: UnixStream,
+) {
+    // The wake socket is closed when the forwarder is dropped
+    let mut wake = Some(wake);
+    loop {
+        // Tunnels that are added later depend on the closed ones
+        tunnels.retain_mut(Tunnel::transfer);
+        if tunnels.is_empty() {
+            break;
+        }
+
+        let mut fds: Vec<libc::pollfd> =
+            tunnels.iter().flat_map(Tunnel::poll_fds).collect();
+        if let Some(ref wake) = wake {
+            fds.push(build_pollfd(wake.as_raw_fd(), libc::POLLIN));
+        }
+        // SAFETY: `fds` is a valid array of `fds.len()` elements
+        let result = unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1)
+        };
+        if result < 0
+            && io::Error::last_os_error().kind() != IoErrorKind::Interrupted
+        {
+            break;
+        }
+
+        if let Some(ref mut socket) = wake {
+            let mut buffer = [0; 64];
+            if let Ok(0) = socket.read(&mut buffer) {
+                wake = None;
+            }
+        }
+        tunnels.extend(receiver.try_iter());
+    }
+}
+
+/// `direct-tcpip` channel that is forwarded to a local socket
+struct Tunnel {
+    session: Session,
+    /// Socket of the session's connection
+    transport: RawFd,
+    channel: Channel,
+    socket: UnixStream,
+    /// Data read from the socket that is not yet sent through the channel
+    to_channel: Vec<u8>,
+    /// Data read from the channel that is not yet written to the socket
+    to_socket: Vec<u8>,
+}
+
+impl Tunnel {
+    fn new(
+        session: Session,
+        transport: RawFd,
+        channel: Channel,
+        socket: UnixStream,
+    ) -> Result<Self, Error> {
+        socket.set_nonblocking(true)?;
+        session.set_blocking(false);
+
+        Ok(Tunnel {
+            session,
+            transport,
+            channel,
+            socket,
+            to_channel: vec![],
+            to_socket: vec![],
+        })
+    }
+
+    /// Forward data until neither side is ready
+    ///
+    /// Returns `false` once either side closed the connection
+    fn transfer(&mut self) -> bool {
+        loop {
+            match self.transfer_once() {
+                Ok(true) => {}
+                Ok(false) => return true,
+                Err(_) => {
+                    let _ = self.channel.close();
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Forward data in both directions once and return if any was forwarded
+    fn transfer_once(&mut self) -> io::Result<bool> {
+        let mut forwarded = false;
+        if self.to_channel.is_empty() {
+            forwarded |= read_into(&mut self.socket, &mut self.to_channel)?;
+        }
+        if !self.to_channel.is_empty() {
+            forwarded |= write_from(&mut self.channel, &mut self.to_channel)?;
+        }
+        if self.to_socket.is_empty() {
+            forwarded |= read_into(&mut self.channel, &mut self.to_socket)?;
+        }
+        if !self.to_socket.is_empty() {
+            forwarded |= write_from(&mut self.socket, &mut self.to_socket)?;
+        }
+
+        Ok(forwarded)
+    }
+
+    /// Return the sockets and the events the tunnel is waiting for
+    ///
+    /// The connection is only watched if data can be taken from the channel
+    /// or libssh2 waits for it, otherwise `poll` would return immediately
+    fn poll_fds(&self) -> [libc::pollfd; 2] {
+        let mut transport = 0;
+        let mut socket = 0;
+        match self.session.block_directions() {
+            BlockDirections::Inbound => transport |= libc::POLLIN,
+            BlockDirections::Outbound => transport |= libc::POLLOUT,
+            BlockDirections::Both => transport |= libc::POLLIN | libc::POLLOUT,
+            BlockDirections::None => {}
+        }
+        if self.to_socket.is_empty() || !self.to_channel.is_empty() {
+            transport |= libc::POLLIN;
+        }
+        if self.to_channel.is_empty() {
+            socket |= libc::POLLIN;
+        }
+        if !self.to_socket.is_empty() {
+            socket |= libc::POLLOUT;
+        }
+
+        [
+            build_pollfd(self.transport, transport),
+            build_pollfd(self.socket.as_raw_fd(), socket),
+        ]
+    }
+}
+
+fn build_pollfd(fd: RawFd, events: libc::c_short) -> libc::pollfd {
+    libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    }
+}
+
+/// Read from the non-blocking reader into the empty buffer
+///
+/// Returns `false` if no data is available and an error if the reader is
+/// closed
+fn read_into<R: Read>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+) -> io::Result<bool> {
+    buffer.resize(BUFFER_SIZE, 0);
+    let result = reader.read(buffer);
+    let length = *result.as_ref().unwrap_or(&0);
+    buffer.truncate(length);
+
+    match result {
+        Ok(0) => Err(IoErrorKind::UnexpectedEof.into()),
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == IoErrorKind::WouldBlock => Ok(false),
+        Err(e) if e.kind() == IoErrorKind::Interrupted => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write the buffer to the non-blocking writer and remove the written data
+///
+/// Returns `false` if the writer is not ready
+fn write_from<W: Write>(
+    writer: &mut W,
+    buffer: &mut Vec<u8>,
+) -> io::Result<bool> {
+    match writer.write(buffer) {
+        Ok(0) => Err(IoErrorKind::WriteZero.into()),
+        Ok(length) => {
+            buffer.drain(..length);
+            Ok(true)
+        }
+        Err(e) if e.kind() == IoErrorKind::WouldBlock => Ok(false),
+        Err(e) if e.kind() == IoErrorKind::Interrupted => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+fn build_address(configuration: &Configuration) -> String {
+    format!("{}:{}", configuration.host(), configuration.port())
+}
+
+fn prefix_message(error: Error, prefix: &str) -> Error {
+    let message = format!("{}: {}", prefix, error.message());
+    Error::with_kind(error.kind().clone(), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn read_into_write_from_test() {
+        let (mut local, mut remote) = UnixStream::pair().unwrap();
+        local.set_nonblocking(true).unwrap();
+        remote.set_nonblocking(true).unwrap();
+
+        let mut buffer = vec![];
+        assert!(!read_into(&mut remote, &mut buffer).unwrap());
+        assert!(buffer.is_empty());
+
+        let mut data = b"forwarded".to_vec();
+        assert!(write_from(&mut local, &mut data).unwrap());
+        assert!(data.is_empty());
+        assert!(read_into(&mut remote, &mut buffer).unwrap());
+        assert_eq!(b"forwarded".to_vec(), buffer);
+
+        drop(local);
+        assert!(read_into(&mut remote, &mut buffer).is_err());
+    }
+
+    #[test]
+    fn prefix_message_test() {
+        let error = prefix_message(Error::auth("denied"), "Jump host");
+        assert_eq!(&ErrorKind::Auth, error.kind());
+        assert_eq!("Jump host: denied", error.message());
+    }
+
+    #[test]
+    fn open_stream_without_jump_hosts_test() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let configuration = Configuration::new_with_password(
+            "127.0.0.1",
+            port,
+            "cmd",
+            "user",
+            "password",
+        );
+
+        match open_stream(&configuration, &Settings::default()).unwrap() {
+            HostStream::Direct(stream) => assert_eq!(
+                listener.local_addr().unwrap(),
+                stream.peer_addr().unwrap()
+            ),
+            HostStream::Tunneled(_) => panic!("Unexpected tunnel"),
+        }
+    }
+
+    /// Needs an SSH server on localhost that accepts the agent or the default
+    /// key of the current user and a known host key, run with
+    /// `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn open_stream_through_jump_host_test() {
+        let username = std::env::var("USER").unwrap();
+        let build = |command| {
+            Configuration::new::<&str, &str>(
+                "localhost",
+                22,
+                command,
+                &username,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        // More than one buffer has to be forwarded through both tunnels
+        let command = "head -c 100000 /dev/zero | tr '\\0' x";
+        let configuration =
+            build(command).with_jump_hosts(vec![build("true"), build("true")]);
+        let settings = Settings::default();
+
+        match open_stream(&configuration, &settings).unwrap() {
+            HostStream::Tunneled(_) => {}
+            HostStream::Direct(_) => panic!("Expected a tunnel"),
+        }
+        let output = super::super::execute_shell_through_ssh(
+            command,
+            &configuration,
+            &settings,
+            settings.command_timeout(&configuration),
+        )
+        .unwrap();
+        assert_eq!("x".repeat(100000), output);
+    }
+}
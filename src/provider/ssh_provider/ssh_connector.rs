@@ -1,67 +1,209 @@
-use crate::configuration::Configuration;
+use super::as_milliseconds;
+use super::credentials::CredentialCache;
+use super::known_hosts::verify_host_key;
+use crate::configuration::{AuthMethod, Configuration, Settings};
 use crate::error::*;
-use ssh2::Session;
+use crate::Printer;
+use ssh2::{KeyboardInteractivePrompt, Prompt, Session};
 use std::env;
-use std::net::TcpStream;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
 
-pub struct SshConnector {}
+pub struct SshConnector {
+    prompt: bool,
+    verbose: bool,
+}
+
+/// Result of a single authentication method
+enum Attempt {
+    /// The method is not available (e.g. no private key is configured)
+    Skipped(String),
+    Failed(Error),
+    Succeeded,
+}
 
 impl SshConnector {
     /// Create a new SSH connector
-    pub fn new() -> Self {
-        SshConnector {}
+    ///
+    /// If prompting is enabled in the settings, missing passphrases and
+    /// passwords are asked for on the terminal. In verbose mode failed
+    /// authentication attempts are reported
+    pub fn new(settings: &Settings) -> Self {
+        SshConnector {
+            prompt: settings.prompt(),
+            verbose: settings.verbose(),
+        }
     }
 
     /// Establish a SSH connection with the given configuration
-    pub fn connect(
+    ///
+    /// The handshake and authentication have to finish within `timeout`
+    pub fn connect<S: 'static + AsRawFd>(
         &self,
         configuration: &Configuration,
-        tcp: TcpStream,
+        stream: S,
+        timeout: Option<Duration>,
     ) -> Result<Session, Error> {
         // Connect to the SSH server
-        let mut session = Session::new().unwrap();
-        session.set_tcp_stream(tcp);
-        session.handshake().unwrap();
+        let mut session = Session::new()?;
+        session.set_timeout(as_milliseconds(timeout));
+        session.set_tcp_stream(stream);
+        if let Err(e) = session.handshake() {
+            return Err(Error::from(e).or_kind(ErrorKind::Connect));
+        }
+        verify_host_key(&session, configuration)?;
 
         if session.authenticated() {
             return Ok(session);
         }
 
-        if configuration.password().is_some() {
-            self.authenticate_password(configuration, session)
-        } else if configuration.private_key().is_some() {
-            self.authenticate_public_key(configuration, session)
+        self.authenticate(configuration, &session)
+            .map_err(|e| e.or_kind(ErrorKind::Auth))?;
+
+        Ok(session)
+    }
+
+    /// Try the configured authentication methods until one succeeds
+    ///
+    /// Methods the server does not offer are skipped
+    fn authenticate(
+        &self,
+        configuration: &Configuration,
+        session: &Session,
+    ) -> Result<(), Error> {
+        let offered = match session.auth_methods(configuration.username()) {
+            Ok(methods) => Some(methods.to_owned()),
+            // The server accepted the `none` authentication
+            Err(_) if session.authenticated() => return Ok(()),
+            Err(_) => None,
+        };
+
+        let mut failures = vec![];
+        let mut skipped = vec![];
+        for method in configuration.auth_methods() {
+            if let Some(ref offered) = offered {
+                if !is_offered(method, offered) {
+                    skipped.push(format!(
+                        "{}: not offered by the server",
+                        method.name()
+                    ));
+                    continue;
+                }
+            }
+
+            match self.attempt(method, configuration, session) {
+                Attempt::Succeeded => return Ok(()),
+                Attempt::Skipped(reason) => {
+                    skipped.push(format!("{}: {}", method.name(), reason))
+                }
+                Attempt::Failed(e) => {
+                    if self.verbose {
+                        Printer::print_warning(format!(
+                            "{}@{}: authentication with {} failed: {}",
+                            configuration.username(),
+                            configuration.host(),
+                            method.name(),
+                            e.message()
+                        ));
+                    }
+                    failures.push(format!(
+                        "{}: {}",
+                        method.name(),
+                        e.message()
+                    ));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Err(Error::auth(format!(
+                "No authentication method is available ({})",
+                skipped.join("; ")
+            )))
         } else {
-            match self.authenticate_agent(configuration, session) {
-                Ok(s) => Ok(s),
-                Err(e) => {
-                    eprintln!(
-                        "{}@{}:{e}",
-                        configuration.username(),
-                        configuration.host()
+            Err(Error::auth(format!(
+                "All authentication methods failed ({})",
+                failures.join("; ")
+            )))
+        }
+    }
+
+    fn attempt(
+        &self,
+        method: AuthMethod,
+        configuration: &Configuration,
+        session: &Session,
+    ) -> Attempt {
+        let result = match method {
+            AuthMethod::Agent => {
+                self.authenticate_agent(configuration, session)
+            }
+            AuthMethod::Publickey => {
+                if configuration.private_key().is_none() {
+                    return Attempt::Skipped(
+                        "no private key configured".into(),
                     );
-                    Err(e)
                 }
+                self.authenticate_public_key(configuration, session)
             }
+            AuthMethod::Password | AuthMethod::KeyboardInteractive => {
+                let password = match self.get_password(configuration) {
+                    Ok(Some(password)) => password,
+                    Ok(None) => {
+                        return Attempt::Skipped(
+                            "no password configured".into(),
+                        )
+                    }
+                    Err(e) => return Attempt::Failed(e),
+                };
+
+                if method == AuthMethod::Password {
+                    session
+                        .userauth_password(configuration.username(), &password)
+                        .map_err(Error::from)
+                } else {
+                    session
+                        .userauth_keyboard_interactive(
+                            configuration.username(),
+                            &mut PasswordPrompt { password },
+                        )
+                        .map_err(Error::from)
+                }
+            }
+        };
+
+        match result {
+            Ok(()) if session.authenticated() => Attempt::Succeeded,
+            Ok(()) => Attempt::Failed(Error::auth("the server rejected it")),
+            Err(e) => Attempt::Failed(e),
         }
     }
 
-    fn authenticate_password(
+    /// Return the configured password or ask for it
+    ///
+    /// `None` is returned if no password is configured and prompting is
+    /// disabled
+    fn get_password(
         &self,
         configuration: &Configuration,
-        session: Session,
-    ) -> Result<Session, Error> {
-        let password = configuration.password().unwrap();
-        session.userauth_password(configuration.username(), &password)?;
+    ) -> Result<Option<String>, Error> {
+        if let Some(password) = configuration.password() {
+            return CredentialCache::shared().resolve(password).map(Some);
+        }
+        if !self.prompt {
+            return Ok(None);
+        }
 
-        Ok(session)
+        CredentialCache::shared()
+            .password_for(configuration.username(), configuration.host())
+            .map(Some)
     }
 
     fn authenticate_agent(
         &self,
         configuration: &Configuration,
-        session: Session,
-    ) -> Result<Session, Error> {
+        session: &Session,
+    ) -> Result<(), Error> {
         let username = configuration.username();
 
         let mut agent = session.agent()?;
@@ -69,34 +211,43 @@ impl SshConnector {
         agent.list_identities()?;
         let identities = agent.identities()?;
         if identities.is_empty() {
-            return Err(Error::new("no identities found in the ssh agent"));
+            return Err(Error::auth("no identities found in the ssh agent"));
         }
 
         for identity in identities {
             if agent.userauth(username, &identity).is_ok() {
-                return Ok(session);
+                return Ok(());
             }
         }
 
-        Err(Error::new(
+        Err(Error::auth(
             "no identity did connect successfully using ssh agent",
         ))
     }
 
-    fn get_passphrase(&self, configuration: &Configuration) -> Option<String> {
-        if let Some(ref passphrase) = configuration.passphrase() {
-            return Some(passphrase.to_owned());
+    fn get_passphrase(
+        &self,
+        configuration: &Configuration,
+    ) -> Result<Option<String>, Error> {
+        if let Some(passphrase) = configuration.passphrase() {
+            return CredentialCache::shared().resolve(passphrase).map(Some);
+        }
+        if let Some(passphrase) = self.get_passphrase_from_env() {
+            return Ok(Some(passphrase));
         }
 
-        self.get_passphrase_from_env()
+        CredentialCache::shared().passphrase_for_key(
+            &configuration.private_key().unwrap(),
+            self.prompt,
+        )
     }
 
     fn authenticate_public_key(
         &self,
         configuration: &Configuration,
-        session: Session,
-    ) -> Result<Session, Error> {
-        let passphrase_option = self.get_passphrase(configuration);
+        session: &Session,
+    ) -> Result<(), Error> {
+        let passphrase_option = self.get_passphrase(configuration)?;
         let passphrase: Option<&str> =
             passphrase_option.as_ref().map(|x| x as _);
 
@@ -107,17 +258,87 @@ impl SshConnector {
             passphrase,
         )?;
 
-        Ok(session)
+        Ok(())
     }
 
     fn get_passphrase_from_env(&self) -> Option<String> {
-        let key = "PASSPHRASE";
-        match env::var(key) {
-            Ok(val) => Some(val),
-            Err(e) => {
-                println!("Couldn't get passphrase from env: {}", e);
-                None
-            }
-        }
+        env::var("PASSPHRASE").ok()
+    }
+}
+
+/// Check if the method is in the comma separated list of the server's methods
+fn is_offered(method: AuthMethod, offered: &str) -> bool {
+    let name = match method {
+        AuthMethod::Agent => AuthMethod::Publickey.name(),
+        method => method.name(),
+    };
+
+    offered.split(',').any(|offered| offered.trim() == name)
+}
+
+/// Answers the keyboard-interactive prompts that hide the input with the
+/// password
+struct PasswordPrompt {
+    password: String,
+}
+
+impl KeyboardInteractivePrompt for PasswordPrompt {
+    fn prompt<'a>(
+        &mut self,
+        _username: &str,
+        _instructions: &str,
+        prompts: &[Prompt<'a>],
+    ) -> Vec<String> {
+        prompts
+            .iter()
+            .map(|prompt| {
+                if prompt.echo {
+                    String::new()
+                } else {
+                    self.password.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_offered_test() {
+        let offered = "publickey,password";
+
+        assert!(is_offered(AuthMethod::Agent, offered));
+        assert!(is_offered(AuthMethod::Publickey, offered));
+        assert!(is_offered(AuthMethod::Password, offered));
+        assert!(!is_offered(AuthMethod::KeyboardInteractive, offered));
+        assert!(is_offered(
+            AuthMethod::KeyboardInteractive,
+            "publickey,keyboard-interactive"
+        ));
+    }
+
+    #[test]
+    fn password_prompt_test() {
+        let mut prompter = PasswordPrompt {
+            password: "s3cr3t".to_owned(),
+        };
+        let prompts = [
+            Prompt {
+                text: "Username: ".into(),
+                echo: true,
+            },
+            Prompt {
+                text: "Password: ".into(),
+                echo: false,
+            },
+        ];
+
+        assert_eq!(
+            vec!["".to_owned(), "s3cr3t".to_owned()],
+            prompter.prompt("daniel", "", &prompts)
+        );
     }
 }
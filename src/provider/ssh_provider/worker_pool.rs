@@ -0,0 +1,124 @@
+use crate::configuration::{Configuration, ConfigurationCollection};
+use crate::error::Error;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+
+pub type JobResult<T> = (String, Result<T, Error>);
+
+/// Pool of worker threads processing one job per host
+///
+/// All jobs are put into a shared queue. Each worker takes the next job as
+/// soon as it finished the previous one, so a slow host only occupies a single
+/// worker instead of holding up other hosts
+pub struct WorkerPool {
+    concurrency: usize,
+}
+
+impl WorkerPool {
+    /// Create a new pool with `concurrency` workers (at least one)
+    pub fn new(concurrency: usize) -> Self {
+        WorkerPool {
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    /// Invoke `job` for each host in the configuration collection
+    pub fn run<T, F>(
+        &self,
+        configuration_collection: ConfigurationCollection,
+        job: F,
+    ) -> Vec<JobResult<T>>
+    where
+        T: Send,
+        F: Fn(&str, &Configuration) -> Result<T, Error> + Sync,
+    {
+        let number_of_jobs = configuration_collection.len();
+        let number_of_workers = self.concurrency.min(number_of_jobs);
+        let queue: Mutex<VecDeque<(String, Configuration)>> =
+            Mutex::new(configuration_collection.into_iter().collect());
+        let (tx, rx) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..number_of_workers {
+                let tx = tx.clone();
+                let queue = &queue;
+                let job = &job;
+
+                scope.spawn(move || loop {
+                    let next = queue.lock().unwrap().pop_front();
+                    match next {
+                        Some((host, configuration)) => {
+                            let result = job(&host, &configuration);
+                            tx.send((host, result)).unwrap();
+                        }
+                        None => break,
+                    }
+                });
+            }
+        });
+        drop(tx);
+
+        rx.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn build_collection(size: usize) -> ConfigurationCollection {
+        (0..size)
+            .map(|i| (format!("host-{}", i), Configuration::new_empty()))
+            .collect()
+    }
+
+    #[test]
+    fn run_test() {
+        let results =
+            WorkerPool::new(4).run(build_collection(10), |host, _| {
+                if host == "host-3" {
+                    Err(Error::new("failed"))
+                } else {
+                    Ok(host.to_owned())
+                }
+            });
+
+        assert_eq!(10, results.len());
+        assert_eq!(1, results.iter().filter(|(_, r)| r.is_err()).count());
+        for (host, result) in results {
+            if let Ok(output) = result {
+                assert_eq!(host, output);
+            }
+        }
+    }
+
+    #[test]
+    fn run_empty_test() {
+        let results =
+            WorkerPool::new(4).run(build_collection(0), |_, _| Ok(()));
+
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn run_respects_concurrency_test() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let results = WorkerPool::new(3).run(build_collection(12), |_, _| {
+            let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(())
+        });
+
+        assert_eq!(12, results.len());
+        assert!(max_running.load(Ordering::SeqCst) <= 3);
+    }
+}
@@ -0,0 +1,4 @@
+mod snapshot_store;
+mod timestamp;
+
+pub use self::snapshot_store::SnapshotStore;
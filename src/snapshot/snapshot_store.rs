@@ -0,0 +1,307 @@
+use super::timestamp::{format_compact, parse_age};
+use crate::configuration::configuration_namespace;
+use crate::error::Error;
+use crate::information::InformationCollection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Information of all hosts at one point in time
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    /// Identifier of the snapshot in the store (or the path it was read from)
+    #[serde(skip)]
+    id: String,
+
+    /// Unix timestamp of the moment the snapshot was taken
+    timestamp: u64,
+    hosts: InformationCollection,
+}
+
+impl Snapshot {
+    pub fn new(time: SystemTime, hosts: InformationCollection) -> Self {
+        let timestamp = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Snapshot {
+            id: format_compact(timestamp),
+            timestamp,
+            hosts,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn into_hosts(self) -> InformationCollection {
+        self.hosts
+    }
+}
+
+/// Directory of snapshots
+///
+/// Each snapshot is stored as a JSON file named after the UTC time it was
+/// taken (e.g. `20261018T120000Z.json`), so that the names sort
+/// chronologically. Snapshots of different configuration files are stored in
+/// separate directories
+pub struct SnapshotStore {
+    directory: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(directory: PathBuf) -> Self {
+        SnapshotStore { directory }
+    }
+
+    /// Open the store of the configuration file in the user's data directory
+    /// (e.g. `$XDG_DATA_HOME/fleet/snapshots/fleet-6c5fe1d7a0b2c413`)
+    pub fn open_default(configuration_file: &Path) -> Result<Self, Error> {
+        match dirs::data_dir() {
+            Some(mut directory) => {
+                directory.push("fleet");
+                directory.push("snapshots");
+                directory.push(configuration_namespace(configuration_file));
+
+                Ok(SnapshotStore::new(directory))
+            }
+            None => Err(Error::new("Could not detect the data directory")),
+        }
+    }
+
+    /// Save a snapshot of the hosts with the current time and return it
+    pub fn save(
+        &self,
+        hosts: InformationCollection,
+    ) -> Result<Snapshot, Error> {
+        let mut snapshot = Snapshot::new(SystemTime::now(), hosts);
+        let content = match serde_json::to_string(&snapshot) {
+            Ok(content) => content,
+            Err(e) => return Err(Error::from_error(&e)),
+        };
+
+        fs::create_dir_all(&self.directory)?;
+
+        // Never overwrite a snapshot taken within the same second
+        let base_id = snapshot.id.clone();
+        let mut counter = 1;
+        while self.path_for_id(&snapshot.id).exists() {
+            snapshot.id = format!("{}-{}", base_id, counter);
+            counter += 1;
+        }
+
+        let path = self.path_for_id(&snapshot.id);
+        let temporary_path = path.with_extension("json.tmp");
+        fs::write(&temporary_path, content)?;
+        fs::rename(&temporary_path, &path)?;
+
+        Ok(snapshot)
+    }
+
+    /// Return the identifiers of all snapshots, oldest first
+    pub fn list(&self) -> Result<Vec<String>, Error> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        let mut ids = vec![];
+        for entry in entries {
+            let file_name = entry?.file_name();
+            if let Some(id) = file_name.to_string_lossy().strip_suffix(".json")
+            {
+                ids.push(id.to_owned());
+            }
+        }
+        ids.sort();
+
+        Ok(ids)
+    }
+
+    /// Load the snapshot with the given identifier
+    pub fn load(&self, id: &str) -> Result<Snapshot, Error> {
+        let mut snapshot = load_file(&self.path_for_id(id))?;
+        snapshot.id = id.to_owned();
+
+        Ok(snapshot)
+    }
+
+    /// Load the snapshot described by the reference
+    ///
+    /// The reference may be
+    ///
+    /// - `latest` or `latest~N` for the newest snapshot or the N-th before it
+    /// - an age like `7d` or `12h` for the newest snapshot taken at least that
+    ///   long ago
+    /// - the identifier of a snapshot
+    /// - the path to a snapshot file
+    pub fn resolve(&self, reference: &str) -> Result<Snapshot, Error> {
+        if let Some(offset) = parse_latest(reference) {
+            let ids = self.list()?;
+            return match ids.len().checked_sub(offset + 1) {
+                Some(index) => self.load(&ids[index]),
+                None => Err(Error::new(format!(
+                    "Snapshot \"{}\" not found: there are only {} snapshots",
+                    reference,
+                    ids.len()
+                ))),
+            };
+        }
+
+        if let Some(age) = parse_age(reference) {
+            let cutoff = SystemTime::now()
+                .checked_sub(age)
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .unwrap_or_default()
+                .as_secs();
+            let cutoff_id = format_compact(cutoff);
+
+            // Identifiers start with the compact time, so text comparison
+            // orders them chronologically
+            let ids = self.list()?;
+            return match ids
+                .iter()
+                .rev()
+                .find(|id| id.get(..cutoff_id.len()) <= Some(&cutoff_id))
+            {
+                Some(id) => self.load(id),
+                None => Err(Error::new(format!(
+                    "No snapshot older than {} found",
+                    reference
+                ))),
+            };
+        }
+
+        if self.path_for_id(reference).is_file() {
+            return self.load(reference);
+        }
+
+        let path = Path::new(reference);
+        if path.is_file() {
+            let mut snapshot = load_file(path)?;
+            snapshot.id = reference.to_owned();
+
+            return Ok(snapshot);
+        }
+
+        Err(Error::new(format!("Snapshot \"{}\" not found", reference)))
+    }
+
+    fn path_for_id(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", id))
+    }
+}
+
+fn load_file(path: &Path) -> Result<Snapshot, Error> {
+    let content = fs::read_to_string(path)?;
+    match serde_json::from_str(&content) {
+        Ok(snapshot) => Ok(snapshot),
+        Err(e) => Err(Error::new(format!(
+            "Could not read the snapshot file '{}': {}",
+            path.to_string_lossy(),
+            e
+        ))),
+    }
+}
+
+/// Parse `latest` or `latest~N` and return N
+fn parse_latest(reference: &str) -> Option<usize> {
+    match reference.strip_prefix("latest")? {
+        "" => Some(0),
+        offset => offset.strip_prefix('~')?.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::information::Information;
+    use std::time::Duration;
+
+    fn build_store(name: &str) -> SnapshotStore {
+        let directory = std::env::temp_dir().join(format!(
+            "fleet-snapshot-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+
+        SnapshotStore::new(directory)
+    }
+
+    fn write_snapshot(store: &SnapshotStore, timestamp: u64, host: &str) {
+        let mut hosts = InformationCollection::new();
+        hosts.insert(host.to_owned(), Information::new_for_current_env());
+        let snapshot =
+            Snapshot::new(UNIX_EPOCH + Duration::from_secs(timestamp), hosts);
+
+        fs::create_dir_all(&store.directory).unwrap();
+        fs::write(
+            store.path_for_id(snapshot.id()),
+            serde_json::to_string(&snapshot).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn parse_latest_test() {
+        assert_eq!(Some(0), parse_latest("latest"));
+        assert_eq!(Some(2), parse_latest("latest~2"));
+        assert_eq!(None, parse_latest("latest~"));
+        assert_eq!(None, parse_latest("latest2"));
+        assert_eq!(None, parse_latest("7d"));
+    }
+
+    #[test]
+    fn save_and_list_test() {
+        let store = build_store("save");
+        assert!(store.list().unwrap().is_empty());
+
+        let first = store.save(InformationCollection::new()).unwrap();
+        let second = store.save(InformationCollection::new()).unwrap();
+        assert_ne!(first.id(), second.id());
+
+        let ids = store.list().unwrap();
+        assert_eq!(vec![first.id().to_owned(), second.id().to_owned()], ids);
+        assert_eq!(first, store.load(first.id()).unwrap());
+
+        let _ = fs::remove_dir_all(&store.directory);
+    }
+
+    #[test]
+    fn resolve_test() {
+        let store = build_store("resolve");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        write_snapshot(&store, now - 10 * 86400, "ten-days");
+        write_snapshot(&store, now - 86400, "one-day");
+        write_snapshot(&store, now - 60, "one-minute");
+
+        let hosts_of = |reference: &str| -> Vec<String> {
+            store
+                .resolve(reference)
+                .unwrap()
+                .into_hosts()
+                .into_keys()
+                .collect()
+        };
+        assert_eq!(vec!["one-minute"], hosts_of("latest"));
+        assert_eq!(vec!["one-day"], hosts_of("latest~1"));
+        assert_eq!(vec!["one-day"], hosts_of("12h"));
+        assert_eq!(vec!["ten-days"], hosts_of("7d"));
+        assert_eq!(vec!["ten-days"], hosts_of(&format_compact(now - 864000)));
+
+        assert!(store.resolve("latest~3").is_err());
+        assert!(store.resolve("30d").is_err());
+        assert!(store.resolve("unknown").is_err());
+
+        let _ = fs::remove_dir_all(&store.directory);
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+/// Format the Unix timestamp as compact UTC date and time
+/// (e.g. `20261018T120000Z`), which sorts chronologically as text
+pub fn format_compact(timestamp: u64) -> String {
+    let (year, month, day, hour, minute, second) = split(timestamp);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Parse an age like `90s`, `30m`, `12h`, `7d` or `2w`
+pub fn parse_age(source: &str) -> Option<Duration> {
+    let unit_position = source.len().checked_sub(1)?;
+    if !source.is_char_boundary(unit_position) {
+        return None;
+    }
+    let (number, unit) = source.split_at(unit_position);
+    let number: u64 = number.parse().ok()?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+
+    Some(Duration::from_secs(number.checked_mul(seconds)?))
+}
+
+/// Split the Unix timestamp into the UTC year, month, day, hour, minute and
+/// second
+fn split(timestamp: u64) -> (u64, u64, u64, u64, u64, u64) {
+    let days = timestamp / 86400;
+    let seconds_of_day = timestamp % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    (
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+    )
+}
+
+/// Convert the number of days since 1970-01-01 into a Gregorian date
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_test() {
+        assert_eq!("19700101T000000Z", format_compact(0));
+        assert_eq!("20000229T235959Z", format_compact(951_868_799));
+        assert_eq!("20261018T120000Z", format_compact(1_792_324_800));
+    }
+
+    #[test]
+    fn parse_age_test() {
+        assert_eq!(Some(Duration::from_secs(90)), parse_age("90s"));
+        assert_eq!(Some(Duration::from_secs(1800)), parse_age("30m"));
+        assert_eq!(Some(Duration::from_secs(43200)), parse_age("12h"));
+        assert_eq!(Some(Duration::from_secs(604_800)), parse_age("7d"));
+        assert_eq!(Some(Duration::from_secs(1_209_600)), parse_age("2w"));
+        assert_eq!(None, parse_age("7"));
+        assert_eq!(None, parse_age("d"));
+        assert_eq!(None, parse_age("latest"));
+        assert_eq!(None, parse_age(""));
+        assert_eq!(None, parse_age("1ä"));
+    }
+}